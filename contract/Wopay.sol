@@ -3,31 +3,131 @@ pragma solidity ^0.8.21;
 
 contract Wopay {
 
+    // 合约管理者 (wopay服务的运营账户)
+    address public owner;
+    // 订单签名者 (wopay服务创建订单时签名订单参数)
+    address public paymentSigner;
     // 已支付的订单
     mapping ( uint256 => bool ) public orderPaid;
+    // 商户可提款余额 (商户ID => 金额)
+    mapping ( uint256 => uint256 ) public merchantBalance;
+    // 商户登记的提款地址 (商户ID => 地址)
+    mapping ( uint256 => address ) public merchantPayout;
+
     // 支付事件
-    event PaymentReceived(uint256 indexed orderId, uint256 amount, address indexed sender);
+    event PaymentReceived(uint256 indexed orderId, uint256 indexed merchantId, uint256 amount, address indexed sender);
+    // 商户提款地址变更事件
+    event MerchantPayoutUpdated(uint256 indexed merchantId, address payout);
+    // 提款事件
+    event Withdrawal(uint256 indexed merchantId, address indexed payout, uint256 amount);
+    // 管理者变更事件
+    event OwnershipTransferred(address indexed previousOwner, address indexed newOwner);
+    // 订单签名者变更事件
+    event PaymentSignerUpdated(address indexed signer);
+
+    // secp256k1曲线阶的一半，签名的s值不得超过该值 (防止签名延展)
+    uint256 private constant SECP256K1_HALF_ORDER = 0x7FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF5D576E7357A4501DDFE92F46681B20A0;
 
-    // 可以接收转账
-    // 转账时需要传入订单号
-    // 收到转账时需要触发日志并记录订单号
-    // 订单号能否重复
-    // 需要有提款功能：1. 提款权限如何确定 2. 如果是多商户，如何设计提款
+    // 订单号由wopay服务随机分配，全局唯一，同一订单只能支付一次
+    // 商户ID为wopay商户UUID的128位整数形式，付款时计入该商户的余额
+    // 商户ID、订单号与订单金额由订单签名者签名，付款时校验签名，且付款金额不得低于订单金额
+    // 提款权限: 管理者或商户登记的提款地址可发起提款，资金只会转入商户登记的提款地址
+    // 提款地址只能由管理者登记 (wopay服务同步商户在后台配置的地址)
+
+    modifier onlyOwner() {
+        require(msg.sender == owner, "Caller is not the owner.");
+        _;
+    }
 
-    function receivePayment(uint256 orderId) external payable { 
+    constructor() {
+        owner = msg.sender;
+        paymentSigner = msg.sender;
+        emit OwnershipTransferred(address(0), msg.sender);
+        emit PaymentSignerUpdated(msg.sender);
+    }
+
+    function receivePayment(uint256 merchantId, uint256 orderId, uint256 amount, bytes calldata signature) external payable {
         // 订单必须未支付
         require(!orderPaid[orderId], "Order already paid.");
-        // 付款金额必须大于0
-        require(msg.value > 0, "Payment must be greater than 0.");
+        // 订单金额必须大于0，付款金额不得低于订单金额
+        require(amount > 0, "Payment must be greater than 0.");
+        require(msg.value >= amount, "Payment below order amount.");
+        // 订单参数必须由订单签名者签名
+        address signer = recoverSigner(paymentHash(merchantId, orderId, amount), signature);
+        require(signer != address(0) && signer == paymentSigner, "Invalid payment signature.");
         // 标记订单为已支付
         orderPaid[orderId] = true;
+        // 计入商户余额
+        merchantBalance[merchantId] += msg.value;
 
-        emit PaymentReceived(orderId, msg.value, msg.sender);
+        emit PaymentReceived(orderId, merchantId, msg.value, msg.sender);
     }
-    
-    
+
+    // 订单签名摘要，绑定合约地址与链ID防止签名跨合约、跨链重放
+    function paymentHash(uint256 merchantId, uint256 orderId, uint256 amount) public view returns (bytes32) {
+        return keccak256(abi.encode(address(this), block.chainid, merchantId, orderId, amount));
+    }
+
+    function setPaymentSigner(address signer) external onlyOwner {
+        require(signer != address(0), "Invalid signer address.");
+        paymentSigner = signer;
+
+        emit PaymentSignerUpdated(signer);
+    }
+
+    function setMerchantPayout(uint256 merchantId, address payout) external onlyOwner {
+        require(payout != address(0), "Invalid payout address.");
+        merchantPayout[merchantId] = payout;
+
+        emit MerchantPayoutUpdated(merchantId, payout);
+    }
+
+    function withdraw(uint256 merchantId, uint256 amount) external {
+        address payout = merchantPayout[merchantId];
+        // 商户必须已登记提款地址
+        require(payout != address(0), "Payout address not set.");
+        // 只有管理者或商户的提款地址可以发起提款
+        require(msg.sender == owner || msg.sender == payout, "Not authorized.");
+        require(amount > 0 && amount <= merchantBalance[merchantId], "Invalid withdrawal amount.");
+
+        // 先扣减余额再转账，防止重入
+        merchantBalance[merchantId] -= amount;
+        (bool success, ) = payout.call{value: amount}("");
+        require(success, "Transfer failed.");
+
+        emit Withdrawal(merchantId, payout, amount);
+    }
+
+    function transferOwnership(address newOwner) external onlyOwner {
+        require(newOwner != address(0), "Invalid owner address.");
+        emit OwnershipTransferred(owner, newOwner);
+        owner = newOwner;
+    }
+
     function balance() external view returns(uint256){
         return address(this).balance;
     }
 
-}
\ No newline at end of file
+    // 从EIP-191签名 (r, s, v 共65字节) 恢复签名者，签名无效时返回零地址
+    function recoverSigner(bytes32 hash, bytes calldata signature) internal pure returns (address) {
+        if (signature.length != 65) {
+            return address(0);
+        }
+
+        bytes32 r;
+        bytes32 s;
+        uint8 v;
+        assembly {
+            r := calldataload(signature.offset)
+            s := calldataload(add(signature.offset, 32))
+            v := byte(0, calldataload(add(signature.offset, 64)))
+        }
+        if (uint256(s) > SECP256K1_HALF_ORDER || (v != 27 && v != 28)) {
+            return address(0);
+        }
+
+        bytes32 digest = keccak256(abi.encodePacked("\x19Ethereum Signed Message:\n32", hash));
+        return ecrecover(digest, v, r, s);
+    }
+
+}
@@ -0,0 +1,7 @@
+-- 观察钱包模式
+-- API进程只持有xpub派生地址，私钥由签名器按address_index重新派生，因此不再强制存储私钥
+
+ALTER TABLE payment_addresses ALTER COLUMN private_key_encrypted DROP NOT NULL;
+
+COMMENT ON COLUMN payment_addresses.address_index IS 'BIP44派生索引 (m/44''/60''/0''/0/{index})';
+COMMENT ON COLUMN payment_addresses.private_key_encrypted IS '加密私钥 (观察钱包模式下为空)';
@@ -0,0 +1,10 @@
+-- 私钥加密存储
+-- private_key_encrypted 使用AES-256-GCM加密 (十六进制编码的 nonce || 密文)，
+-- key_version 记录加密所用的密钥版本，便于在线轮换密钥加密密钥 (KEK)
+
+ALTER TABLE payment_addresses ADD COLUMN key_version INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_payment_addresses_key_version ON payment_addresses(key_version)
+    WHERE private_key_encrypted IS NOT NULL;
+
+COMMENT ON COLUMN payment_addresses.key_version IS '私钥加密密钥版本 (0表示早期未加密的十六进制私钥，需通过 wopay rotate-keys 重新加密)';
@@ -0,0 +1,15 @@
+-- HD地址索引计数器
+-- 地址索引由数据库原子分配，多实例并发生成地址时不会重复；
+-- 计数器在生成地址的事务内更新，事务回滚时索引一并回滚，不产生空洞
+
+CREATE TABLE hd_address_counter (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    next_index INTEGER NOT NULL CHECK (next_index >= 0),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- 从已分配的最大索引继续
+INSERT INTO hd_address_counter (next_index)
+SELECT COALESCE(MAX(address_index) + 1, 0) FROM payment_addresses;
+
+CREATE UNIQUE INDEX idx_payment_addresses_address_index ON payment_addresses(address_index);
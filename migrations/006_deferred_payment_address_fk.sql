@@ -0,0 +1,5 @@
+-- 支付地址与支付订单在同一事务中创建
+-- 地址需先于订单分配 (订单记录包含支付地址)，因此外键检查延迟到事务提交时进行
+
+ALTER TABLE payment_addresses
+    ALTER CONSTRAINT payment_addresses_payment_id_fkey DEFERRABLE INITIALLY DEFERRED;
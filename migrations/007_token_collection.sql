@@ -0,0 +1,11 @@
+-- ERC20代币归集
+-- 归集代币前需由gas钱包向充值地址补充ETH，两笔交易都记录在collection_transactions中
+
+ALTER TABLE collection_transactions
+    ADD COLUMN currency VARCHAR(10) NOT NULL DEFAULT 'ETH' CHECK (currency IN ('ETH', 'USDT')),
+    ADD COLUMN tx_type VARCHAR(20) NOT NULL DEFAULT 'sweep' CHECK (tx_type IN ('sweep', 'gas_topup'));
+
+COMMENT ON COLUMN collection_transactions.amount IS '转账数量 (币种单位，非最小单位)';
+COMMENT ON COLUMN collection_transactions.tx_type IS 'sweep: 充值地址归集到主地址; gas_topup: gas钱包向充值地址补充ETH';
+
+CREATE INDEX idx_collection_transactions_to_address ON collection_transactions(to_address);
@@ -0,0 +1,22 @@
+-- 归集交易跟踪
+-- 保存nonce和完整交易参数，超时未上链的交易可用相同nonce提高费用替换 (replace-by-fee)
+
+ALTER TABLE collection_transactions
+    ADD COLUMN nonce BIGINT,
+    ADD COLUMN gas_limit BIGINT,
+    ADD COLUMN max_fee_per_gas VARCHAR(32),
+    ADD COLUMN max_priority_fee_per_gas VARCHAR(32),
+    ADD COLUMN tx_to VARCHAR(42),
+    ADD COLUMN tx_value VARCHAR(78),
+    ADD COLUMN tx_data TEXT,
+    ADD COLUMN replaced_by VARCHAR(66);
+
+ALTER TABLE collection_transactions
+    ADD CONSTRAINT collection_transactions_status_check
+    CHECK (status IN ('pending', 'confirmed', 'failed', 'replaced', 'dropped'));
+
+COMMENT ON COLUMN collection_transactions.status IS 'pending: 待上链; confirmed: 成功; failed: 执行失败; replaced: 已被同nonce交易替换; dropped: nonce已被其他交易使用';
+COMMENT ON COLUMN collection_transactions.tx_to IS '交易的to字段 (代币归集为合约地址)';
+
+CREATE INDEX idx_collection_transactions_status ON collection_transactions(status);
+CREATE INDEX idx_collection_transactions_nonce ON collection_transactions(from_address, nonce);
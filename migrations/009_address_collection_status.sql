@@ -0,0 +1,25 @@
+-- 支付地址归集状态机
+-- active: 等待归集; sweeping: 归集交易已广播; swept: 归集交易已确认; sweep_failed: 归集交易失败或被丢弃，等待重试
+-- 已归集地址会被定期重新扫描，收到迟到或重复付款时重新置为active
+
+ALTER TABLE payment_addresses
+    ADD COLUMN collection_status VARCHAR(20) NOT NULL DEFAULT 'active'
+        CHECK (collection_status IN ('active', 'sweeping', 'swept', 'sweep_failed')),
+    ADD COLUMN swept_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN last_scanned_at TIMESTAMP WITH TIME ZONE;
+
+-- 早期记录在广播时即标记为已归集，迁移为sweeping，由交易回执确认最终状态
+UPDATE payment_addresses SET collection_status = 'sweeping' WHERE is_collected = true;
+UPDATE payment_addresses pa SET collection_status = 'swept', swept_at = ct.updated_at
+FROM collection_transactions ct
+WHERE ct.from_address = pa.address AND ct.tx_type = 'sweep' AND ct.status = 'confirmed'
+  AND pa.collection_status = 'sweeping';
+-- 没有待确认归集交易的地址 (交易失败或已丢弃) 重新归集
+UPDATE payment_addresses pa SET collection_status = 'sweep_failed', is_collected = false
+WHERE pa.collection_status = 'sweeping'
+  AND NOT EXISTS (
+      SELECT 1 FROM collection_transactions ct
+      WHERE ct.from_address = pa.address AND ct.tx_type = 'sweep' AND ct.status = 'pending'
+  );
+
+CREATE INDEX idx_payment_addresses_collection_status ON payment_addresses(collection_status);
@@ -0,0 +1,17 @@
+-- 区块扫描游标
+-- 记录各扫描任务最近处理的区块号与哈希，重启后从游标继续扫描，
+-- 并通过父哈希校验检测区块重组
+
+CREATE TABLE chain_cursor (
+    name VARCHAR(50) NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_hash VARCHAR(66) NOT NULL,
+    parent_hash VARCHAR(66) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (name, block_number)
+);
+
+-- 记录到账交易所在区块哈希，用于回滚孤块中的交易
+ALTER TABLE blockchain_transactions ADD COLUMN block_hash VARCHAR(66);
+
+COMMENT ON TABLE chain_cursor IS '区块扫描游标 (保留最近区块哈希用于重组检测)';
@@ -0,0 +1,29 @@
+-- 少付/多付处理
+-- 支付订单记录累计到账金额，按商户容差判断少付 (underpaid) 或多付 (overpaid)
+
+ALTER TABLE payments
+    ADD COLUMN amount_received DECIMAL(36,18) NOT NULL DEFAULT 0;
+
+ALTER TABLE payments DROP CONSTRAINT payments_status_check;
+ALTER TABLE payments ADD CONSTRAINT payments_status_check
+    CHECK (status IN ('pending', 'underpaid', 'confirmed', 'completed', 'overpaid', 'expired', 'failed'));
+
+-- 已有订单按成功交易汇总到账金额
+UPDATE payments p SET amount_received = bt.total
+FROM (
+    SELECT payment_id, SUM(amount) AS total
+    FROM blockchain_transactions
+    WHERE status = 'confirmed'
+    GROUP BY payment_id
+) bt
+WHERE bt.payment_id = p.id;
+
+-- 商户付款容差: absolute为币种单位的固定金额，percentage为订单金额的百分比
+ALTER TABLE merchants
+    ADD COLUMN payment_tolerance_type VARCHAR(20) NOT NULL DEFAULT 'absolute'
+        CHECK (payment_tolerance_type IN ('absolute', 'percentage')),
+    ADD COLUMN payment_tolerance DECIMAL(36,18) NOT NULL DEFAULT 0
+        CHECK (payment_tolerance >= 0);
+
+COMMENT ON COLUMN payments.amount_received IS '累计到账金额';
+COMMENT ON COLUMN merchants.payment_tolerance IS '付款金额容差';
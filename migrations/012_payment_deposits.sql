@@ -0,0 +1,13 @@
+-- 一笔支付订单支持多笔到账
+-- blockchain_transactions作为订单到账记录的唯一来源，同一交易中的多笔代币转账按日志序号区分，
+-- 原生币内部转账没有日志序号，同一交易转入多个收款地址 (如交易所批量提现) 时按收款地址区分
+
+ALTER TABLE blockchain_transactions
+    ADD COLUMN log_index BIGINT NOT NULL DEFAULT -1;
+
+ALTER TABLE blockchain_transactions DROP CONSTRAINT blockchain_transactions_transaction_hash_key;
+ALTER TABLE blockchain_transactions
+    ADD CONSTRAINT blockchain_transactions_transfer_key UNIQUE (transaction_hash, log_index, to_address);
+
+COMMENT ON COLUMN blockchain_transactions.log_index IS '代币Transfer事件日志序号 (原生ETH转账为-1)';
+COMMENT ON COLUMN payments.transaction_hash IS '最近一笔到账交易哈希 (完整到账记录见blockchain_transactions)';
@@ -0,0 +1,21 @@
+-- 过期订单迟到付款
+-- 订单过期后在宽限期内继续监听收款地址，迟到的到账标记为paid_after_expiry，由商户接受或退款
+
+ALTER TABLE payments DROP CONSTRAINT payments_status_check;
+ALTER TABLE payments ADD CONSTRAINT payments_status_check
+    CHECK (status IN (
+        'pending', 'underpaid', 'confirmed', 'completed', 'overpaid',
+        'expired', 'paid_after_expiry', 'refunded', 'failed'
+    ));
+
+-- 商户对迟到付款的处理: accepted按正常订单结算，refunded退回到refund_address
+ALTER TABLE payments
+    ADD COLUMN late_payment_action VARCHAR(20)
+        CHECK (late_payment_action IN ('accepted', 'refunded')),
+    ADD COLUMN late_payment_resolved_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN refund_address VARCHAR(42);
+
+CREATE INDEX idx_payments_expired_at ON payments(expires_at) WHERE status IN ('expired', 'paid_after_expiry');
+
+COMMENT ON COLUMN payments.late_payment_action IS '商户对迟到付款的处理 (accepted/refunded)';
+COMMENT ON COLUMN payments.refund_address IS '迟到付款退款地址 (归集时资金转入该地址)';
@@ -0,0 +1,22 @@
+-- 确认数规则
+-- 商户按币种和金额档位配置确认数，创建订单时解析为订单的required_confirmations
+
+CREATE TABLE merchant_confirmation_rules (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    merchant_id UUID NOT NULL REFERENCES merchants(id) ON DELETE CASCADE,
+    currency VARCHAR(10) NOT NULL CHECK (currency IN ('ETH', 'USDT')),
+    min_amount DECIMAL(36,18) NOT NULL DEFAULT 0 CHECK (min_amount >= 0),
+    confirmations INTEGER NOT NULL CHECK (confirmations >= 1),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (merchant_id, currency, min_amount)
+);
+
+CREATE INDEX idx_merchant_confirmation_rules_merchant ON merchant_confirmation_rules(merchant_id);
+
+-- 已有订单沿用之前主网的固定确认数
+ALTER TABLE payments
+    ADD COLUMN required_confirmations INTEGER NOT NULL DEFAULT 12 CHECK (required_confirmations >= 1);
+ALTER TABLE payments ALTER COLUMN required_confirmations DROP DEFAULT;
+
+COMMENT ON TABLE merchant_confirmation_rules IS '商户确认数规则 (订单金额达到min_amount的最高一档生效)';
+COMMENT ON COLUMN payments.required_confirmations IS '订单完成所需的区块确认数';
@@ -0,0 +1,27 @@
+-- 零确认风险规则
+-- 商户按币种配置零确认上限金额，内存池中检测到的付款交易将订单标记为detected，
+-- 订单仍需达到正常确认数才会完成
+
+ALTER TABLE payments DROP CONSTRAINT payments_status_check;
+ALTER TABLE payments ADD CONSTRAINT payments_status_check
+    CHECK (status IN (
+        'pending', 'detected', 'underpaid', 'confirmed', 'completed', 'overpaid',
+        'expired', 'paid_after_expiry', 'refunded', 'failed'
+    ));
+
+CREATE TABLE merchant_zero_conf_rules (
+    merchant_id UUID NOT NULL REFERENCES merchants(id) ON DELETE CASCADE,
+    currency VARCHAR(10) NOT NULL CHECK (currency IN ('ETH', 'USDT')),
+    max_amount DECIMAL(36,18) NOT NULL CHECK (max_amount > 0),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (merchant_id, currency)
+);
+
+ALTER TABLE payments
+    ADD COLUMN detected_tx_hash VARCHAR(66),
+    ADD COLUMN detected_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX idx_payments_detected_at ON payments(detected_at) WHERE status = 'detected';
+
+COMMENT ON TABLE merchant_zero_conf_rules IS '商户零确认规则 (订单金额不超过max_amount时允许零确认展示)';
+COMMENT ON COLUMN payments.detected_tx_hash IS '内存池中检测到的付款交易哈希';
@@ -0,0 +1,56 @@
+-- 多链支持
+-- 订单记录所在链，同一币种可在不同链上收款 (如BSC与Polygon上的USDT)，
+-- 各链的扫描游标与归集交易分开记录
+
+ALTER TABLE payments
+    ADD COLUMN chain VARCHAR(20) NOT NULL DEFAULT 'ethereum'
+    CHECK (chain IN ('ethereum', 'bsc', 'polygon', 'arbitrum', 'base'));
+
+-- 新增各链原生币
+ALTER TABLE payments DROP CONSTRAINT payments_currency_check;
+ALTER TABLE payments ADD CONSTRAINT payments_currency_check
+    CHECK (currency IN ('ETH', 'USDT', 'BNB', 'POL'));
+
+ALTER TABLE merchant_confirmation_rules DROP CONSTRAINT merchant_confirmation_rules_currency_check;
+ALTER TABLE merchant_confirmation_rules ADD CONSTRAINT merchant_confirmation_rules_currency_check
+    CHECK (currency IN ('ETH', 'USDT', 'BNB', 'POL'));
+
+ALTER TABLE merchant_zero_conf_rules DROP CONSTRAINT merchant_zero_conf_rules_currency_check;
+ALTER TABLE merchant_zero_conf_rules ADD CONSTRAINT merchant_zero_conf_rules_currency_check
+    CHECK (currency IN ('ETH', 'USDT', 'BNB', 'POL'));
+
+-- 确认数与零确认规则按链配置 (各链出块与最终性差异较大)，已有规则归属以太坊主网
+ALTER TABLE merchant_confirmation_rules
+    ADD COLUMN chain VARCHAR(20) NOT NULL DEFAULT 'ethereum'
+    CHECK (chain IN ('ethereum', 'bsc', 'polygon', 'arbitrum', 'base'));
+ALTER TABLE merchant_confirmation_rules ALTER COLUMN chain DROP DEFAULT;
+ALTER TABLE merchant_confirmation_rules DROP CONSTRAINT merchant_confirmation_rules_merchant_id_currency_min_amount_key;
+ALTER TABLE merchant_confirmation_rules ADD CONSTRAINT merchant_confirmation_rules_merchant_chain_key
+    UNIQUE (merchant_id, chain, currency, min_amount);
+
+ALTER TABLE merchant_zero_conf_rules
+    ADD COLUMN chain VARCHAR(20) NOT NULL DEFAULT 'ethereum'
+    CHECK (chain IN ('ethereum', 'bsc', 'polygon', 'arbitrum', 'base'));
+ALTER TABLE merchant_zero_conf_rules ALTER COLUMN chain DROP DEFAULT;
+ALTER TABLE merchant_zero_conf_rules DROP CONSTRAINT merchant_zero_conf_rules_pkey;
+ALTER TABLE merchant_zero_conf_rules ADD PRIMARY KEY (merchant_id, chain, currency);
+
+ALTER TABLE collection_transactions DROP CONSTRAINT collection_transactions_currency_check;
+ALTER TABLE collection_transactions ADD CONSTRAINT collection_transactions_currency_check
+    CHECK (currency IN ('ETH', 'USDT', 'BNB', 'POL'));
+
+-- 归集交易的nonce按链独立
+ALTER TABLE collection_transactions
+    ADD COLUMN chain VARCHAR(20) NOT NULL DEFAULT 'ethereum';
+
+-- 已有游标归属以太坊主网
+UPDATE chain_cursor SET name = 'ethereum:native_deposits' WHERE name = 'eth_deposits';
+UPDATE chain_cursor SET name = 'ethereum:' || name WHERE name NOT LIKE '%:%';
+
+CREATE INDEX idx_payments_chain_status ON payments(chain, status);
+CREATE INDEX idx_collection_transactions_chain ON collection_transactions(chain, status);
+
+COMMENT ON COLUMN payments.chain IS '收款链 (ethereum, bsc, polygon, arbitrum, base)';
+COMMENT ON COLUMN collection_transactions.chain IS '归集交易所在链';
+COMMENT ON COLUMN merchant_confirmation_rules.chain IS '规则适用的链';
+COMMENT ON COLUMN merchant_zero_conf_rules.chain IS '规则适用的链';
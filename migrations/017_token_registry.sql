@@ -0,0 +1,48 @@
+-- 代币登记表
+-- 各链可收款代币的合约地址、精度与金额限制改由数据库维护，新增代币 (如USDC、DAI)
+-- 只需通过管理接口登记，不再需要修改代码或币种CHECK约束
+
+CREATE TABLE tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    chain VARCHAR(20) NOT NULL CHECK (chain IN ('ethereum', 'bsc', 'polygon', 'arbitrum', 'base')),
+    symbol VARCHAR(10) NOT NULL,
+    contract_address VARCHAR(42),
+    decimals INTEGER NOT NULL CHECK (decimals BETWEEN 0 AND 36),
+    min_amount DECIMAL(36,18) NOT NULL CHECK (min_amount > 0),
+    max_amount DECIMAL(36,18) NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (chain, symbol),
+    CHECK (max_amount >= min_amount)
+);
+
+-- 每条链只能登记一个原生代币，同一合约不能重复登记
+CREATE UNIQUE INDEX idx_tokens_chain_native ON tokens(chain) WHERE contract_address IS NULL;
+CREATE UNIQUE INDEX idx_tokens_chain_contract ON tokens(chain, LOWER(contract_address)) WHERE contract_address IS NOT NULL;
+
+CREATE TRIGGER update_tokens_updated_at BEFORE UPDATE ON tokens
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- 原有硬编码的币种
+INSERT INTO tokens (chain, symbol, contract_address, decimals, min_amount, max_amount) VALUES
+    ('ethereum', 'ETH', NULL, 18, 0.0001, 1000),
+    ('arbitrum', 'ETH', NULL, 18, 0.0001, 1000),
+    ('base', 'ETH', NULL, 18, 0.0001, 1000),
+    ('bsc', 'BNB', NULL, 18, 0.0001, 10000),
+    ('polygon', 'POL', NULL, 18, 0.01, 10000000),
+    ('ethereum', 'USDT', '0xdAC17F958D2ee523a2206206994597C13D831ec7', 6, 0.01, 1000000),
+    ('bsc', 'USDT', '0x55d398326f99059fF775485246999027B3197955', 18, 0.01, 1000000),
+    ('polygon', 'USDT', '0xc2132D05D31c914a87C6611C10748AEb04B58e8F', 6, 0.01, 1000000),
+    ('arbitrum', 'USDT', '0xFd086bC7CD5C481DCC9C85ebE478A1C0b69FCbb9', 6, 0.01, 1000000),
+    ('base', 'USDT', '0xfde4C96c8593536E31F229EA8f37b2ADa2699bb2', 6, 0.01, 1000000);
+
+-- 币种取值由tokens表约束 (创建订单与规则时校验)，移除固定的CHECK
+ALTER TABLE payments DROP CONSTRAINT payments_currency_check;
+ALTER TABLE merchant_confirmation_rules DROP CONSTRAINT merchant_confirmation_rules_currency_check;
+ALTER TABLE merchant_zero_conf_rules DROP CONSTRAINT merchant_zero_conf_rules_currency_check;
+ALTER TABLE collection_transactions DROP CONSTRAINT collection_transactions_currency_check;
+
+COMMENT ON TABLE tokens IS '各链可收款代币登记';
+COMMENT ON COLUMN tokens.contract_address IS 'ERC-20合约地址 (原生代币为空)';
+COMMENT ON COLUMN tokens.enabled IS '是否可用于新建订单 (停用后已有订单仍会继续扫描)';
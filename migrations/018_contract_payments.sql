@@ -0,0 +1,17 @@
+-- 合约收款模式
+-- 订单可选择通过Wopay合约的receivePayment(orderId)付款，不再分配独立收款地址，
+-- 到账由PaymentReceived事件检测
+
+CREATE SEQUENCE contract_order_id_seq START 1;
+
+ALTER TABLE payments
+    ADD COLUMN payment_mode VARCHAR(20) NOT NULL DEFAULT 'address'
+        CHECK (payment_mode IN ('address', 'contract')),
+    ADD COLUMN contract_order_id BIGINT UNIQUE,
+    ADD CONSTRAINT payments_contract_order_id_check
+        CHECK ((payment_mode = 'contract') = (contract_order_id IS NOT NULL));
+
+ALTER TABLE payments ALTER COLUMN payment_mode DROP DEFAULT;
+
+COMMENT ON COLUMN payments.payment_mode IS '收款模式 (address: 独立收款地址, contract: Wopay合约)';
+COMMENT ON COLUMN payments.contract_order_id IS '合约收款模式下传给receivePayment的链上订单号';
@@ -0,0 +1,29 @@
+-- 合约提款
+-- 合约收款计入商户在Wopay合约中的余额，商户登记提款地址后可提款到该地址
+
+ALTER TABLE merchants ADD COLUMN payout_address VARCHAR(42);
+
+COMMENT ON COLUMN merchants.payout_address IS '合约收款的提款地址 (提款前同步登记到各链的Wopay合约)';
+
+CREATE TABLE contract_withdrawals (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    merchant_id UUID NOT NULL REFERENCES merchants(id),
+    chain VARCHAR(20) NOT NULL CHECK (chain IN ('ethereum', 'bsc', 'polygon', 'arbitrum', 'base')),
+    currency VARCHAR(10) NOT NULL,
+    amount DECIMAL(36,18) NOT NULL CHECK (amount > 0),
+    payout_address VARCHAR(42) NOT NULL,
+    tx_hash VARCHAR(66) NOT NULL UNIQUE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'confirmed', 'failed')),
+    block_number BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON COLUMN contract_withdrawals.status IS 'pending: 待上链; confirmed: 成功; failed: 执行失败';
+
+CREATE INDEX idx_contract_withdrawals_merchant ON contract_withdrawals(merchant_id, created_at DESC);
+CREATE INDEX idx_contract_withdrawals_pending ON contract_withdrawals(chain) WHERE status = 'pending';
+
+CREATE TRIGGER update_contract_withdrawals_updated_at BEFORE UPDATE ON contract_withdrawals
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,9 @@
+-- 托管收银台
+-- 订单可指定收银台页面支付成功与取消后的跳转地址
+
+ALTER TABLE payments
+    ADD COLUMN success_url VARCHAR(500),
+    ADD COLUMN cancel_url VARCHAR(500);
+
+COMMENT ON COLUMN payments.success_url IS '收银台支付完成后的跳转地址';
+COMMENT ON COLUMN payments.cancel_url IS '收银台取消支付时的跳转地址';
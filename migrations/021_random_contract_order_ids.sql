@@ -0,0 +1,12 @@
+-- 合约链上订单号改为随机生成
+-- 自增序列的订单号可被预测，他人可抢先以该订单号调用合约占用订单；
+-- 改为服务端随机生成的256位整数，以十进制字符串存储
+
+ALTER TABLE payments
+    ALTER COLUMN contract_order_id TYPE VARCHAR(78) USING contract_order_id::TEXT,
+    ADD CONSTRAINT payments_contract_order_id_format_check
+        CHECK (contract_order_id ~ '^[0-9]{1,78}$');
+
+DROP SEQUENCE contract_order_id_seq;
+
+COMMENT ON COLUMN payments.contract_order_id IS '合约收款模式下传给receivePayment的链上订单号 (随机256位整数的十进制表示)';
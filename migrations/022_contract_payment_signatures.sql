@@ -0,0 +1,8 @@
+-- 合约收款订单签名
+-- receivePayment需要传入订单金额与服务端签名，合约校验签名后才接受付款，
+-- 付款人无法改动商户ID、订单号或低于订单金额付款
+
+ALTER TABLE payments
+    ADD COLUMN contract_signature VARCHAR(132);
+
+COMMENT ON COLUMN payments.contract_signature IS '合约收款模式下订单签名者对 (合约地址, 链ID, 商户ID, 订单号, 订单金额) 的EIP-191签名';
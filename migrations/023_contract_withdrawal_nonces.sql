@@ -0,0 +1,10 @@
+-- 提款交易nonce
+-- 记录提款交易的发送地址与nonce，交易被丢弃 (nonce被其他交易占用或超时仍未上链) 时标记为失败
+
+ALTER TABLE contract_withdrawals
+    ADD COLUMN sender_address VARCHAR(42),
+    ADD COLUMN nonce BIGINT;
+
+COMMENT ON COLUMN contract_withdrawals.sender_address IS '发送提款交易的合约管理者地址';
+COMMENT ON COLUMN contract_withdrawals.nonce IS '提款交易的nonce';
+COMMENT ON COLUMN contract_withdrawals.status IS 'pending: 待上链; confirmed: 成功; failed: 执行失败或交易被丢弃';
@@ -4,6 +4,7 @@
 use serde::{Deserialize, Serialize};
 use std::env;
 use anyhow::{Result, Context};
+use crate::models::Chain;
 
 /// 应用程序配置结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +19,8 @@ pub struct Config {
     pub security: SecurityConfig,
     /// Webhook配置
     pub webhook: WebhookConfig,
+    /// HD钱包配置
+    pub wallet: WalletConfig,
 }
 
 /// 服务器配置
@@ -51,12 +54,24 @@ pub struct DatabaseConfig {
 /// 区块链配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockchainConfig {
-    /// Ethereum配置
+    /// Ethereum配置 (gas钱包与归集交易参数)
     pub ethereum: EthereumConfig,
+    /// 启用的链 (第一个为默认链)
+    pub chains: Vec<ChainConfig>,
     /// 默认确认数要求
     pub default_confirmations: i32,
     /// 交易监听间隔 (秒)
     pub listener_interval: u64,
+    /// 订单过期后继续监听收款地址的宽限期 (分钟)
+    pub late_payment_grace_minutes: i32,
+    /// 是否启用内存池零确认检测 (需要WebSocket URL)
+    pub zero_conf_enabled: bool,
+    /// 零确认检测后未上链的超时时间 (分钟)，超时后订单恢复为待付款
+    pub zero_conf_timeout_minutes: i32,
+    /// Wopay合约管理者私钥 (登记商户提款地址与提交提款，未配置时禁用合约提款)
+    pub contract_owner_key: Option<String>,
+    /// Wopay合约订单签名者私钥 (与合约的paymentSigner一致，签名合约收款订单，未配置时禁用合约收款模式)
+    pub contract_signer_key: Option<String>,
 }
 
 /// Ethereum网络配置
@@ -76,6 +91,62 @@ pub struct EthereumConfig {
     pub gas_limit: u64,
 }
 
+/// 单条链的节点与确认数配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainConfig {
+    /// 链
+    pub chain: Chain,
+    /// RPC节点URL
+    pub rpc_url: String,
+    /// WebSocket URL
+    pub ws_url: Option<String>,
+    /// 链ID
+    pub chain_id: u64,
+    /// 默认确认数要求
+    pub default_confirmations: i32,
+    /// Wopay收款合约地址 (配置后支持合约收款模式)
+    pub payment_contract: Option<String>,
+}
+
+impl ChainConfig {
+    /// 从环境变量加载链配置
+    ///
+    /// 变量名以链名称大写为前缀，如 `BSC_RPC_URL`、`BSC_WS_URL`、`BSC_CHAIN_ID`、`BSC_CONFIRMATIONS`、`BSC_PAYMENT_CONTRACT`，
+    /// 链ID与确认数未配置时使用该链主网的默认值
+    ///
+    /// # Arguments
+    /// * `chain` - 链
+    /// * `default_confirmations` - 未配置且无主网默认值时的确认数
+    fn from_env(chain: Chain, default_confirmations: i32) -> Result<Self> {
+        let prefix = chain.as_str().to_uppercase();
+        let preset = crate::models::BlockchainConfig::for_chain(&chain);
+
+        // 以太坊沿用DEFAULT_CONFIRMATIONS，其他链使用各自主网的默认确认数
+        let default_confirmations = match chain {
+            Chain::Ethereum => default_confirmations,
+            _ => preset.required_confirmations,
+        };
+
+        Ok(ChainConfig {
+            chain,
+            rpc_url: env::var(format!("{}_RPC_URL", prefix))
+                .with_context(|| format!("{}_RPC_URL environment variable is required", prefix))?,
+            ws_url: env::var(format!("{}_WS_URL", prefix)).ok(),
+            chain_id: match env::var(format!("{}_CHAIN_ID", prefix)) {
+                Ok(chain_id) => chain_id.parse()
+                    .with_context(|| format!("Invalid {}_CHAIN_ID", prefix))?,
+                Err(_) => preset.chain_id,
+            },
+            default_confirmations: match env::var(format!("{}_CONFIRMATIONS", prefix)) {
+                Ok(confirmations) => confirmations.parse()
+                    .with_context(|| format!("Invalid {}_CONFIRMATIONS", prefix))?,
+                Err(_) => default_confirmations,
+            },
+            payment_contract: env::var(format!("{}_PAYMENT_CONTRACT", prefix)).ok(),
+        })
+    }
+}
+
 /// 安全配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecurityConfig {
@@ -85,6 +156,8 @@ pub struct SecurityConfig {
     pub api_key_length: usize,
     /// HMAC密钥长度
     pub hmac_key_length: usize,
+    /// 管理接口密钥 (未配置时禁用管理接口)
+    pub admin_api_key: Option<String>,
     /// 请求限流配置
     pub rate_limit: RateLimitConfig,
 }
@@ -111,11 +184,45 @@ pub struct WebhookConfig {
     pub concurrent_sends: usize,
 }
 
+/// HD钱包配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletConfig {
+    /// HD主密钥 (BIP39助记词或xprv扩展私钥)
+    pub hd_master_key: Option<String>,
+    /// BIP39助记词密码 (可选)
+    pub hd_passphrase: Option<String>,
+    /// 外部链扩展公钥 (观察钱包模式，API进程只需配置此项)
+    pub hd_account_xpub: Option<String>,
+    /// 归集目标地址
+    pub collection_address: Option<String>,
+    /// 归集阈值 (ETH)
+    pub collection_threshold: f64,
+    /// ERC20代币归集阈值 (代币单位)
+    pub collection_token_threshold: f64,
+    /// 归集间隔 (分钟)
+    pub collection_interval: u64,
+    /// 归集交易超时替换时间 (分钟)
+    pub collection_tx_timeout: u64,
+    /// 私钥加密密钥环 (逗号分隔的 `版本:十六进制AES-256密钥`)
+    pub key_encryption_keys: Option<String>,
+    /// 当前使用的加密密钥版本 (默认使用最大版本)
+    pub key_encryption_version: Option<i32>,
+    /// 签名私钥缓存容量 (地址数)
+    pub key_cache_size: usize,
+}
+
 impl Config {
     /// 从环境变量加载配置
     pub fn from_env() -> Result<Self> {
         dotenv::dotenv().ok(); // 加载.env文件，忽略错误
 
+        // 兼容旧的CONFIRMATION_BLOCKS变量名
+        let default_confirmations: i32 = env::var("DEFAULT_CONFIRMATIONS")
+            .or_else(|_| env::var("CONFIRMATION_BLOCKS"))
+            .unwrap_or_else(|_| "12".to_string())
+            .parse()
+            .context("Invalid DEFAULT_CONFIRMATIONS")?;
+
         Ok(Config {
             server: ServerConfig {
                 host: env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
@@ -152,6 +259,13 @@ impl Config {
                     .context("Invalid DB_IDLE_TIMEOUT")?,
             },
             blockchain: BlockchainConfig {
+                chains: env::var("ENABLED_CHAINS")
+                    .unwrap_or_else(|_| "ethereum".to_string())
+                    .split(',')
+                    .filter(|name| !name.trim().is_empty())
+                    .map(|name| ChainConfig::from_env(name.parse()?, default_confirmations))
+                    .collect::<Result<Vec<_>>>()
+                    .context("Invalid ENABLED_CHAINS")?,
                 ethereum: EthereumConfig {
                     rpc_url: env::var("ETHEREUM_RPC_URL")
                         .context("ETHEREUM_RPC_URL environment variable is required")?,
@@ -171,14 +285,25 @@ impl Config {
                         .parse()
                         .context("Invalid ETHEREUM_GAS_LIMIT")?,
                 },
-                default_confirmations: env::var("DEFAULT_CONFIRMATIONS")
-                    .unwrap_or_else(|_| "12".to_string())
-                    .parse()
-                    .context("Invalid DEFAULT_CONFIRMATIONS")?,
+                default_confirmations,
                 listener_interval: env::var("LISTENER_INTERVAL")
                     .unwrap_or_else(|_| "30".to_string())
                     .parse()
                     .context("Invalid LISTENER_INTERVAL")?,
+                late_payment_grace_minutes: env::var("LATE_PAYMENT_GRACE_MINUTES")
+                    .unwrap_or_else(|_| "60".to_string())
+                    .parse()
+                    .context("Invalid LATE_PAYMENT_GRACE_MINUTES")?,
+                zero_conf_enabled: env::var("ZERO_CONF_ENABLED")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .context("Invalid ZERO_CONF_ENABLED")?,
+                zero_conf_timeout_minutes: env::var("ZERO_CONF_TIMEOUT_MINUTES")
+                    .unwrap_or_else(|_| "30".to_string())
+                    .parse()
+                    .context("Invalid ZERO_CONF_TIMEOUT_MINUTES")?,
+                contract_owner_key: env::var("CONTRACT_OWNER_PRIVATE_KEY").ok(),
+                contract_signer_key: env::var("CONTRACT_SIGNER_PRIVATE_KEY").ok(),
             },
             security: SecurityConfig {
                 jwt_secret: env::var("JWT_SECRET")
@@ -191,6 +316,7 @@ impl Config {
                     .unwrap_or_else(|_| "64".to_string())
                     .parse()
                     .context("Invalid HMAC_KEY_LENGTH")?,
+                admin_api_key: env::var("ADMIN_API_KEY").ok(),
                 rate_limit: RateLimitConfig {
                     requests_per_minute: env::var("RATE_LIMIT_RPM")
                         .unwrap_or_else(|_| "100".to_string())
@@ -220,6 +346,38 @@ impl Config {
                     .parse()
                     .context("Invalid WEBHOOK_CONCURRENT_SENDS")?,
             },
+            wallet: WalletConfig {
+                hd_master_key: env::var("HD_MASTER_KEY").ok(),
+                hd_passphrase: env::var("HD_PASSPHRASE").ok(),
+                hd_account_xpub: env::var("HD_ACCOUNT_XPUB").ok(),
+                collection_address: env::var("COLLECTION_ADDRESS").ok(),
+                collection_threshold: env::var("COLLECTION_THRESHOLD")
+                    .unwrap_or_else(|_| "0.1".to_string())
+                    .parse()
+                    .context("Invalid COLLECTION_THRESHOLD")?,
+                collection_token_threshold: env::var("COLLECTION_TOKEN_THRESHOLD")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()
+                    .context("Invalid COLLECTION_TOKEN_THRESHOLD")?,
+                collection_interval: env::var("COLLECTION_INTERVAL_MINUTES")
+                    .unwrap_or_else(|_| "60".to_string())
+                    .parse()
+                    .context("Invalid COLLECTION_INTERVAL_MINUTES")?,
+                collection_tx_timeout: env::var("COLLECTION_TX_TIMEOUT_MINUTES")
+                    .unwrap_or_else(|_| "15".to_string())
+                    .parse()
+                    .context("Invalid COLLECTION_TX_TIMEOUT_MINUTES")?,
+                key_encryption_keys: env::var("WALLET_KEKS").ok(),
+                key_encryption_version: env::var("WALLET_KEK_VERSION")
+                    .ok()
+                    .map(|v| v.parse())
+                    .transpose()
+                    .context("Invalid WALLET_KEK_VERSION")?,
+                key_cache_size: env::var("WALLET_KEY_CACHE_SIZE")
+                    .unwrap_or_else(|_| "1000".to_string())
+                    .parse()
+                    .context("Invalid WALLET_KEY_CACHE_SIZE")?,
+            },
         })
     }
 
@@ -244,6 +402,41 @@ impl Config {
             anyhow::bail!("Ethereum private key cannot be empty");
         }
 
+        if self.blockchain.chains.is_empty() {
+            anyhow::bail!("ENABLED_CHAINS cannot be empty");
+        }
+
+        for (index, chain_config) in self.blockchain.chains.iter().enumerate() {
+            if self.blockchain.chains[..index].iter().any(|c| c.chain == chain_config.chain) {
+                anyhow::bail!("Chain {} is enabled more than once", chain_config.chain);
+            }
+            if chain_config.rpc_url.is_empty() {
+                anyhow::bail!("RPC URL for chain {} cannot be empty", chain_config.chain);
+            }
+            if !(1..=crate::models::MAX_REQUIRED_CONFIRMATIONS).contains(&chain_config.default_confirmations) {
+                anyhow::bail!("Default confirmations for chain {} must be between 1 and {}",
+                    chain_config.chain, crate::models::MAX_REQUIRED_CONFIRMATIONS);
+            }
+            if let Some(contract) = &chain_config.payment_contract {
+                if !crate::utils::validate_ethereum_address(contract) {
+                    anyhow::bail!("Invalid payment contract address for chain {}", chain_config.chain);
+                }
+            }
+        }
+
+        if let Some(key) = &self.blockchain.contract_owner_key {
+            key.parse::<ethers::signers::LocalWallet>()
+                .map_err(|_| anyhow::anyhow!("Invalid CONTRACT_OWNER_PRIVATE_KEY"))?;
+        }
+        if let Some(key) = &self.blockchain.contract_signer_key {
+            key.parse::<ethers::signers::LocalWallet>()
+                .map_err(|_| anyhow::anyhow!("Invalid CONTRACT_SIGNER_PRIVATE_KEY"))?;
+        }
+
+        if self.blockchain.zero_conf_enabled && self.blockchain.chains.iter().all(|c| c.ws_url.is_none()) {
+            anyhow::bail!("ZERO_CONF_ENABLED requires a WebSocket URL for at least one chain");
+        }
+
         // 验证安全配置
         if self.security.jwt_secret.len() < 32 {
             anyhow::bail!("JWT secret must be at least 32 characters");
@@ -253,9 +446,73 @@ impl Config {
             anyhow::bail!("API key length must be at least 16");
         }
 
+        if self.security.admin_api_key.as_ref().is_some_and(|key| key.len() < 32) {
+            anyhow::bail!("Admin API key must be at least 32 characters");
+        }
+
+        // 验证HD钱包配置
+        let master_xpub = match &self.wallet.hd_master_key {
+            Some(master_key) => {
+                let master = crate::utils::parse_master_key(master_key, self.wallet.hd_passphrase.as_deref())
+                    .context("Invalid HD_MASTER_KEY")?;
+                Some(crate::utils::account_xpub(&crate::utils::derive_account_xpriv(&master)?))
+            },
+            None => None,
+        };
+
+        if let Some(xpub) = &self.wallet.hd_account_xpub {
+            let xpub = crate::utils::parse_account_xpub(xpub)
+                .context("Invalid HD_ACCOUNT_XPUB")?;
+
+            if master_xpub.as_ref().map_or(false, |expected| *expected != xpub) {
+                anyhow::bail!("HD_ACCOUNT_XPUB does not belong to HD_MASTER_KEY");
+            }
+        }
+
+        if self.wallet.hd_master_key.is_some() || self.wallet.hd_account_xpub.is_some() {
+            match &self.wallet.collection_address {
+                Some(address) if crate::utils::validate_ethereum_address(address) => {},
+                _ => anyhow::bail!("COLLECTION_ADDRESS must be a valid address when an HD wallet is configured"),
+            }
+        }
+
+        if self.wallet.key_cache_size == 0 {
+            anyhow::bail!("Wallet key cache size must be greater than 0");
+        }
+
+        if self.wallet.key_encryption_keys.is_some() {
+            self.key_ring()?;
+        } else if self.wallet.hd_master_key.is_some() {
+            anyhow::bail!("WALLET_KEKS is required when HD_MASTER_KEY is configured");
+        }
+
         Ok(())
     }
 
+    /// 解析私钥加密密钥环
+    pub fn key_ring(&self) -> Result<crate::utils::KeyRing> {
+        let keys = self.wallet.key_encryption_keys.as_deref()
+            .context("WALLET_KEKS is not configured")?;
+
+        crate::utils::KeyRing::parse(keys, self.wallet.key_encryption_version)
+            .context("Invalid WALLET_KEKS")
+    }
+
+    /// 获取已启用链的配置
+    ///
+    /// # Arguments
+    /// * `chain` - 链
+    pub fn chain(&self, chain: &Chain) -> Result<&ChainConfig> {
+        self.blockchain.chains.iter()
+            .find(|c| c.chain == *chain)
+            .ok_or_else(|| anyhow::anyhow!("Chain {} is not enabled", chain))
+    }
+
+    /// 获取默认链配置 (ENABLED_CHAINS中的第一个)
+    pub fn default_chain(&self) -> &ChainConfig {
+        &self.blockchain.chains[0]
+    }
+
     /// 获取服务器绑定地址
     pub fn bind_address(&self) -> String {
         format!("{}:{}", self.server.host, self.server.port)
@@ -287,13 +544,27 @@ impl Default for Config {
                     max_gas_price: 100,
                     gas_limit: 21000,
                 },
+                chains: vec![ChainConfig {
+                    chain: Chain::Ethereum,
+                    rpc_url: "https://eth-mainnet.alchemyapi.io/v2/demo".to_string(),
+                    ws_url: None,
+                    chain_id: 1,
+                    default_confirmations: 12,
+                    payment_contract: None,
+                }],
                 default_confirmations: 12,
                 listener_interval: 30,
+                late_payment_grace_minutes: 60,
+                zero_conf_enabled: false,
+                zero_conf_timeout_minutes: 30,
+                contract_owner_key: None,
+                contract_signer_key: None,
             },
             security: SecurityConfig {
                 jwt_secret: "default-jwt-secret-change-in-production".to_string(),
                 api_key_length: 32,
                 hmac_key_length: 64,
+                admin_api_key: None,
                 rate_limit: RateLimitConfig {
                     requests_per_minute: 100,
                     burst_size: 10,
@@ -305,6 +576,19 @@ impl Default for Config {
                 timeout: 30,
                 concurrent_sends: 10,
             },
+            wallet: WalletConfig {
+                hd_master_key: None,
+                hd_passphrase: None,
+                hd_account_xpub: None,
+                collection_address: None,
+                collection_threshold: 0.1,
+                collection_token_threshold: 10.0,
+                collection_interval: 60,
+                collection_tx_timeout: 15,
+                key_encryption_keys: None,
+                key_encryption_version: None,
+                key_cache_size: 1000,
+            },
         }
     }
 }
@@ -0,0 +1,238 @@
+// 托管收银台处理器
+// 渲染公开的收银台页面，并提供页面轮询的订单状态接口
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use serde::Serialize;
+use uuid::Uuid;
+use crate::models::{ApiResponse, CheckoutStatusResponse, Payment};
+use crate::services::PaymentService;
+use crate::state::AppState;
+use crate::utils::{generate_payment_qr_code, ContractOrderArgs, PaymentUri};
+
+/// 收银台页面模板
+const CHECKOUT_TEMPLATE: &str = include_str!("../../templates/checkout.html");
+
+/// 收银台页面脚本使用的订单数据
+#[derive(Debug, Serialize)]
+struct CheckoutPageData {
+    #[serde(flatten)]
+    status: CheckoutStatusResponse,
+    /// 订单状态轮询地址
+    status_url: String,
+    /// 取消支付的跳转地址
+    cancel_url: Option<String>,
+    /// 浏览器钱包 (EIP-1193) 发送的交易
+    transaction: WalletTransaction,
+}
+
+/// 浏览器钱包交易参数 (数值均为0x开头的十六进制)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WalletTransaction {
+    to: String,
+    value: String,
+    data: String,
+    chain_id: String,
+    gas: Option<String>,
+}
+
+/// 收银台页面
+///
+/// GET /checkout/{payment_id}
+///
+/// 无需认证，订单ID即访问凭证
+/// 响应: HTML页面
+pub async fn checkout_page(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let payment_id = path.into_inner();
+
+    let payment = match PaymentService::get_checkout_payment(&data.db_pool, payment_id).await {
+        Ok(Some(payment)) => payment,
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound()
+                .content_type("text/plain; charset=utf-8")
+                .body("Payment not found"));
+        },
+        Err(e) => {
+            log::error!("Failed to get checkout payment {}: {}", payment_id, e);
+            return Ok(HttpResponse::InternalServerError()
+                .content_type("text/plain; charset=utf-8")
+                .body("Internal server error"));
+        }
+    };
+
+    match render_checkout_page(&data, &payment) {
+        Ok(page) => Ok(HttpResponse::Ok()
+            .content_type("text/html; charset=utf-8")
+            .insert_header(("Cache-Control", "no-store"))
+            .insert_header(("X-Frame-Options", "DENY"))
+            .body(page)),
+        Err(e) => {
+            log::error!("Failed to render checkout page for payment {}: {}", payment_id, e);
+            Ok(HttpResponse::InternalServerError()
+                .content_type("text/plain; charset=utf-8")
+                .body("Internal server error"))
+        }
+    }
+}
+
+/// 收银台订单状态
+///
+/// GET /checkout/{payment_id}/status
+///
+/// 无需认证，供收银台页面轮询
+/// 响应: CheckoutStatusResponse
+pub async fn checkout_status(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    let payment_id = path.into_inner();
+
+    match PaymentService::get_checkout_payment(&data.db_pool, payment_id).await {
+        Ok(Some(payment)) => Ok(HttpResponse::Ok()
+            .insert_header(("Cache-Control", "no-store"))
+            .json(ApiResponse::success(payment.to_checkout_status()))),
+        Ok(None) => Ok(HttpResponse::NotFound().json(
+            ApiResponse::<()>::error(404, "Payment not found".to_string())
+        )),
+        Err(e) => {
+            log::error!("Failed to get checkout status for payment {}: {}", payment_id, e);
+            Ok(HttpResponse::InternalServerError().json(
+                ApiResponse::<()>::error(500, "Internal server error".to_string())
+            ))
+        }
+    }
+}
+
+/// 渲染收银台页面
+fn render_checkout_page(data: &AppState, payment: &Payment) -> anyhow::Result<String> {
+    let chain_id = data.config.chain(&payment.chain)?.chain_id;
+    let token = data.token_registry.get(&payment.chain, &payment.currency)?;
+    let amount = payment.checkout_amount();
+
+    let uri = PaymentUri::for_payment(
+        &token,
+        chain_id,
+        &payment.payment_address,
+        amount,
+        payment.contract_order_id.as_deref().map(|order_id| ContractOrderArgs {
+            merchant_id: payment.merchant_id,
+            order_id,
+            signature: payment.contract_signature.as_deref(),
+        }),
+    )?;
+    let payment_url = uri.to_string();
+    let qr_code = generate_payment_qr_code(&payment_url)?;
+
+    let page_data = CheckoutPageData {
+        status: payment.to_checkout_status(),
+        status_url: format!("/checkout/{}/status", payment.id),
+        cancel_url: payment.cancel_url.clone(),
+        transaction: WalletTransaction {
+            to: format!("{:?}", uri.target),
+            value: format!("{:#x}", uri.value.unwrap_or_default()),
+            data: format!("{}", uri.calldata()?),
+            chain_id: format!("{:#x}", chain_id),
+            gas: uri.gas_limit.map(|gas| format!("{:#x}", gas)),
+        },
+    };
+
+    Ok(render_template(
+        CHECKOUT_TEMPLATE,
+        &[
+            ("order_id", payment.order_id.clone()),
+            ("amount", amount.normalize().to_string()),
+            ("currency", payment.currency.to_string()),
+            ("chain", payment.chain.to_string()),
+            ("payment_address", payment.payment_address.clone()),
+            ("payment_url", payment_url),
+            ("qr_code", qr_code),
+        ],
+        &script_json(&page_data)?,
+    ))
+}
+
+/// 替换模板中的 `{{name}}` 占位符 (值经过HTML转义)，`{{checkout_data}}` 替换为页面脚本数据
+///
+/// 单次扫描替换，替换后的内容不会再被当作占位符处理
+fn render_template(template: &str, values: &[(&str, String)], checkout_data: &str) -> String {
+    let mut page = String::with_capacity(template.len() + checkout_data.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}").map(|end| start + end) else {
+            break;
+        };
+        let name = &rest[start + 2..end];
+
+        page.push_str(&rest[..start]);
+        match values.iter().find(|(key, _)| *key == name) {
+            Some((_, value)) => page.push_str(&escape_html(value)),
+            None if name == "checkout_data" => page.push_str(checkout_data),
+            None => page.push_str(&rest[start..end + 2]),
+        }
+        rest = &rest[end + 2..];
+    }
+
+    page.push_str(rest);
+    page
+}
+
+/// HTML转义
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 序列化为可嵌入 `<script>` 标签的JSON (转义 `<` 防止提前闭合标签)
+fn script_json<T: Serialize>(value: &T) -> anyhow::Result<String> {
+    Ok(serde_json::to_string(value)?.replace('<', "\\u003c"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let template = "<h1>{{order_id}}</h1><a href=\"{{payment_url}}\"></a><script>{{checkout_data}}</script>{{unknown}}";
+        let page = render_template(
+            template,
+            &[
+                ("order_id", "<script>alert('x')</script>".to_string()),
+                ("payment_url", "ethereum:0x00@1?value=1&gasLimit=2".to_string()),
+            ],
+            &script_json(&serde_json::json!({ "cancel_url": "https://example.com/</script>" })).unwrap(),
+        );
+
+        assert_eq!(
+            page,
+            "<h1>&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;</h1>\
+             <a href=\"ethereum:0x00@1?value=1&amp;gasLimit=2\"></a>\
+             <script>{\"cancel_url\":\"https://example.com/\\u003c/script>\"}</script>{{unknown}}"
+        );
+
+        // 替换后的内容不会再被展开
+        let page = render_template("{{order_id}}", &[("order_id", "{{checkout_data}}".to_string())], "{}");
+        assert_eq!(page, "{{checkout_data}}");
+    }
+
+    #[test]
+    fn test_checkout_template_placeholders() {
+        for name in ["order_id", "amount", "currency", "chain", "payment_address", "payment_url", "qr_code", "checkout_data"] {
+            assert!(CHECKOUT_TEMPLATE.contains(&format!("{{{{{}}}}}", name)), "missing placeholder {}", name);
+        }
+    }
+}
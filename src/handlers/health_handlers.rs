@@ -58,12 +58,8 @@ pub async fn health_check(
         }
     }
 
-    // 检查区块链连接
-    match EthereumService::new_with_config(
-        data.config.blockchain.ethereum_rpc_url.clone(),
-        data.config.blockchain.ethereum_ws_url.clone(),
-        data.config.blockchain.chain_id,
-    ).await {
+    // 检查默认链连接
+    match EthereumService::from_chain_config(data.config.default_chain()).await {
         Ok(ethereum_service) => {
             match ethereum_service.get_network_status().await {
                 Ok(_) => {
@@ -102,26 +98,24 @@ pub async fn health_check(
 pub async fn system_status(
     data: web::Data<AppState>,
 ) -> ActixResult<HttpResponse> {
-    // 获取区块链网络状态
-    let ethereum_status = match EthereumService::new_with_config(
-        data.config.blockchain.ethereum_rpc_url.clone(),
-        data.config.blockchain.ethereum_ws_url.clone(),
-        data.config.blockchain.chain_id,
-    ).await {
-        Ok(service) => {
-            match service.get_network_status().await {
+    // 获取各链网络状态
+    let mut chain_statuses = serde_json::Map::new();
+    for chain_config in &data.config.blockchain.chains {
+        let status = match EthereumService::from_chain_config(chain_config).await {
+            Ok(service) => match service.get_network_status().await {
                 Ok(status) => Some(status),
                 Err(e) => {
-                    log::error!("Failed to get Ethereum network status: {}", e);
+                    log::error!("Failed to get {} network status: {}", chain_config.chain, e);
                     None
                 }
+            },
+            Err(e) => {
+                log::error!("Failed to create {} service: {}", chain_config.chain, e);
+                None
             }
-        },
-        Err(e) => {
-            log::error!("Failed to create Ethereum service: {}", e);
-            None
-        }
-    };
+        };
+        chain_statuses.insert(chain_config.chain.to_string(), serde_json::json!(status));
+    }
 
     // 获取数据库统计
     let db_stats = get_database_stats(&data.db_pool).await;
@@ -134,9 +128,7 @@ pub async fn system_status(
             "timestamp": chrono::Utc::now()
         },
         "database": db_stats,
-        "blockchain": {
-            "ethereum": ethereum_status
-        }
+        "blockchain": chain_statuses
     });
 
     Ok(HttpResponse::Ok().json(ApiResponse::success(response)))
@@ -151,11 +143,7 @@ pub async fn system_status(
 pub async fn network_status(
     data: web::Data<AppState>,
 ) -> ActixResult<HttpResponse> {
-    match EthereumService::new_with_config(
-        data.config.blockchain.ethereum.rpc_url.clone(),
-        data.config.blockchain.ethereum.ws_url.clone(),
-        data.config.blockchain.ethereum.chain_id,
-    ).await {
+    match EthereumService::from_chain_config(data.config.default_chain()).await {
         Ok(ethereum_service) => {
             match ethereum_service.get_network_status().await {
                 Ok(ethereum_status) => {
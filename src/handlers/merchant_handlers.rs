@@ -10,6 +10,7 @@ use crate::models::{
 use crate::services::{MerchantService, merchant_service::MerchantStats};
 use crate::state::AppState;
 use crate::utils::extract_api_key;
+use super::authenticate_merchant;
 
 /// 注册新商户
 /// 
@@ -266,6 +267,60 @@ pub async fn get_merchant_stats(
     }
 }
 
+/// 获取商户确认数规则
+/// 
+/// GET /api/v1/merchants/{merchant_id}/confirmation-rules
+/// 
+/// 需要API密钥认证，规则通过更新商户信息接口的 `confirmation_rules` 字段修改
+/// 响应: Vec<ConfirmationRule>
+pub async fn get_confirmation_rules(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    req: actix_web::HttpRequest,
+) -> ActixResult<HttpResponse> {
+    let merchant = match authenticate_merchant(&data, &req, path.into_inner()).await {
+        Ok(merchant) => merchant,
+        Err(response) => return Ok(response),
+    };
+
+    match MerchantService::new(data.db_pool.clone()).get_confirmation_rules(merchant.id).await {
+        Ok(rules) => Ok(HttpResponse::Ok().json(ApiResponse::success(rules))),
+        Err(e) => {
+            log::error!("Failed to get confirmation rules for {}: {}", merchant.id, e);
+            Ok(HttpResponse::InternalServerError().json(
+                ApiResponse::<()>::error(500, "Internal server error".to_string())
+            ))
+        }
+    }
+}
+
+/// 获取商户零确认规则
+/// 
+/// GET /api/v1/merchants/{merchant_id}/zero-conf-rules
+/// 
+/// 需要API密钥认证，规则通过更新商户信息接口的 `zero_conf_rules` 字段修改
+/// 响应: Vec<ZeroConfRule>
+pub async fn get_zero_conf_rules(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    req: actix_web::HttpRequest,
+) -> ActixResult<HttpResponse> {
+    let merchant = match authenticate_merchant(&data, &req, path.into_inner()).await {
+        Ok(merchant) => merchant,
+        Err(response) => return Ok(response),
+    };
+
+    match MerchantService::new(data.db_pool.clone()).get_zero_conf_rules(merchant.id).await {
+        Ok(rules) => Ok(HttpResponse::Ok().json(ApiResponse::success(rules))),
+        Err(e) => {
+            log::error!("Failed to get zero-confirmation rules for {}: {}", merchant.id, e);
+            Ok(HttpResponse::InternalServerError().json(
+                ApiResponse::<()>::error(500, "Internal server error".to_string())
+            ))
+        }
+    }
+}
+
 /// 停用商户账户
 /// 
 /// DELETE /api/v1/merchants/{merchant_id}
@@ -5,9 +5,49 @@ pub mod merchant_handlers;
 pub mod payment_handlers;
 pub mod webhook_handlers;
 pub mod health_handlers;
+pub mod token_handlers;
+pub mod withdrawal_handlers;
+pub mod checkout_handlers;
+
+use actix_web::HttpResponse;
+use uuid::Uuid;
+use crate::models::{ApiResponse, Merchant};
+use crate::services::MerchantService;
+use crate::state::AppState;
+use crate::utils::extract_api_key;
 
 // 重新导出处理器
 pub use merchant_handlers::*;
 pub use payment_handlers::*;
 pub use webhook_handlers::*;
 pub use health_handlers::*;
+pub use token_handlers::*;
+pub use withdrawal_handlers::*;
+pub use checkout_handlers::*;
+
+/// 验证API密钥并检查商户只能访问自己的数据
+pub(crate) async fn authenticate_merchant(
+    data: &AppState,
+    req: &actix_web::HttpRequest,
+    merchant_id: Uuid,
+) -> Result<Merchant, HttpResponse> {
+    let api_key = extract_api_key(req).map_err(|e| {
+        HttpResponse::Unauthorized().json(ApiResponse::<()>::error(401, e.to_string()))
+    })?;
+
+    match MerchantService::new(data.db_pool.clone()).get_merchant_by_api_key(&api_key).await {
+        Ok(Some(merchant)) if merchant.id == merchant_id => Ok(merchant),
+        Ok(Some(_)) => Err(HttpResponse::Forbidden().json(
+            ApiResponse::<()>::error(403, "Access denied".to_string())
+        )),
+        Ok(None) => Err(HttpResponse::Unauthorized().json(
+            ApiResponse::<()>::error(401, "Invalid API key".to_string())
+        )),
+        Err(e) => {
+            log::error!("Failed to authenticate merchant: {}", e);
+            Err(HttpResponse::InternalServerError().json(
+                ApiResponse::<()>::error(500, "Internal server error".to_string())
+            ))
+        }
+    }
+}
@@ -4,11 +4,11 @@
 use actix_web::{web, HttpResponse, Result as ActixResult};
 use uuid::Uuid;
 use crate::models::{
-    CreatePaymentRequest, PaymentListQuery, ApiResponse, Payment, PaginationParams, PaginatedResponse
+    CreatePaymentRequest, PaymentListQuery, ResolveLatePaymentRequest, ApiResponse, Payment, PaymentResponse, PaginationParams, PaginatedResponse
 };
 use crate::services::{PaymentService, EthereumService, MerchantService};
 use crate::state::AppState;
-use crate::utils::{extract_api_key, generate_payment_qr_code};
+use crate::utils::{extract_api_key, generate_payment_qr_code, PaymentUri};
 
 /// 创建支付订单
 /// 
@@ -43,17 +43,26 @@ pub async fn create_payment(
         }
     };
 
+    // 订单所在链需已启用
+    let chain_config = match data.config.chain(&request.chain) {
+        Ok(chain_config) => chain_config,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(&e.to_string())));
+        }
+    };
+
     // 创建支付订单
-    let ethereum_service = EthereumService::new_with_config(
-        data.config.blockchain.ethereum_rpc_url.clone(),
-        data.config.blockchain.ethereum_ws_url.clone(),
-        data.config.blockchain.chain_id,
-    ).await.map_err(|e| {
+    let ethereum_service = EthereumService::from_chain_config(chain_config).await.map_err(|e| {
         log::error!("Failed to create Ethereum service: {}", e);
         actix_web::error::ErrorInternalServerError("Blockchain service unavailable")
     })?;
 
-    let payment_service = PaymentService::new(data.db_pool.clone(), ethereum_service);
+    let payment_service = PaymentService::new(data.db_pool.clone(), ethereum_service, data.wallet_manager.clone(), data.token_registry.clone())
+        .with_contract_signer(data.config.blockchain.contract_signer_key.as_deref())
+        .map_err(|e| {
+            log::error!("Failed to create payment service: {}", e);
+            actix_web::error::ErrorInternalServerError("Payment service unavailable")
+        })?;
 
     match payment_service.create_payment(merchant.id, request.into_inner()).await {
         Ok(response) => {
@@ -102,16 +111,12 @@ pub async fn get_payment(
     };
 
     // 获取支付订单
-    let ethereum_service = EthereumService::new_with_config(
-        data.config.blockchain.ethereum_rpc_url.clone(),
-        data.config.blockchain.ethereum_ws_url.clone(),
-        data.config.blockchain.chain_id,
-    ).await.map_err(|e| {
+    let ethereum_service = EthereumService::from_chain_config(data.config.default_chain()).await.map_err(|e| {
         log::error!("Failed to create Ethereum service: {}", e);
         actix_web::error::ErrorInternalServerError("Blockchain service unavailable")
     })?;
 
-    let payment_service = PaymentService::new(data.db_pool.clone(), ethereum_service);
+    let payment_service = PaymentService::new(data.db_pool.clone(), ethereum_service, data.wallet_manager.clone(), data.token_registry.clone());
 
     match payment_service.get_payment(payment_id, merchant.id).await {
         Ok(Some(payment)) => {
@@ -161,16 +166,12 @@ pub async fn list_payments(
     };
 
     // 获取支付订单列表
-    let ethereum_service = EthereumService::new_with_config(
-        data.config.blockchain.ethereum_rpc_url.clone(),
-        data.config.blockchain.ethereum_ws_url.clone(),
-        data.config.blockchain.chain_id,
-    ).await.map_err(|e| {
+    let ethereum_service = EthereumService::from_chain_config(data.config.default_chain()).await.map_err(|e| {
         log::error!("Failed to create Ethereum service: {}", e);
         actix_web::error::ErrorInternalServerError("Blockchain service unavailable")
     })?;
 
-    let payment_service = PaymentService::new(data.db_pool.clone(), ethereum_service);
+    let payment_service = PaymentService::new(data.db_pool.clone(), ethereum_service, data.wallet_manager.clone(), data.token_registry.clone());
 
     match payment_service.list_payments(merchant.id, query.into_inner()).await {
         Ok(response) => {
@@ -183,6 +184,63 @@ pub async fn list_payments(
     }
 }
 
+/// 处理过期订单的迟到付款
+/// 
+/// POST /api/v1/payments/{payment_id}/late-payment
+/// 
+/// 需要API密钥认证
+/// 请求体: ResolveLatePaymentRequest
+/// 响应: PaymentResponse
+pub async fn resolve_late_payment(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    request: web::Json<ResolveLatePaymentRequest>,
+    req: actix_web::HttpRequest,
+) -> ActixResult<HttpResponse> {
+    let payment_id = path.into_inner();
+
+    // 提取并验证API密钥
+    let api_key = match extract_api_key(&req) {
+        Ok(key) => key,
+        Err(e) => {
+            return Ok(HttpResponse::Unauthorized().json(ApiResponse::<()>::error(401, e.to_string())));
+        }
+    };
+
+    // 验证商户身份
+    let merchant_service = crate::services::MerchantService::new(data.db_pool.clone());
+    let merchant = match merchant_service.get_merchant_by_api_key(&api_key).await {
+        Ok(Some(merchant)) => merchant,
+        Ok(None) => {
+            return Ok(HttpResponse::Unauthorized().json(ApiResponse::<()>::error(401, "Invalid API key".to_string())));
+        },
+        Err(e) => {
+            log::error!("Failed to authenticate merchant: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(500, "Internal server error".to_string())));
+        }
+    };
+
+    let ethereum_service = EthereumService::from_chain_config(data.config.default_chain()).await.map_err(|e| {
+        log::error!("Failed to create Ethereum service: {}", e);
+        actix_web::error::ErrorInternalServerError("Blockchain service unavailable")
+    })?;
+
+    let payment_service = PaymentService::new(data.db_pool.clone(), ethereum_service, data.wallet_manager.clone(), data.token_registry.clone());
+
+    match payment_service.resolve_late_payment(payment_id, merchant.id, request.into_inner()).await {
+        Ok(payment) => {
+            Ok(HttpResponse::Ok().json(ApiResponse::success(payment)))
+        },
+        Err(e) if e.to_string() == "Payment not found" => {
+            Ok(HttpResponse::NotFound().json(ApiResponse::<()>::error(404, "Payment not found".to_string())))
+        },
+        Err(e) => {
+            log::error!("Failed to resolve late payment {} for merchant {}: {}", payment_id, merchant.id, e);
+            Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(400, e.to_string())))
+        }
+    }
+}
+
 /// 获取支付二维码
 /// 
 /// GET /api/v1/payments/{payment_id}/qrcode
@@ -218,24 +276,25 @@ pub async fn get_payment_qrcode(
     };
 
     // 获取支付订单
-    let ethereum_service = EthereumService::new_with_config(
-        data.config.blockchain.ethereum_rpc_url.clone(),
-        data.config.blockchain.ethereum_ws_url.clone(),
-        data.config.blockchain.chain_id,
-    ).await.map_err(|e| {
+    let ethereum_service = EthereumService::from_chain_config(data.config.default_chain()).await.map_err(|e| {
         log::error!("Failed to create Ethereum service: {}", e);
         actix_web::error::ErrorInternalServerError("Blockchain service unavailable")
     })?;
 
-    let payment_service = PaymentService::new(data.db_pool.clone(), ethereum_service);
+    let payment_service = PaymentService::new(data.db_pool.clone(), ethereum_service, data.wallet_manager.clone(), data.token_registry.clone());
 
     match payment_service.get_payment(payment_id, merchant.id).await {
         Ok(Some(payment)) => {
-            // 生成二维码
-            let payment_url = format!("ethereum:{}?value={}", 
-                payment.payment_address, 
-                payment.amount * rust_decimal::Decimal::from(10_u64.pow(18))
-            );
+            // 生成EIP-681支付链接 (按订单所在链与代币精度)
+            let payment_url = match payment_uri(&data, &payment, merchant.id) {
+                Ok(uri) => uri.to_string(),
+                Err(e) => {
+                    log::error!("Failed to build payment URI for payment {}: {}", payment_id, e);
+                    return Ok(HttpResponse::InternalServerError().json(
+                        ApiResponse::<()>::error(500, "Failed to generate QR code".to_string())
+                    ));
+                }
+            };
 
             match crate::utils::generate_payment_qr_code(&payment_url) {
                 Ok(qr_code_data) => {
@@ -278,12 +337,19 @@ pub async fn get_payment_qrcode(
     }
 }
 
+/// 按订单所在链的链ID与代币精度生成支付URI
+pub(crate) fn payment_uri(data: &AppState, payment: &PaymentResponse, merchant_id: Uuid) -> anyhow::Result<PaymentUri> {
+    let chain_id = data.config.chain(&payment.chain)?.chain_id;
+    let token = data.token_registry.get(&payment.chain, &payment.currency)?;
+    payment.payment_uri(&token, chain_id, merchant_id)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use actix_web::{test, App};
     use crate::state::AppState;
-    use crate::models::{CreatePaymentRequest, Currency};
+    use crate::models::{CreatePaymentRequest, Chain, Currency, PaymentMode};
 
     #[actix_web::test]
     async fn test_create_payment_handler() {
@@ -297,9 +363,14 @@ mod tests {
         let request_body = CreatePaymentRequest {
             order_id: "TEST_ORDER_001".to_string(),
             amount: rust_decimal::Decimal::new(100, 2),
+            chain: Chain::Ethereum,
             currency: Currency::ETH,
+            payment_mode: PaymentMode::Address,
             callback_url: Some("https://example.com/callback".to_string()),
             expires_in: Some(3600),
+            required_confirmations: None,
+            success_url: None,
+            cancel_url: None,
         };
 
         let req = test::TestRequest::post()
@@ -0,0 +1,95 @@
+// 代币登记API处理器
+// 处理可收款代币的查询，以及管理员登记、修改代币的HTTP请求
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use uuid::Uuid;
+use crate::models::{ApiResponse, CreateTokenRequest, UpdateTokenRequest, TokenListQuery};
+use crate::state::AppState;
+use crate::utils::verify_admin_api_key;
+
+/// 查询可收款代币
+///
+/// GET /api/v1/tokens?chain=
+///
+/// 无需认证，只返回启用的代币
+/// 响应: Vec<Token>
+pub async fn list_tokens(
+    data: web::Data<AppState>,
+    query: web::Query<TokenListQuery>,
+) -> ActixResult<HttpResponse> {
+    let tokens = data.token_registry.list(query.chain, true);
+    Ok(HttpResponse::Ok().json(ApiResponse::success(tokens)))
+}
+
+/// 查询全部已登记代币 (包括已停用的代币)
+///
+/// GET /api/v1/admin/tokens?chain=
+///
+/// 需要管理接口密钥
+/// 响应: Vec<Token>
+pub async fn admin_list_tokens(
+    data: web::Data<AppState>,
+    query: web::Query<TokenListQuery>,
+    req: actix_web::HttpRequest,
+) -> ActixResult<HttpResponse> {
+    if let Err(e) = verify_admin_api_key(&req, data.config.security.admin_api_key.as_deref()) {
+        return Ok(HttpResponse::Unauthorized().json(ApiResponse::<()>::error(401, e.to_string())));
+    }
+
+    let tokens = data.token_registry.list(query.chain, false);
+    Ok(HttpResponse::Ok().json(ApiResponse::success(tokens)))
+}
+
+/// 登记新代币
+///
+/// POST /api/v1/admin/tokens
+///
+/// 需要管理接口密钥
+/// 请求体: CreateTokenRequest
+/// 响应: Token
+pub async fn create_token(
+    data: web::Data<AppState>,
+    request: web::Json<CreateTokenRequest>,
+    req: actix_web::HttpRequest,
+) -> ActixResult<HttpResponse> {
+    if let Err(e) = verify_admin_api_key(&req, data.config.security.admin_api_key.as_deref()) {
+        return Ok(HttpResponse::Unauthorized().json(ApiResponse::<()>::error(401, e.to_string())));
+    }
+
+    match data.token_registry.create_token(request.into_inner()).await {
+        Ok(token) => Ok(HttpResponse::Created().json(ApiResponse::success(token))),
+        Err(e) => {
+            log::error!("Failed to register token: {}", e);
+            Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(400, e.to_string())))
+        }
+    }
+}
+
+/// 修改代币金额限制或启用状态
+///
+/// PUT /api/v1/admin/tokens/{token_id}
+///
+/// 需要管理接口密钥
+/// 请求体: UpdateTokenRequest
+/// 响应: Token
+pub async fn update_token(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    request: web::Json<UpdateTokenRequest>,
+    req: actix_web::HttpRequest,
+) -> ActixResult<HttpResponse> {
+    if let Err(e) = verify_admin_api_key(&req, data.config.security.admin_api_key.as_deref()) {
+        return Ok(HttpResponse::Unauthorized().json(ApiResponse::<()>::error(401, e.to_string())));
+    }
+
+    let token_id = path.into_inner();
+
+    match data.token_registry.update_token(token_id, request.into_inner()).await {
+        Ok(Some(token)) => Ok(HttpResponse::Ok().json(ApiResponse::success(token))),
+        Ok(None) => Ok(HttpResponse::NotFound().json(ApiResponse::<()>::error(404, "Token not found".to_string()))),
+        Err(e) => {
+            log::error!("Failed to update token {}: {}", token_id, e);
+            Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(400, e.to_string())))
+        }
+    }
+}
@@ -4,7 +4,7 @@
 use actix_web::{web, HttpResponse, Result as ActixResult};
 use uuid::Uuid;
 use serde::Deserialize;
-use crate::models::{ApiResponse, PaymentWebhookPayload, PaymentStatus, Currency};
+use crate::models::{ApiResponse, PaymentWebhookPayload, PaymentStatus, Chain, Currency};
 use crate::services::{WebhookService, webhook_service::WebhookStats};
 use crate::state::AppState;
 use crate::utils::extract_api_key;
@@ -67,6 +67,8 @@ pub async fn test_webhook(
         order_id: "TEST_ORDER_WEBHOOK".to_string(),
         status: PaymentStatus::Completed,
         amount: rust_decimal::Decimal::new(100, 2),
+        amount_received: rust_decimal::Decimal::new(100, 2),
+        chain: Chain::Ethereum,
         currency: Currency::ETH,
         transaction_hash: Some("0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string()),
         confirmations: Some(12),
@@ -0,0 +1,126 @@
+// 合约提款API处理器
+// 处理商户合约余额查询、提款与提款记录查询的HTTP请求
+
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use uuid::Uuid;
+use crate::config::ChainConfig;
+use crate::models::{ApiResponse, CreateWithdrawalRequest};
+use crate::services::{ContractService, EthereumService};
+use crate::state::AppState;
+use super::authenticate_merchant;
+
+/// 查询商户在各链收款合约中的余额
+///
+/// GET /api/v1/merchants/{merchant_id}/contract-balances
+///
+/// 需要API密钥认证
+/// 响应: Vec<ContractBalance>
+pub async fn get_contract_balances(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    req: actix_web::HttpRequest,
+) -> ActixResult<HttpResponse> {
+    let merchant = match authenticate_merchant(&data, &req, path.into_inner()).await {
+        Ok(merchant) => merchant,
+        Err(response) => return Ok(response),
+    };
+
+    let mut balances = Vec::new();
+
+    for chain_config in data.config.blockchain.chains.iter().filter(|c| c.payment_contract.is_some()) {
+        let result = match contract_service(&data, chain_config).await {
+            Ok(contract_service) => contract_service.get_balance(merchant.id).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(balance) => balances.push(balance),
+            Err(e) => {
+                log::error!("Failed to get contract balance on {} for merchant {}: {}", chain_config.chain, merchant.id, e);
+                return Ok(HttpResponse::InternalServerError().json(
+                    ApiResponse::<()>::error(500, "Blockchain service unavailable".to_string())
+                ));
+            }
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(balances)))
+}
+
+/// 从收款合约提款到商户的提款地址
+///
+/// POST /api/v1/merchants/{merchant_id}/withdrawals
+///
+/// 需要API密钥认证，商户需先配置提款地址 (payout_address)
+/// 请求体: CreateWithdrawalRequest
+/// 响应: ContractWithdrawal
+pub async fn create_withdrawal(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    request: web::Json<CreateWithdrawalRequest>,
+    req: actix_web::HttpRequest,
+) -> ActixResult<HttpResponse> {
+    let merchant = match authenticate_merchant(&data, &req, path.into_inner()).await {
+        Ok(merchant) => merchant,
+        Err(response) => return Ok(response),
+    };
+
+    // 提款所在链需已启用且配置了收款合约
+    let chain_config = match data.config.chain(&request.chain) {
+        Ok(chain_config) if chain_config.payment_contract.is_some() => chain_config,
+        Ok(_) => {
+            return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(
+                400, format!("Contract payments are not enabled on {}", request.chain)
+            )));
+        },
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(400, e.to_string())));
+        }
+    };
+
+    let contract_service = contract_service(&data, chain_config).await.map_err(|e| {
+        log::error!("Failed to create contract service: {}", e);
+        actix_web::error::ErrorInternalServerError("Blockchain service unavailable")
+    })?;
+
+    match contract_service.withdraw(merchant.id, merchant.payout_address.as_deref(), request.into_inner()).await {
+        Ok(withdrawal) => Ok(HttpResponse::Created().json(ApiResponse::success(withdrawal))),
+        Err(e) => {
+            log::error!("Failed to withdraw for merchant {}: {}", merchant.id, e);
+            Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(400, e.to_string())))
+        }
+    }
+}
+
+/// 查询商户的提款记录
+///
+/// GET /api/v1/merchants/{merchant_id}/withdrawals
+///
+/// 需要API密钥认证
+/// 响应: Vec<ContractWithdrawal>
+pub async fn list_withdrawals(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    req: actix_web::HttpRequest,
+) -> ActixResult<HttpResponse> {
+    let merchant = match authenticate_merchant(&data, &req, path.into_inner()).await {
+        Ok(merchant) => merchant,
+        Err(response) => return Ok(response),
+    };
+
+    match ContractService::list_withdrawals(&data.db_pool, merchant.id).await {
+        Ok(withdrawals) => Ok(HttpResponse::Ok().json(ApiResponse::success(withdrawals))),
+        Err(e) => {
+            log::error!("Failed to list withdrawals for merchant {}: {}", merchant.id, e);
+            Ok(HttpResponse::InternalServerError().json(
+                ApiResponse::<()>::error(500, "Internal server error".to_string())
+            ))
+        }
+    }
+}
+
+/// 创建指定链的合约服务
+async fn contract_service(data: &AppState, chain_config: &ChainConfig) -> anyhow::Result<ContractService> {
+    let ethereum_service = EthereumService::from_chain_config(chain_config).await?;
+    ContractService::new(ethereum_service, data.db_pool.clone(), data.config.blockchain.contract_owner_key.as_deref())
+}
@@ -10,7 +10,10 @@ mod services;
 mod utils;
 mod middleware;
 
-use crate::config::Config;
+#[cfg(test)]
+mod devnet;
+
+use crate::config::{Config, ChainConfig};
 use crate::routes::{api_v1_routes, public_routes};
 use crate::state::AppState;
 use crate::middleware::{RequestLogging, create_cors};
@@ -55,8 +58,15 @@ async fn main() -> Result<()> {
 
     log::info!("Database migrations completed");
 
+    // 签名器模式: 只运行资金归集，不对外提供HTTP服务
+    match std::env::args().nth(1).as_deref() {
+        Some("signer") => return run_signer(db_pool, config).await,
+        Some("rotate-keys") => return run_key_rotation(db_pool, config).await,
+        _ => {},
+    }
+
     // 创建应用状态
-    let app_state = actix_web::web::Data::new(AppState::new(db_pool, config.clone()));
+    let app_state = actix_web::web::Data::new(AppState::new(db_pool, config.clone()).await?);
 
     // 启动后台任务
     start_background_tasks(app_state.clone()).await?;
@@ -93,14 +103,72 @@ async fn main() -> Result<()> {
 async fn start_background_tasks(app_state: actix_web::web::Data<AppState>) -> Result<()> {
     let pool = app_state.db_pool.clone();
     let config = app_state.config.clone();
+    let wallet_manager = app_state.wallet_manager.clone();
+    let token_registry = app_state.token_registry.clone();
 
     // 启动支付监听任务
     tokio::spawn(async move {
-        if let Err(e) = payment_monitoring_task(pool.clone(), config.clone()).await {
+        if let Err(e) = payment_monitoring_task(pool.clone(), config.clone(), wallet_manager, token_registry).await {
             log::error!("Payment monitoring task failed: {}", e);
         }
     });
 
+    // 每条启用的链独立运行扫描与索引任务
+    for chain_config in &app_state.config.blockchain.chains {
+        let chain = chain_config.chain;
+
+        // 启动区块扫描充值检测任务
+        let pool_clone = app_state.db_pool.clone();
+        let chain_clone = chain_config.clone();
+        let grace_minutes = app_state.config.blockchain.late_payment_grace_minutes;
+        tokio::spawn(async move {
+            if let Err(e) = deposit_scanning_task(pool_clone, chain_clone, grace_minutes).await {
+                log::error!("Deposit scanning task on {} failed: {}", chain, e);
+            }
+        });
+
+        // 启动ERC20 Transfer事件索引任务
+        let pool_clone = app_state.db_pool.clone();
+        let chain_clone = chain_config.clone();
+        let token_registry = app_state.token_registry.clone();
+        tokio::spawn(async move {
+            if let Err(e) = token_indexing_task(pool_clone, chain_clone, token_registry, grace_minutes).await {
+                log::error!("Token indexing task on {} failed: {}", chain, e);
+            }
+        });
+
+        // 启动合约收款事件监听任务 (需要配置收款合约)
+        if chain_config.payment_contract.is_some() {
+            let pool_clone = app_state.db_pool.clone();
+            let chain_clone = chain_config.clone();
+            tokio::spawn(async move {
+                if let Err(e) = contract_listening_task(pool_clone, chain_clone, grace_minutes).await {
+                    log::error!("Contract listening task on {} failed: {}", chain, e);
+                }
+            });
+
+            // 启动合约提款跟踪任务
+            let pool_clone = app_state.db_pool.clone();
+            let chain_clone = chain_config.clone();
+            tokio::spawn(async move {
+                if let Err(e) = withdrawal_tracking_task(pool_clone, chain_clone).await {
+                    log::error!("Withdrawal tracking task on {} failed: {}", chain, e);
+                }
+            });
+        }
+
+        // 启动内存池零确认检测任务 (需要WebSocket URL)
+        if app_state.config.blockchain.zero_conf_enabled && chain_config.ws_url.is_some() {
+            let pool_clone = app_state.db_pool.clone();
+            let chain_clone = chain_config.clone();
+            tokio::spawn(async move {
+                if let Err(e) = mempool_watching_task(pool_clone, chain_clone).await {
+                    log::error!("Mempool watching task on {} failed: {}", chain, e);
+                }
+            });
+        }
+    }
+
     // 启动Webhook重试任务
     let pool_clone = app_state.db_pool.clone();
     tokio::spawn(async move {
@@ -121,23 +189,88 @@ async fn start_background_tasks(app_state: actix_web::web::Data<AppState>) -> Re
     Ok(())
 }
 
+/// 签名器进程
+///
+/// 持有HD主密钥并签名归集交易，API进程只需配置 `HD_ACCOUNT_XPUB`。
+/// 每个签名器进程归集一条链，未指定时为默认链
+/// 启动方式: `wopay signer [chain]`
+async fn run_signer(pool: sqlx::PgPool, config: Config) -> Result<()> {
+    use crate::services::{WalletManager, CollectionService};
+    use crate::models::Chain;
+    use std::sync::Arc;
+
+    let chain = match std::env::args().nth(2) {
+        Some(chain) => chain.parse::<Chain>()?,
+        None => config.default_chain().chain,
+    };
+
+    let wallet_manager = WalletManager::signer_from_config(&config, pool.clone(), &chain)?;
+
+    log::info!("Starting WoPay signer on {} (collection to {})", chain,
+        config.wallet.collection_address.as_deref().unwrap_or_default());
+
+    let collection_service = CollectionService::new(
+        Arc::new(wallet_manager),
+        pool,
+        config.wallet.collection_interval,
+    );
+
+    collection_service.start_auto_collection().await
+}
+
+/// 私钥加密密钥轮换
+///
+/// 使用 `WALLET_KEK_VERSION` 指定的新密钥重新加密所有已存储的私钥，
+/// 可在API与签名器进程运行期间执行
+/// 启动方式: `wopay rotate-keys`
+async fn run_key_rotation(pool: sqlx::PgPool, config: Config) -> Result<()> {
+    use crate::services::WalletManager;
+
+    let wallet_manager = WalletManager::signer_from_config(&config, pool, &config.default_chain().chain)?;
+
+    let rotated = wallet_manager.rotate_encryption_keys(100).await?;
+    log::info!("Key rotation completed, {} private keys re-encrypted", rotated);
+
+    Ok(())
+}
+
 /// 支付监听后台任务
-async fn payment_monitoring_task(pool: sqlx::PgPool, config: Config) -> Result<()> {
-    use crate::services::{PaymentService, EthereumService};
+async fn payment_monitoring_task(
+    pool: sqlx::PgPool,
+    config: Config,
+    wallet_manager: std::sync::Arc<crate::services::WalletManager>,
+    token_registry: crate::services::TokenRegistry,
+) -> Result<()> {
+    use crate::services::{PaymentService, EthereumService, WebhookService};
     use tokio::time::{sleep, Duration};
 
-    let ethereum_service = EthereumService::new_with_config(
-        config.blockchain.ethereum_rpc_url.clone(),
-        config.blockchain.ethereum_ws_url.clone(),
-        config.blockchain.chain_id,
-    ).await?;
+    let mut ethereum_services = Vec::with_capacity(config.blockchain.chains.len());
+    for chain_config in &config.blockchain.chains {
+        ethereum_services.push(EthereumService::from_chain_config(chain_config).await?);
+    }
+    let ethereum_service = ethereum_services[0].clone();
 
-    let payment_service = PaymentService::new(pool.clone(), ethereum_service.clone());
+    let payment_service = PaymentService::new(pool.clone(), ethereum_service.clone(), wallet_manager, token_registry);
+    let webhook_service = WebhookService::new(pool.clone(), 5);
 
     loop {
-        // 更新确认数
-        if let Err(e) = ethereum_service.update_confirmations(&pool).await {
-            log::error!("Failed to update confirmations: {}", e);
+        // 按链更新确认数
+        for chain_service in &ethereum_services {
+            if let Err(e) = chain_service.update_confirmations(&pool).await {
+                log::error!("Failed to update confirmations on {}: {}", chain_service.chain(), e);
+            }
+        }
+
+        // 回退超时未上链的零确认检测
+        match ethereum_service.revert_stale_detections(config.blockchain.zero_conf_timeout_minutes, &pool).await {
+            Ok(reverted) => {
+                for payment_id in reverted {
+                    if let Err(e) = webhook_service.notify_payment_status(payment_id).await {
+                        log::error!("Failed to notify reverted detection {}: {}", payment_id, e);
+                    }
+                }
+            },
+            Err(e) => log::error!("Failed to revert stale detections: {}", e),
         }
 
         // 标记过期支付
@@ -149,6 +282,74 @@ async fn payment_monitoring_task(pool: sqlx::PgPool, config: Config) -> Result<(
     }
 }
 
+/// 区块扫描充值检测后台任务
+async fn deposit_scanning_task(pool: sqlx::PgPool, chain_config: ChainConfig, grace_minutes: i32) -> Result<()> {
+    use crate::services::{DepositScanner, EthereumService};
+
+    let ethereum_service = EthereumService::from_chain_config(&chain_config).await?;
+
+    DepositScanner::new(ethereum_service, pool, grace_minutes)
+        .start_scanning()
+        .await
+}
+
+/// ERC20 Transfer事件索引后台任务
+async fn token_indexing_task(
+    pool: sqlx::PgPool,
+    chain_config: ChainConfig,
+    token_registry: crate::services::TokenRegistry,
+    grace_minutes: i32,
+) -> Result<()> {
+    use crate::services::{TokenIndexer, EthereumService};
+
+    let ethereum_service = EthereumService::from_chain_config(&chain_config).await?;
+
+    TokenIndexer::new(ethereum_service, pool, token_registry, grace_minutes)
+        .start_indexing()
+        .await
+}
+
+/// 合约收款事件监听后台任务
+async fn contract_listening_task(pool: sqlx::PgPool, chain_config: ChainConfig, grace_minutes: i32) -> Result<()> {
+    use crate::services::{ContractListener, EthereumService};
+
+    let ethereum_service = EthereumService::from_chain_config(&chain_config).await?;
+
+    ContractListener::new(ethereum_service, pool, grace_minutes)?
+        .start_listening()
+        .await
+}
+
+/// 合约提款跟踪后台任务
+async fn withdrawal_tracking_task(pool: sqlx::PgPool, chain_config: ChainConfig) -> Result<()> {
+    use crate::services::{ContractService, EthereumService};
+    use tokio::time::{sleep, Duration};
+
+    let ethereum_service = EthereumService::from_chain_config(&chain_config).await?;
+    let contract_service = ContractService::new(ethereum_service, pool, None)?;
+
+    loop {
+        match contract_service.track_pending_withdrawals().await {
+            Ok(settled) if settled > 0 => log::info!("{} withdrawals settled on {}", settled, chain_config.chain),
+            Ok(_) => {},
+            Err(e) => log::error!("Failed to track withdrawals on {}: {}", chain_config.chain, e),
+        }
+
+        sleep(Duration::from_secs(30)).await;
+    }
+}
+
+/// 内存池零确认检测后台任务
+async fn mempool_watching_task(pool: sqlx::PgPool, chain_config: ChainConfig) -> Result<()> {
+    use crate::services::{MempoolWatcher, EthereumService};
+
+    let ethereum_service = EthereumService::from_chain_config(&chain_config).await?;
+
+    MempoolWatcher::new(ethereum_service, pool)?
+        .start_watching()
+        .await
+}
+
 /// Webhook重试后台任务
 async fn webhook_retry_task(pool: sqlx::PgPool) -> Result<()> {
     use crate::services::WebhookService;
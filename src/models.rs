@@ -5,12 +5,16 @@ mod merchant;
 mod payment;
 mod transaction;
 mod webhook;
+mod token;
+mod withdrawal;
 
 // 重新导出核心类型
 pub use merchant::*;
 pub use payment::*;
 pub use transaction::*;
 pub use webhook::*;
+pub use token::*;
+pub use withdrawal::*;
 
 use serde::Serialize;
 
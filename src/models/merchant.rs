@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use crate::models::payment::{AmountMatch, Chain, Currency};
 
 /// 商户信息模型
 #[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
@@ -22,8 +24,14 @@ pub struct Merchant {
     pub api_secret: String,
     /// Webhook回调地址
     pub webhook_url: Option<String>,
+    /// 合约收款的提款地址
+    pub payout_address: Option<String>,
     /// 商户状态
     pub status: MerchantStatus,
+    /// 付款容差类型
+    pub payment_tolerance_type: ToleranceType,
+    /// 付款容差 (固定金额或百分比)
+    pub payment_tolerance: Decimal,
     /// 创建时间
     pub created_at: DateTime<Utc>,
     /// 更新时间
@@ -51,6 +59,122 @@ impl Default for MerchantStatus {
     }
 }
 
+/// 付款容差类型
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, Copy, PartialEq)]
+#[sqlx(type_name = "varchar")]
+#[serde(rename_all = "lowercase")]
+pub enum ToleranceType {
+    /// 固定金额 (币种单位)
+    #[sqlx(rename = "absolute")]
+    Absolute,
+    /// 订单金额的百分比
+    #[sqlx(rename = "percentage")]
+    Percentage,
+}
+
+impl Default for ToleranceType {
+    fn default() -> Self {
+        ToleranceType::Absolute
+    }
+}
+
+/// 付款金额容差
+///
+/// 到账金额在 `订单金额 ± 容差` 范围内视为足额支付
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaymentTolerance {
+    /// 容差类型
+    pub tolerance_type: ToleranceType,
+    /// 容差值
+    pub value: Decimal,
+}
+
+impl PaymentTolerance {
+    /// 创建付款容差
+    pub fn new(tolerance_type: ToleranceType, value: Decimal) -> Self {
+        Self { tolerance_type, value }
+    }
+
+    /// 计算订单金额允许的偏差
+    pub fn allowance(&self, expected: Decimal) -> Decimal {
+        match self.tolerance_type {
+            ToleranceType::Absolute => self.value,
+            ToleranceType::Percentage => expected * self.value / Decimal::ONE_HUNDRED,
+        }
+    }
+
+    /// 比较到账金额与订单金额
+    ///
+    /// # Arguments
+    /// * `expected` - 订单金额
+    /// * `received` - 累计到账金额
+    pub fn classify(&self, expected: Decimal, received: Decimal) -> AmountMatch {
+        let allowance = self.allowance(expected);
+
+        if received < expected - allowance {
+            AmountMatch::Underpaid
+        } else if received > expected + allowance {
+            AmountMatch::Overpaid
+        } else {
+            AmountMatch::Matched
+        }
+    }
+}
+
+/// 确认数配置上限 (规则与单笔订单)
+pub const MAX_REQUIRED_CONFIRMATIONS: i32 = 100;
+
+/// 商户确认数规则
+///
+/// 按链与币种配置，同一链上的同一币种可配置多档，订单金额达到 `min_amount` 的最高一档生效，
+/// 例如小额订单1个确认、大额订单20个确认
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, PartialEq)]
+pub struct ConfirmationRule {
+    /// 适用链 (未指定时为ethereum)
+    #[serde(default)]
+    pub chain: Chain,
+    /// 适用币种
+    pub currency: Currency,
+    /// 起始金额 (包含)
+    pub min_amount: Decimal,
+    /// 需要的区块确认数
+    pub confirmations: i32,
+}
+
+impl ConfirmationRule {
+    /// 按链、币种与金额匹配确认数规则
+    ///
+    /// # Arguments
+    /// * `rules` - 商户配置的规则
+    /// * `chain` - 订单所在链
+    /// * `currency` - 订单币种
+    /// * `amount` - 订单金额
+    ///
+    /// # Returns
+    /// * 匹配规则的确认数，没有匹配的规则时返回None
+    pub fn resolve(rules: &[ConfirmationRule], chain: &Chain, currency: &Currency, amount: Decimal) -> Option<i32> {
+        rules.iter()
+            .filter(|rule| &rule.chain == chain && &rule.currency == currency && rule.min_amount <= amount)
+            .max_by_key(|rule| rule.min_amount)
+            .map(|rule| rule.confirmations)
+    }
+}
+
+/// 商户零确认规则
+///
+/// 订单金额不超过 `max_amount` 时，内存池中检测到的足额付款即可标记为detected，
+/// 订单完成仍需达到正常确认数
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, PartialEq)]
+pub struct ZeroConfRule {
+    /// 适用链 (未指定时为ethereum)
+    #[serde(default)]
+    pub chain: Chain,
+    /// 适用币种
+    pub currency: Currency,
+    /// 允许零确认的最大订单金额 (包含)
+    pub max_amount: Decimal,
+}
+
 /// 商户注册请求
 #[derive(Debug, Deserialize)]
 pub struct CreateMerchantRequest {
@@ -86,8 +210,18 @@ pub struct UpdateMerchantRequest {
     pub name: Option<String>,
     /// Webhook回调地址 (可选)
     pub webhook_url: Option<String>,
+    /// 合约收款的提款地址 (可选)
+    pub payout_address: Option<String>,
     /// 商户状态 (可选)
     pub status: Option<MerchantStatus>,
+    /// 付款容差类型 (可选)
+    pub payment_tolerance_type: Option<ToleranceType>,
+    /// 付款容差 (可选)
+    pub payment_tolerance: Option<Decimal>,
+    /// 确认数规则 (可选，提供时替换全部已有规则)
+    pub confirmation_rules: Option<Vec<ConfirmationRule>>,
+    /// 零确认规则 (可选，提供时替换全部已有规则，空列表表示关闭零确认)
+    pub zero_conf_rules: Option<Vec<ZeroConfRule>>,
 }
 
 /// API密钥重新生成响应
@@ -107,6 +241,11 @@ impl Merchant {
         self.status == MerchantStatus::Active
     }
 
+    /// 获取付款容差
+    pub fn payment_tolerance(&self) -> PaymentTolerance {
+        PaymentTolerance::new(self.payment_tolerance_type, self.payment_tolerance)
+    }
+
     /// 验证API密钥是否匹配
     pub fn verify_api_key(&self, api_key: &str) -> bool {
         self.api_key == api_key
@@ -133,3 +272,57 @@ pub struct MerchantPublic {
     pub status: MerchantStatus,
     pub created_at: DateTime<Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payment_tolerance_classify() {
+        let expected = Decimal::new(100, 0);
+
+        // 无容差: 必须足额
+        let exact = PaymentTolerance::new(ToleranceType::Absolute, Decimal::ZERO);
+        assert_eq!(exact.classify(expected, Decimal::new(9999, 2)), AmountMatch::Underpaid);
+        assert_eq!(exact.classify(expected, expected), AmountMatch::Matched);
+        assert_eq!(exact.classify(expected, Decimal::new(10001, 2)), AmountMatch::Overpaid);
+
+        // 固定金额容差 0.5
+        let absolute = PaymentTolerance::new(ToleranceType::Absolute, Decimal::new(5, 1));
+        assert_eq!(absolute.classify(expected, Decimal::new(995, 1)), AmountMatch::Matched);
+        assert_eq!(absolute.classify(expected, Decimal::new(994, 1)), AmountMatch::Underpaid);
+        assert_eq!(absolute.classify(expected, Decimal::new(1005, 1)), AmountMatch::Matched);
+        assert_eq!(absolute.classify(expected, Decimal::new(1006, 1)), AmountMatch::Overpaid);
+
+        // 百分比容差 1%
+        let percentage = PaymentTolerance::new(ToleranceType::Percentage, Decimal::ONE);
+        assert_eq!(percentage.allowance(expected), Decimal::ONE);
+        assert_eq!(percentage.classify(expected, Decimal::new(99, 0)), AmountMatch::Matched);
+        assert_eq!(percentage.classify(expected, Decimal::new(9899, 2)), AmountMatch::Underpaid);
+        assert_eq!(percentage.classify(expected, Decimal::new(10101, 2)), AmountMatch::Overpaid);
+    }
+
+    #[test]
+    fn test_resolve_confirmation_rule() {
+        let rules = vec![
+            ConfirmationRule { chain: Chain::Ethereum, currency: Currency::USDT, min_amount: Decimal::ZERO, confirmations: 1 },
+            ConfirmationRule { chain: Chain::Ethereum, currency: Currency::USDT, min_amount: Decimal::new(1000, 0), confirmations: 20 },
+            ConfirmationRule { chain: Chain::Ethereum, currency: Currency::USDT, min_amount: Decimal::new(100, 0), confirmations: 6 },
+            ConfirmationRule { chain: Chain::Ethereum, currency: Currency::ETH, min_amount: Decimal::new(1, 0), confirmations: 12 },
+            ConfirmationRule { chain: Chain::Polygon, currency: Currency::USDT, min_amount: Decimal::ZERO, confirmations: 64 },
+        ];
+
+        // 小额、中额、大额订单按金额档位匹配
+        assert_eq!(ConfirmationRule::resolve(&rules, &Chain::Ethereum, &Currency::USDT, Decimal::new(50, 0)), Some(1));
+        assert_eq!(ConfirmationRule::resolve(&rules, &Chain::Ethereum, &Currency::USDT, Decimal::new(100, 0)), Some(6));
+        assert_eq!(ConfirmationRule::resolve(&rules, &Chain::Ethereum, &Currency::USDT, Decimal::new(5000, 0)), Some(20));
+
+        // 同一币种在其他链上按该链的规则匹配
+        assert_eq!(ConfirmationRule::resolve(&rules, &Chain::Polygon, &Currency::USDT, Decimal::new(5000, 0)), Some(64));
+        assert_eq!(ConfirmationRule::resolve(&rules, &Chain::Bsc, &Currency::USDT, Decimal::new(50, 0)), None);
+
+        // 低于最低档位或没有该币种规则时使用默认值
+        assert_eq!(ConfirmationRule::resolve(&rules, &Chain::Ethereum, &Currency::ETH, Decimal::new(5, 1)), None);
+        assert_eq!(ConfirmationRule::resolve(&[], &Chain::Ethereum, &Currency::ETH, Decimal::ONE), None);
+    }
+}
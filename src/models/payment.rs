@@ -6,6 +6,9 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
+use std::borrow::Cow;
+use super::{PaymentDeposit, Token};
+use crate::utils::{ContractOrderArgs, PaymentUri};
 
 /// 支付订单模型
 #[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
@@ -18,16 +21,32 @@ pub struct Payment {
     pub order_id: String,
     /// 支付金额
     pub amount: Decimal,
+    /// 累计到账金额
+    pub amount_received: Decimal,
+    /// 收款链
+    pub chain: Chain,
     /// 支付币种
     pub currency: Currency,
-    /// 收款地址
+    /// 收款模式
+    pub payment_mode: PaymentMode,
+    /// 收款地址 (合约收款模式下为合约地址)
     pub payment_address: String,
+    /// 合约收款模式下的链上订单号
+    pub contract_order_id: Option<String>,
+    /// 合约收款模式下服务端对订单参数的签名
+    pub contract_signature: Option<String>,
+    /// 收银台支付完成后的跳转地址
+    pub success_url: Option<String>,
+    /// 收银台取消支付时的跳转地址
+    pub cancel_url: Option<String>,
     /// 支付状态
     pub status: PaymentStatus,
     /// 区块链交易哈希
     pub transaction_hash: Option<String>,
     /// 区块确认数
     pub confirmations: i32,
+    /// 订单完成所需的区块确认数
+    pub required_confirmations: i32,
     /// 订单过期时间
     pub expires_at: Option<DateTime<Utc>>,
     /// 创建时间
@@ -36,6 +55,20 @@ pub struct Payment {
     pub updated_at: DateTime<Utc>,
 }
 
+/// 收款模式
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, Copy, PartialEq, Eq, Default)]
+#[sqlx(type_name = "varchar")]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentMode {
+    /// 为每个订单分配独立的HD钱包收款地址
+    #[default]
+    #[sqlx(rename = "address")]
+    Address,
+    /// 通过Wopay合约的 `receivePayment(merchantId, orderId, amount, signature)` 付款 (仅支持原生币)
+    #[sqlx(rename = "contract")]
+    Contract,
+}
+
 /// 支付状态枚举
 #[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, PartialEq)]
 #[sqlx(type_name = "varchar")]
@@ -43,15 +76,30 @@ pub enum PaymentStatus {
     /// 待支付状态
     #[sqlx(rename = "pending")]
     Pending,
+    /// 已检测状态 (内存池中发现足额付款交易，满足商户零确认规则，尚未上链)
+    #[sqlx(rename = "detected")]
+    Detected,
+    /// 少付状态 (到账金额低于订单金额减容差，等待补款)
+    #[sqlx(rename = "underpaid")]
+    Underpaid,
     /// 已确认状态 (收到交易但确认数不足)
     #[sqlx(rename = "confirmed")]
     Confirmed,
     /// 已完成状态 (确认数足够)
     #[sqlx(rename = "completed")]
     Completed,
+    /// 多付状态 (确认数足够，到账金额超过订单金额加容差)
+    #[sqlx(rename = "overpaid")]
+    Overpaid,
     /// 已过期状态
     #[sqlx(rename = "expired")]
     Expired,
+    /// 过期后付款状态 (宽限期内收到到账，等待商户接受或退款)
+    #[sqlx(rename = "paid_after_expiry")]
+    PaidAfterExpiry,
+    /// 已退款状态 (商户拒绝迟到付款，归集时退回付款方)
+    #[sqlx(rename = "refunded")]
+    Refunded,
     /// 失败状态
     #[sqlx(rename = "failed")]
     Failed,
@@ -63,38 +111,199 @@ impl Default for PaymentStatus {
     }
 }
 
-/// 支持的币种枚举
-#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, PartialEq)]
+/// 到账金额与订单金额的比较结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AmountMatch {
+    /// 少付
+    Underpaid,
+    /// 在容差范围内
+    Matched,
+    /// 多付
+    Overpaid,
+}
+
+impl AmountMatch {
+    /// 根据比较结果与确认情况得到支付状态
+    ///
+    /// 多付只在确认数足够后标记，确认前与正常付款一样为confirmed
+    pub fn payment_status(&self, fully_confirmed: bool) -> PaymentStatus {
+        match (self, fully_confirmed) {
+            (AmountMatch::Underpaid, _) => PaymentStatus::Underpaid,
+            (AmountMatch::Matched, true) => PaymentStatus::Completed,
+            (AmountMatch::Overpaid, true) => PaymentStatus::Overpaid,
+            (_, false) => PaymentStatus::Confirmed,
+        }
+    }
+}
+
+/// 商户对迟到付款的处理方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LatePaymentAction {
+    /// 接受付款，按正常订单结算
+    Accept,
+    /// 退款到指定地址
+    Refund,
+}
+
+/// 处理迟到付款请求
+#[derive(Debug, Deserialize)]
+pub struct ResolveLatePaymentRequest {
+    /// 处理方式
+    pub action: LatePaymentAction,
+    /// 退款地址 (退款时可选，默认退回最早一笔到账的付款地址)
+    pub refund_address: Option<String>,
+}
+
+/// 支持的区块链网络
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, Copy, PartialEq, Eq, Hash, Default)]
 #[sqlx(type_name = "varchar")]
-pub enum Currency {
-    /// 以太坊原生代币
-    #[sqlx(rename = "ETH")]
-    ETH,
-    /// USDT稳定币
-    #[sqlx(rename = "USDT")]
-    USDT,
+#[serde(rename_all = "lowercase")]
+pub enum Chain {
+    /// 以太坊主网
+    #[default]
+    #[sqlx(rename = "ethereum")]
+    Ethereum,
+    /// BNB Smart Chain
+    #[sqlx(rename = "bsc")]
+    Bsc,
+    /// Polygon PoS
+    #[sqlx(rename = "polygon")]
+    Polygon,
+    /// Arbitrum One
+    #[sqlx(rename = "arbitrum")]
+    Arbitrum,
+    /// Base
+    #[sqlx(rename = "base")]
+    Base,
 }
 
-impl Currency {
-    /// 获取代币合约地址 (如果是ERC20代币)
-    pub fn contract_address(&self) -> Option<&'static str> {
+impl Chain {
+    /// 所有支持的链
+    pub const ALL: [Chain; 5] = [Chain::Ethereum, Chain::Bsc, Chain::Polygon, Chain::Arbitrum, Chain::Base];
+
+    /// 链名称 (与数据库及API中的取值一致)
+    pub fn as_str(&self) -> &'static str {
         match self {
-            Currency::ETH => None, // ETH是原生代币，没有合约地址
-            Currency::USDT => Some("0xdAC17F958D2ee523a2206206994597C13D831ec7"), // USDT合约地址
+            Chain::Ethereum => "ethereum",
+            Chain::Bsc => "bsc",
+            Chain::Polygon => "polygon",
+            Chain::Arbitrum => "arbitrum",
+            Chain::Base => "base",
         }
     }
 
-    /// 获取代币精度 (小数位数)
-    pub fn decimals(&self) -> u8 {
+    /// 获取链的原生代币
+    pub fn native_currency(&self) -> Currency {
         match self {
-            Currency::ETH => 18,
-            Currency::USDT => 6,
+            Chain::Ethereum | Chain::Arbitrum | Chain::Base => Currency::ETH,
+            Chain::Bsc => Currency::BNB,
+            Chain::Polygon => Currency::POL,
+        }
+    }
+}
+
+impl std::str::FromStr for Chain {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        Chain::ALL.into_iter()
+            .find(|chain| chain.as_str() == value.trim().to_lowercase())
+            .ok_or_else(|| anyhow::anyhow!("Unsupported chain: {}", value))
+    }
+}
+
+impl std::fmt::Display for Chain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 支付币种 (代币符号，如ETH、USDT、USDC)
+///
+/// 可用币种及其合约地址、精度由`tokens`表登记，见[`super::Token`]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Currency(Cow<'static, str>);
+
+impl Currency {
+    /// 以太坊原生代币 (Ethereum、Arbitrum、Base)
+    pub const ETH: Currency = Currency(Cow::Borrowed("ETH"));
+    /// USDT稳定币
+    pub const USDT: Currency = Currency(Cow::Borrowed("USDT"));
+    /// BSC原生代币
+    pub const BNB: Currency = Currency(Cow::Borrowed("BNB"));
+    /// Polygon原生代币
+    pub const POL: Currency = Currency(Cow::Borrowed("POL"));
+
+    /// 代币符号
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// 检查是否为指定链的原生代币
+    pub fn is_native_on(&self, chain: &Chain) -> bool {
+        chain.native_currency() == *self
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = anyhow::Error;
+
+    fn try_from(symbol: String) -> anyhow::Result<Self> {
+        let symbol = symbol.trim();
+        if symbol.is_empty() || symbol.len() > 10 || !symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+            anyhow::bail!("Invalid currency symbol: {}", symbol);
         }
+        Ok(Currency(Cow::Owned(symbol.to_uppercase())))
+    }
+}
+
+impl From<Currency> for String {
+    fn from(currency: Currency) -> Self {
+        currency.0.into_owned()
+    }
+}
+
+impl std::str::FromStr for Currency {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        Currency::try_from(value.to_string())
+    }
+}
+
+impl std::fmt::Display for Currency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::fmt::Debug for Currency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl sqlx::Type<sqlx::Postgres> for Currency {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <String as sqlx::Type<sqlx::Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        <String as sqlx::Type<sqlx::Postgres>>::compatible(ty)
     }
+}
+
+impl sqlx::Encode<'_, sqlx::Postgres> for Currency {
+    fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> sqlx::encode::IsNull {
+        <&str as sqlx::Encode<sqlx::Postgres>>::encode(self.as_str(), buf)
+    }
+}
 
-    /// 检查是否为原生代币
-    pub fn is_native(&self) -> bool {
-        matches!(self, Currency::ETH)
+impl<'r> sqlx::Decode<'r, sqlx::Postgres> for Currency {
+    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        Ok(Currency(Cow::Owned(<String as sqlx::Decode<sqlx::Postgres>>::decode(value)?)))
     }
 }
 
@@ -105,12 +314,24 @@ pub struct CreatePaymentRequest {
     pub order_id: String,
     /// 支付金额
     pub amount: Decimal,
+    /// 收款链 (可选，默认ethereum)
+    #[serde(default)]
+    pub chain: Chain,
     /// 支付币种
     pub currency: Currency,
+    /// 收款模式 (可选，默认为独立收款地址)
+    #[serde(default)]
+    pub payment_mode: PaymentMode,
     /// 回调地址 (可选，覆盖商户默认配置)
     pub callback_url: Option<String>,
     /// 过期时间 (秒，可选，默认1小时)
     pub expires_in: Option<i64>,
+    /// 所需区块确认数 (可选，覆盖商户确认数规则)
+    pub required_confirmations: Option<i32>,
+    /// 收银台支付完成后的跳转地址 (可选)
+    pub success_url: Option<String>,
+    /// 收银台取消支付时的跳转地址 (可选)
+    pub cancel_url: Option<String>,
 }
 
 /// 创建支付订单响应
@@ -118,18 +339,30 @@ pub struct CreatePaymentRequest {
 pub struct CreatePaymentResponse {
     /// 支付订单ID
     pub payment_id: Uuid,
-    /// 收款地址
+    /// 收款模式
+    pub payment_mode: PaymentMode,
+    /// 收款地址 (合约收款模式下为合约地址)
     pub payment_address: String,
+    /// 链上订单号 (合约收款模式下调用 `receivePayment` 时传入)
+    pub contract_order_id: Option<String>,
+    /// 订单签名 (合约收款模式下调用 `receivePayment` 时传入)
+    pub contract_signature: Option<String>,
     /// 支付金额
     pub amount: Decimal,
+    /// 收款链
+    pub chain: Chain,
     /// 支付币种
     pub currency: Currency,
     /// 过期时间
     pub expires_at: Option<DateTime<Utc>>,
+    /// 所需区块确认数
+    pub required_confirmations: i32,
     /// 支付二维码 (Base64编码的PNG图片)
     pub qr_code: String,
     /// 支付链接 (用于钱包应用直接调用)
     pub payment_url: String,
+    /// 托管收银台页面路径 (无需认证，可直接引导付款人访问)
+    pub checkout_url: String,
 }
 
 /// 支付订单查询响应
@@ -143,14 +376,30 @@ pub struct PaymentResponse {
     pub status: PaymentStatus,
     /// 支付金额
     pub amount: Decimal,
+    /// 累计到账金额
+    pub amount_received: Decimal,
+    /// 待付金额 (多付时为0)
+    pub amount_outstanding: Decimal,
+    /// 收款链
+    pub chain: Chain,
     /// 支付币种
     pub currency: Currency,
-    /// 收款地址
+    /// 收款模式
+    pub payment_mode: PaymentMode,
+    /// 收款地址 (合约收款模式下为合约地址)
     pub payment_address: String,
-    /// 区块链交易哈希
+    /// 合约收款模式下的链上订单号
+    pub contract_order_id: Option<String>,
+    /// 合约收款模式下的订单签名
+    pub contract_signature: Option<String>,
+    /// 最近一笔到账交易哈希
     pub transaction_hash: Option<String>,
-    /// 区块确认数
+    /// 区块确认数 (各笔到账交易中的最小值)
     pub confirmations: i32,
+    /// 订单完成所需的区块确认数
+    pub required_confirmations: i32,
+    /// 到账交易列表
+    pub deposits: Vec<PaymentDeposit>,
     /// 创建时间
     pub created_at: DateTime<Utc>,
     /// 完成时间 (如果已完成)
@@ -159,6 +408,49 @@ pub struct PaymentResponse {
     pub expires_at: Option<DateTime<Utc>>,
 }
 
+/// 收银台订单状态响应 (公开接口，不包含商户信息)
+#[derive(Debug, Serialize)]
+pub struct CheckoutStatusResponse {
+    /// 支付状态
+    pub status: PaymentStatus,
+    /// 支付金额
+    pub amount: Decimal,
+    /// 累计到账金额
+    pub amount_received: Decimal,
+    /// 待付金额 (多付时为0)
+    pub amount_outstanding: Decimal,
+    /// 区块确认数
+    pub confirmations: i32,
+    /// 订单完成所需的区块确认数
+    pub required_confirmations: i32,
+    /// 过期时间
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 支付完成后的跳转地址 (订单完成前为空)
+    pub redirect_url: Option<String>,
+}
+
+impl PaymentResponse {
+    /// 生成EIP-681支付URI (用于钱包应用)
+    ///
+    /// # Arguments
+    /// * `token` - 订单的收款代币
+    /// * `chain_id` - 收款链的链ID
+    /// * `merchant_id` - 商户ID (合约收款模式的调用参数)
+    pub fn payment_uri(&self, token: &Token, chain_id: u64, merchant_id: Uuid) -> anyhow::Result<PaymentUri> {
+        PaymentUri::for_payment(
+            token,
+            chain_id,
+            &self.payment_address,
+            self.amount,
+            self.contract_order_id.as_deref().map(|order_id| ContractOrderArgs {
+                merchant_id,
+                order_id,
+                signature: self.contract_signature.as_deref(),
+            }),
+        )
+    }
+}
+
 impl Payment {
     /// 检查支付订单是否已过期
     pub fn is_expired(&self) -> bool {
@@ -171,30 +463,75 @@ impl Payment {
 
     /// 检查支付订单是否可以被取消
     pub fn can_be_cancelled(&self) -> bool {
-        matches!(self.status, PaymentStatus::Pending | PaymentStatus::Confirmed)
+        matches!(self.status, PaymentStatus::Pending | PaymentStatus::Detected | PaymentStatus::Underpaid | PaymentStatus::Confirmed)
     }
 
     /// 检查支付订单是否已完成
     pub fn is_completed(&self) -> bool {
-        self.status == PaymentStatus::Completed
+        matches!(self.status, PaymentStatus::Completed | PaymentStatus::Overpaid)
+    }
+
+    /// 待付金额
+    pub fn amount_outstanding(&self) -> Decimal {
+        (self.amount - self.amount_received).max(Decimal::ZERO)
+    }
+
+    /// 收银台应付金额
+    ///
+    /// 地址收款模式下部分到账后只需补付差额；合约收款模式的链上订单号只能支付一次，
+    /// 且合约拒绝低于订单金额的付款，不会出现部分到账，始终为订单金额
+    pub fn checkout_amount(&self) -> Decimal {
+        match self.payment_mode {
+            PaymentMode::Address if self.amount_received > Decimal::ZERO => self.amount_outstanding(),
+            _ => self.amount,
+        }
+    }
+
+    /// 转换为收银台状态响应
+    pub fn to_checkout_status(&self) -> CheckoutStatusResponse {
+        CheckoutStatusResponse {
+            status: self.status.clone(),
+            amount: self.amount,
+            amount_received: self.amount_received,
+            amount_outstanding: self.amount_outstanding(),
+            confirmations: self.confirmations,
+            required_confirmations: self.required_confirmations,
+            expires_at: self.expires_at,
+            redirect_url: if self.is_completed() {
+                self.success_url.clone()
+            } else {
+                None
+            },
+        }
     }
 
     /// 检查支付订单是否需要更多确认
-    pub fn needs_more_confirmations(&self, required_confirmations: i32) -> bool {
-        self.status == PaymentStatus::Confirmed && self.confirmations < required_confirmations
+    pub fn needs_more_confirmations(&self) -> bool {
+        self.status == PaymentStatus::Confirmed && self.confirmations < self.required_confirmations
     }
 
     /// 转换为API响应格式
-    pub fn to_response(&self) -> PaymentResponse {
+    ///
+    /// # Arguments
+    /// * `deposits` - 该订单的到账交易列表
+    pub fn to_response(&self, deposits: Vec<PaymentDeposit>) -> PaymentResponse {
         PaymentResponse {
             payment_id: self.id,
             order_id: self.order_id.clone(),
             status: self.status.clone(),
             amount: self.amount,
+            amount_received: self.amount_received,
+            amount_outstanding: self.amount_outstanding(),
+            chain: self.chain,
             currency: self.currency.clone(),
+            payment_mode: self.payment_mode,
             payment_address: self.payment_address.clone(),
+            contract_order_id: self.contract_order_id.clone(),
+            contract_signature: self.contract_signature.clone(),
             transaction_hash: self.transaction_hash.clone(),
             confirmations: self.confirmations,
+            required_confirmations: self.required_confirmations,
+            deposits,
             created_at: self.created_at,
             completed_at: if self.is_completed() { 
                 Some(self.updated_at) 
@@ -204,37 +541,6 @@ impl Payment {
             expires_at: self.expires_at,
         }
     }
-
-    /// 生成支付URL (用于钱包应用)
-    pub fn generate_payment_url(&self) -> String {
-        match self.currency {
-            Currency::ETH => {
-                format!("ethereum:{}?value={}", 
-                    self.payment_address, 
-                    self.amount_in_wei()
-                )
-            },
-            Currency::USDT => {
-                format!("ethereum:{}@1/transfer?address={}&uint256={}",
-                    self.currency.contract_address().unwrap(),
-                    self.payment_address,
-                    self.amount_in_smallest_unit()
-                )
-            }
-        }
-    }
-
-    /// 获取以Wei为单位的金额 (ETH)
-    fn amount_in_wei(&self) -> String {
-        let wei_amount = self.amount * Decimal::from(10_u64.pow(18));
-        format!("{}", wei_amount.trunc())
-    }
-
-    /// 获取以最小单位的金额
-    fn amount_in_smallest_unit(&self) -> String {
-        let smallest_unit = self.amount * Decimal::from(10_u64.pow(self.currency.decimals() as u32));
-        format!("{}", smallest_unit.trunc())
-    }
 }
 
 /// 支付订单列表查询参数
@@ -246,6 +552,8 @@ pub struct PaymentListQuery {
     pub limit: Option<u32>,
     /// 状态过滤
     pub status: Option<PaymentStatus>,
+    /// 链过滤
+    pub chain: Option<Chain>,
     /// 币种过滤
     pub currency: Option<Currency>,
     /// 开始时间
@@ -309,3 +617,61 @@ impl PaginationInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount_match_payment_status() {
+        assert_eq!(AmountMatch::Underpaid.payment_status(false), PaymentStatus::Underpaid);
+        assert_eq!(AmountMatch::Underpaid.payment_status(true), PaymentStatus::Underpaid);
+        assert_eq!(AmountMatch::Matched.payment_status(false), PaymentStatus::Confirmed);
+        assert_eq!(AmountMatch::Matched.payment_status(true), PaymentStatus::Completed);
+        assert_eq!(AmountMatch::Overpaid.payment_status(false), PaymentStatus::Confirmed);
+        assert_eq!(AmountMatch::Overpaid.payment_status(true), PaymentStatus::Overpaid);
+    }
+
+    #[test]
+    fn test_checkout_amount() {
+        let mut payment = Payment {
+            id: Uuid::new_v4(),
+            merchant_id: Uuid::new_v4(),
+            order_id: "ORDER_001".to_string(),
+            amount: Decimal::new(100, 0),
+            amount_received: Decimal::ZERO,
+            chain: Chain::Ethereum,
+            currency: Currency::USDT,
+            payment_mode: PaymentMode::Address,
+            payment_address: "0x742d35Cc6634c0532925A3B8d4c9DB96Dfbbb8B2".to_string(),
+            contract_order_id: None,
+            contract_signature: None,
+            success_url: Some("https://example.com/success".to_string()),
+            cancel_url: None,
+            status: PaymentStatus::Pending,
+            transaction_hash: None,
+            confirmations: 0,
+            required_confirmations: 12,
+            expires_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        assert_eq!(payment.checkout_amount(), Decimal::new(100, 0));
+        assert_eq!(payment.to_checkout_status().redirect_url, None);
+
+        // 部分到账后只需补付差额
+        payment.amount_received = Decimal::new(40, 0);
+        payment.status = PaymentStatus::Underpaid;
+        assert_eq!(payment.checkout_amount(), Decimal::new(60, 0));
+
+        // 合约订单号只能支付一次，且合约拒绝低于订单金额的付款
+        payment.payment_mode = PaymentMode::Contract;
+        payment.contract_order_id = Some("1".to_string());
+        assert_eq!(payment.checkout_amount(), Decimal::new(100, 0));
+
+        payment.amount_received = Decimal::new(100, 0);
+        payment.status = PaymentStatus::Completed;
+        assert_eq!(payment.to_checkout_status().redirect_url.as_deref(), Some("https://example.com/success"));
+    }
+}
@@ -0,0 +1,165 @@
+// 代币登记数据模型
+// 定义各链可收款代币 (合约地址、精度、金额限制) 的数据结构
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use ethers::types::U256;
+use crate::models::payment::{Chain, Currency};
+
+/// 原生代币精度 (各EVM链均为18位)
+pub const NATIVE_DECIMALS: u32 = 18;
+
+/// 已登记代币
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone, PartialEq)]
+pub struct Token {
+    /// 代币唯一标识符
+    pub id: Uuid,
+    /// 所在链
+    pub chain: Chain,
+    /// 代币符号
+    pub symbol: Currency,
+    /// 合约地址 (原生代币为空)
+    pub contract_address: Option<String>,
+    /// 精度 (小数位数)
+    pub decimals: i32,
+    /// 单笔最小收款金额
+    pub min_amount: Decimal,
+    /// 单笔最大收款金额
+    pub max_amount: Decimal,
+    /// 是否可用于新建订单 (停用后已有订单仍会继续扫描)
+    pub enabled: bool,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 更新时间
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Token {
+    /// 检查是否为原生代币
+    pub fn is_native(&self) -> bool {
+        self.contract_address.is_none()
+    }
+
+    /// 精度 (小数位数)
+    pub fn decimals(&self) -> u32 {
+        self.decimals as u32
+    }
+
+    /// 将金额换算为最小单位 (截断多余精度)
+    ///
+    /// 按金额的尾数与小数位数在U256中换算，金额为负或超出U256范围时返回错误
+    pub fn to_smallest_unit(&self, amount: Decimal) -> anyhow::Result<U256> {
+        if amount < Decimal::ZERO {
+            anyhow::bail!("Amount must not be negative: {}", amount);
+        }
+
+        let mantissa = U256::from(amount.mantissa().unsigned_abs());
+        let (scale, decimals) = (amount.scale(), self.decimals());
+        let units = if decimals >= scale {
+            U256::from(10).checked_pow(U256::from(decimals - scale))
+                .and_then(|factor| mantissa.checked_mul(factor))
+        } else {
+            U256::from(10).checked_pow(U256::from(scale - decimals))
+                .map(|factor| mantissa / factor)
+        };
+
+        units.ok_or_else(|| anyhow::anyhow!("Amount {} overflows {} decimals", amount, decimals))
+    }
+}
+
+/// 构造测试用代币
+#[cfg(test)]
+pub(crate) fn test_token(chain: Chain, symbol: Currency, contract_address: Option<&str>, decimals: i32) -> Token {
+    Token {
+        id: Uuid::new_v4(),
+        chain,
+        symbol,
+        contract_address: contract_address.map(str::to_string),
+        decimals,
+        min_amount: Decimal::new(1, 2),
+        max_amount: Decimal::new(1000000, 0),
+        enabled: true,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+/// 登记代币请求
+#[derive(Debug, Deserialize)]
+pub struct CreateTokenRequest {
+    /// 所在链
+    pub chain: Chain,
+    /// 代币符号
+    pub symbol: Currency,
+    /// 合约地址 (原生代币为空)
+    pub contract_address: Option<String>,
+    /// 精度 (小数位数)
+    pub decimals: i32,
+    /// 单笔最小收款金额
+    pub min_amount: Decimal,
+    /// 单笔最大收款金额
+    pub max_amount: Decimal,
+    /// 是否启用 (默认启用)
+    pub enabled: Option<bool>,
+}
+
+/// 更新代币请求 (合约地址与精度登记后不可修改)
+#[derive(Debug, Deserialize)]
+pub struct UpdateTokenRequest {
+    /// 单笔最小收款金额
+    pub min_amount: Option<Decimal>,
+    /// 单笔最大收款金额
+    pub max_amount: Option<Decimal>,
+    /// 是否启用
+    pub enabled: Option<bool>,
+}
+
+/// 代币列表查询参数
+#[derive(Debug, Deserialize)]
+pub struct TokenListQuery {
+    /// 链过滤
+    pub chain: Option<Chain>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_smallest_unit() {
+        let token = test_token(Chain::Ethereum, Currency::USDT, Some("0xdAC17F958D2ee523a2206206994597C13D831ec7"), 6);
+
+        assert!(!token.is_native());
+        assert_eq!(token.to_smallest_unit(Decimal::new(1050, 2)).unwrap(), U256::from(10_500_000));
+        // 超出精度的部分截断
+        assert_eq!(token.to_smallest_unit(Decimal::new(1, 7)).unwrap(), U256::zero());
+        assert!(token.to_smallest_unit(Decimal::new(-1, 0)).is_err());
+    }
+
+    #[test]
+    fn test_token_smallest_unit_high_decimals() {
+        // 超过u64范围的精度 (10^20以上) 不溢出
+        let token = test_token(Chain::Ethereum, Currency::USDT, Some("0xdAC17F958D2ee523a2206206994597C13D831ec7"), 24);
+        assert_eq!(
+            token.to_smallest_unit(Decimal::new(15, 1)).unwrap(),
+            U256::from(15) * U256::exp10(23)
+        );
+
+        let token = test_token(Chain::Ethereum, Currency::USDT, Some("0xdAC17F958D2ee523a2206206994597C13D831ec7"), 36);
+        assert_eq!(token.to_smallest_unit(Decimal::MAX).unwrap(), U256::from(Decimal::MAX.mantissa() as u128) * U256::exp10(36));
+        assert_eq!(token.to_smallest_unit(Decimal::new(1, 28)).unwrap(), U256::exp10(8));
+    }
+
+    #[test]
+    fn test_currency_symbol_normalized() {
+        let currency: Currency = serde_json::from_str("\" usdc \"").unwrap();
+        assert_eq!(currency.as_str(), "USDC");
+        assert!(serde_json::from_str::<Currency>("\"US-DT\"").is_err());
+        assert_eq!(serde_json::to_string(&Currency::ETH).unwrap(), "\"ETH\"");
+        assert!(Currency::POL.is_native_on(&Chain::Polygon));
+        assert!(!Currency::USDT.is_native_on(&Chain::Ethereum));
+    }
+}
@@ -57,6 +57,25 @@ impl Default for TransactionStatus {
     }
 }
 
+/// 支付订单的单笔到账记录
+#[derive(Debug, Serialize, Clone)]
+pub struct PaymentDeposit {
+    /// 交易哈希
+    pub transaction_hash: String,
+    /// 发送方地址
+    pub from_address: String,
+    /// 到账金额
+    pub amount: Decimal,
+    /// 区块号
+    pub block_number: Option<i64>,
+    /// 确认数
+    pub confirmations: i32,
+    /// 交易状态
+    pub status: TransactionStatus,
+    /// 检测时间
+    pub created_at: DateTime<Utc>,
+}
+
 /// 创建区块链交易记录请求
 #[derive(Debug, Deserialize)]
 pub struct CreateTransactionRequest {
@@ -149,6 +168,9 @@ impl BlockchainTransaction {
         match self.blockchain.as_str() {
             "ethereum" => format!("https://etherscan.io/tx/{}", self.transaction_hash),
             "bsc" => format!("https://bscscan.com/tx/{}", self.transaction_hash),
+            "polygon" => format!("https://polygonscan.com/tx/{}", self.transaction_hash),
+            "arbitrum" => format!("https://arbiscan.io/tx/{}", self.transaction_hash),
+            "base" => format!("https://basescan.org/tx/{}", self.transaction_hash),
             "solana" => format!("https://explorer.solana.com/tx/{}", self.transaction_hash),
             _ => format!("https://etherscan.io/tx/{}", self.transaction_hash), // 默认使用以太坊
         }
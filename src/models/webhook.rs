@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
-use crate::models::payment::PaymentStatus;
+use crate::models::payment::{PaymentStatus, Chain, Currency};
 use rust_decimal::Decimal;
 
 /// Webhook日志记录模型
@@ -37,15 +37,30 @@ pub enum WebhookEventType {
     /// 支付创建事件
     #[serde(rename = "payment.created")]
     PaymentCreated,
+    /// 内存池检测到付款事件 (零确认)
+    #[serde(rename = "payment.detected")]
+    PaymentDetected,
+    /// 支付少付事件
+    #[serde(rename = "payment.underpaid")]
+    PaymentUnderpaid,
     /// 支付确认事件
     #[serde(rename = "payment.confirmed")]
     PaymentConfirmed,
     /// 支付完成事件
     #[serde(rename = "payment.completed")]
     PaymentCompleted,
+    /// 支付多付事件
+    #[serde(rename = "payment.overpaid")]
+    PaymentOverpaid,
     /// 支付过期事件
     #[serde(rename = "payment.expired")]
     PaymentExpired,
+    /// 过期订单收到迟到付款事件
+    #[serde(rename = "payment.paid_after_expiry")]
+    PaymentPaidAfterExpiry,
+    /// 迟到付款已退款事件
+    #[serde(rename = "payment.refunded")]
+    PaymentRefunded,
     /// 支付失败事件
     #[serde(rename = "payment.failed")]
     PaymentFailed,
@@ -55,9 +70,14 @@ impl From<PaymentStatus> for WebhookEventType {
     fn from(status: PaymentStatus) -> Self {
         match status {
             PaymentStatus::Pending => WebhookEventType::PaymentCreated,
+            PaymentStatus::Detected => WebhookEventType::PaymentDetected,
+            PaymentStatus::Underpaid => WebhookEventType::PaymentUnderpaid,
             PaymentStatus::Confirmed => WebhookEventType::PaymentConfirmed,
             PaymentStatus::Completed => WebhookEventType::PaymentCompleted,
+            PaymentStatus::Overpaid => WebhookEventType::PaymentOverpaid,
             PaymentStatus::Expired => WebhookEventType::PaymentExpired,
+            PaymentStatus::PaidAfterExpiry => WebhookEventType::PaymentPaidAfterExpiry,
+            PaymentStatus::Refunded => WebhookEventType::PaymentRefunded,
             PaymentStatus::Failed => WebhookEventType::PaymentFailed,
         }
     }
@@ -88,6 +108,29 @@ pub struct WebhookPayload {
     pub signature: String,
 }
 
+/// 支付状态变更通知载荷
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PaymentWebhookPayload {
+    /// 支付订单ID
+    pub payment_id: Uuid,
+    /// 商户订单号
+    pub order_id: String,
+    /// 支付状态
+    pub status: PaymentStatus,
+    /// 支付金额
+    pub amount: Decimal,
+    /// 累计到账金额
+    pub amount_received: Decimal,
+    /// 收款链
+    pub chain: Chain,
+    /// 支付币种
+    pub currency: Currency,
+    /// 区块链交易哈希 (如果有)
+    pub transaction_hash: Option<String>,
+    /// 区块确认数
+    pub confirmations: Option<i32>,
+}
+
 /// Webhook发送请求
 #[derive(Debug, Clone)]
 pub struct WebhookRequest {
@@ -244,6 +287,56 @@ impl BlockchainConfig {
             is_testnet: false,
         }
     }
+
+    /// 创建Polygon主网配置
+    pub fn polygon_mainnet() -> Self {
+        Self {
+            name: "polygon".to_string(),
+            rpc_url: "https://polygon-rpc.com".to_string(),
+            ws_url: None,
+            chain_id: 137,
+            required_confirmations: 64,
+            block_time: 2,
+            is_testnet: false,
+        }
+    }
+
+    /// 创建Arbitrum One配置
+    pub fn arbitrum_mainnet() -> Self {
+        Self {
+            name: "arbitrum".to_string(),
+            rpc_url: "https://arb1.arbitrum.io/rpc".to_string(),
+            ws_url: None,
+            chain_id: 42161,
+            required_confirmations: 20,
+            block_time: 1,
+            is_testnet: false,
+        }
+    }
+
+    /// 创建Base主网配置
+    pub fn base_mainnet() -> Self {
+        Self {
+            name: "base".to_string(),
+            rpc_url: "https://mainnet.base.org".to_string(),
+            ws_url: None,
+            chain_id: 8453,
+            required_confirmations: 10,
+            block_time: 2,
+            is_testnet: false,
+        }
+    }
+
+    /// 获取指定链的主网默认配置
+    pub fn for_chain(chain: &Chain) -> Self {
+        match chain {
+            Chain::Ethereum => Self::ethereum_mainnet(),
+            Chain::Bsc => Self::bsc_mainnet(),
+            Chain::Polygon => Self::polygon_mainnet(),
+            Chain::Arbitrum => Self::arbitrum_mainnet(),
+            Chain::Base => Self::base_mainnet(),
+        }
+    }
 }
 
 /// 交易监听配置
@@ -0,0 +1,62 @@
+// 合约提款数据模型
+// 定义商户在Wopay合约中的余额与提款记录
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use crate::models::{Chain, Currency, TransactionStatus};
+
+/// 合约提款记录
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct ContractWithdrawal {
+    /// 提款记录ID
+    pub id: Uuid,
+    /// 商户ID
+    pub merchant_id: Uuid,
+    /// 提款所在链
+    pub chain: Chain,
+    /// 币种 (该链的原生币)
+    pub currency: Currency,
+    /// 提款金额
+    pub amount: Decimal,
+    /// 收款的提款地址
+    pub payout_address: String,
+    /// 提款交易哈希
+    pub tx_hash: String,
+    /// 交易状态
+    pub status: TransactionStatus,
+    /// 上链区块号
+    pub block_number: Option<i64>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 更新时间
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 商户在合约中的余额
+#[derive(Debug, Serialize, Clone)]
+pub struct ContractBalance {
+    /// 链
+    pub chain: Chain,
+    /// 币种 (该链的原生币)
+    pub currency: Currency,
+    /// 收款合约地址
+    pub contract_address: String,
+    /// 可提款余额
+    pub balance: Decimal,
+    /// 提款中的金额 (已提交未上链)
+    pub pending_withdrawals: Decimal,
+    /// 合约中登记的提款地址 (未登记时为None)
+    pub payout_address: Option<String>,
+}
+
+/// 提款请求
+#[derive(Debug, Deserialize)]
+pub struct CreateWithdrawalRequest {
+    /// 提款所在链
+    pub chain: Chain,
+    /// 提款金额 (可选，默认提取全部余额)
+    pub amount: Option<Decimal>,
+}
@@ -13,6 +13,10 @@ pub fn api_v1_routes() -> Scope {
         .service(payment_routes())
         // Webhook路由
         .service(webhook_routes())
+        // 代币查询路由
+        .route("/tokens", web::get().to(list_tokens))
+        // 管理接口路由
+        .service(admin_routes())
         // 系统状态路由
         .route("/status", web::get().to(system_status))
         .route("/version", web::get().to(version_info))
@@ -28,6 +32,11 @@ fn merchant_routes() -> Scope {
         .route("/{merchant_id}", web::delete().to(deactivate_merchant))
         .route("/{merchant_id}/regenerate-keys", web::post().to(regenerate_api_keys))
         .route("/{merchant_id}/stats", web::get().to(get_merchant_stats))
+        .route("/{merchant_id}/confirmation-rules", web::get().to(get_confirmation_rules))
+        .route("/{merchant_id}/zero-conf-rules", web::get().to(get_zero_conf_rules))
+        .route("/{merchant_id}/contract-balances", web::get().to(get_contract_balances))
+        .route("/{merchant_id}/withdrawals", web::get().to(list_withdrawals))
+        .route("/{merchant_id}/withdrawals", web::post().to(create_withdrawal))
 }
 
 /// 支付订单路由
@@ -37,6 +46,7 @@ fn payment_routes() -> Scope {
         .route("", web::get().to(list_payments))
         .route("/{payment_id}", web::get().to(get_payment))
         .route("/{payment_id}/qrcode", web::get().to(get_payment_qrcode))
+        .route("/{payment_id}/late-payment", web::post().to(resolve_late_payment))
 }
 
 /// Webhook路由
@@ -46,9 +56,19 @@ fn webhook_routes() -> Scope {
         .route("/stats", web::get().to(get_webhook_stats))
 }
 
+/// 管理接口路由 (需要管理接口密钥)
+fn admin_routes() -> Scope {
+    web::scope("/admin")
+        .route("/tokens", web::get().to(admin_list_tokens))
+        .route("/tokens", web::post().to(create_token))
+        .route("/tokens/{token_id}", web::put().to(update_token))
+}
 
 /// 公共路由 (无需认证)
 pub fn public_routes() -> Scope {
     web::scope("")
         .route("/health", web::get().to(health_check))
+        // 托管收银台 (订单ID即访问凭证)
+        .route("/checkout/{payment_id}", web::get().to(checkout_page))
+        .route("/checkout/{payment_id}/status", web::get().to(checkout_status))
 }
\ No newline at end of file
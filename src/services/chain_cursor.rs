@@ -0,0 +1,201 @@
+// 区块扫描游标服务
+// 持久化最近处理的区块哈希，校验父哈希以检测区块重组并定位分叉点
+
+use ethers::{
+    prelude::*,
+    providers::{Provider, Http},
+    types::H256,
+};
+use sqlx::PgPool;
+use anyhow::{Result, Context};
+use std::sync::Arc;
+use crate::models::{Chain, Currency, PaymentMode};
+use crate::services::{EthereumService, WebhookService};
+
+/// 每个游标保留的区块记录数 (可处理的最大重组深度)
+const CURSOR_HISTORY_BLOCKS: i64 = 256;
+
+/// 区块扫描游标
+///
+/// 每条链的每个扫描任务使用独立的游标名称 (`链:任务`)，记录已处理区块的哈希与父哈希
+pub struct ChainCursor {
+    name: String,
+    provider: Arc<Provider<Http>>,
+    pool: PgPool,
+}
+
+impl ChainCursor {
+    /// 创建新的扫描游标
+    ///
+    /// # Arguments
+    /// * `chain` - 扫描的链
+    /// * `task` - 扫描任务名称 (同一条链上唯一)
+    /// * `provider` - 该链的RPC Provider
+    /// * `pool` - 数据库连接池
+    pub fn new(chain: Chain, task: &str, provider: Arc<Provider<Http>>, pool: PgPool) -> Self {
+        Self { name: format!("{}:{}", chain, task), provider, pool }
+    }
+
+    /// 获取下一个待处理的区块号
+    ///
+    /// # Arguments
+    /// * `default_block` - 游标为空时的起始区块
+    pub async fn next_block(&self, default_block: u64) -> Result<u64> {
+        let last_block = sqlx::query_scalar!(
+            "SELECT MAX(block_number) FROM chain_cursor WHERE name = $1",
+            self.name
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to get chain cursor")?;
+
+        Ok(last_block.map_or(default_block, |block| block as u64 + 1))
+    }
+
+    /// 校验待处理区块的父哈希
+    ///
+    /// # Arguments
+    /// * `number` - 待处理区块号
+    /// * `parent_hash` - 该区块的父哈希
+    ///
+    /// # Returns
+    /// * 发生重组时返回分叉点 (仍在主链上的最后一个已处理区块)
+    pub async fn check_parent(&self, number: u64, parent_hash: H256) -> Result<Option<u64>> {
+        let stored_hash = sqlx::query_scalar!(
+            "SELECT block_hash FROM chain_cursor WHERE name = $1 AND block_number = $2",
+            self.name,
+            number as i64 - 1
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to get cursor block hash")?;
+
+        match stored_hash {
+            Some(hash) if hash != format!("{:?}", parent_hash) => {
+                let fork_block = self.find_fork_point().await?;
+                log::warn!("Chain reorg detected by {} at block {}, rolling back to block {}",
+                    self.name, number, fork_block);
+                Ok(Some(fork_block))
+            },
+            _ => Ok(None),
+        }
+    }
+
+    /// 记录已处理的区块，并清理超出保留范围的旧记录
+    pub async fn record_block(&self, number: u64, hash: H256, parent_hash: H256) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO chain_cursor (name, block_number, block_hash, parent_hash)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (name, block_number)
+            DO UPDATE SET block_hash = EXCLUDED.block_hash, parent_hash = EXCLUDED.parent_hash, created_at = NOW()
+            "#,
+            self.name,
+            number as i64,
+            format!("{:?}", hash),
+            format!("{:?}", parent_hash)
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to record cursor block")?;
+
+        sqlx::query!(
+            "DELETE FROM chain_cursor WHERE name = $1 AND block_number <= $2",
+            self.name,
+            number as i64 - CURSOR_HISTORY_BLOCKS
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to prune chain cursor")?;
+
+        Ok(())
+    }
+
+    /// 处理区块重组
+    ///
+    /// 回滚分叉点之后的到账交易并回退游标，状态被回滚的支付重新发送Webhook通知
+    ///
+    /// # Arguments
+    /// * `fork_block` - 分叉点
+    /// * `ethereum_service` - 以太坊服务
+    /// * `currencies` - 该扫描任务负责的币种
+    /// * `mode` - 该扫描任务负责的收款模式
+    pub async fn rollback(
+        &self,
+        fork_block: u64,
+        ethereum_service: &EthereumService,
+        currencies: &[Currency],
+        mode: PaymentMode,
+    ) -> Result<()> {
+        let reverted = ethereum_service.rollback_transfers_after(fork_block, currencies, mode, &self.pool).await?;
+        self.rewind(fork_block).await?;
+
+        for payment_id in reverted {
+            let webhook_service = WebhookService::new(self.pool.clone(), 5);
+            tokio::spawn(async move {
+                if let Err(e) = webhook_service.notify_payment_status(payment_id).await {
+                    log::error!("Failed to notify reverted payment {}: {}", payment_id, e);
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    /// 将游标回退到分叉点，删除孤块记录
+    async fn rewind(&self, fork_block: u64) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM chain_cursor WHERE name = $1 AND block_number > $2",
+            self.name,
+            fork_block as i64
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to rewind chain cursor")?;
+
+        Ok(())
+    }
+
+    /// 从最新记录向前查找仍在主链上的区块
+    ///
+    /// 重组深度超过保留范围时回退到最早记录之前
+    async fn find_fork_point(&self) -> Result<u64> {
+        let blocks = sqlx::query!(
+            r#"
+            SELECT block_number, block_hash
+            FROM chain_cursor
+            WHERE name = $1
+            ORDER BY block_number DESC
+            "#,
+            self.name
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get cursor blocks")?;
+
+        let mut oldest_block = 0;
+        for block in blocks {
+            let canonical = self.provider.get_block(block.block_number as u64).await
+                .context("Failed to get block")?
+                .and_then(|canonical| canonical.hash);
+
+            if canonical.map(|hash| format!("{:?}", hash)) == Some(block.block_hash) {
+                return Ok(block.block_number as u64);
+            }
+
+            oldest_block = block.block_number as u64;
+        }
+
+        // 以主链上最早记录之前的区块作为新的游标起点，避免回退后从最新区块重新开始
+        log::error!("Reorg deeper than {} tracked blocks for {}", CURSOR_HISTORY_BLOCKS, self.name);
+        let fork_block = oldest_block.saturating_sub(1);
+        let canonical = self.provider.get_block(fork_block).await
+            .context("Failed to get block")?
+            .ok_or_else(|| anyhow::anyhow!("Block {} not found", fork_block))?;
+        let hash = canonical.hash
+            .ok_or_else(|| anyhow::anyhow!("Block {} has no hash", fork_block))?;
+        self.record_block(fork_block, hash, canonical.parent_hash).await?;
+
+        Ok(fork_block)
+    }
+}
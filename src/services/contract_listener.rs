@@ -0,0 +1,276 @@
+// 合约收款事件监听服务
+// 查询Wopay合约的PaymentReceived事件，按链上订单号匹配合约收款模式的订单
+
+use ethers::{
+    prelude::*,
+    providers::{Provider, Http},
+    types::{Address, Block, H256},
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+use anyhow::{Result, Context};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+use crate::models::{Chain, Currency, PaymentMode, NATIVE_DECIMALS};
+use crate::services::{EthereumService, ChainCursor, ethereum_service::DetectedTransfer};
+use crate::utils::{Wopay, PaymentReceivedFilter, contract_merchant_id};
+
+/// 扫描游标名称
+const CURSOR_NAME: &str = "contract_payments";
+
+/// 每次 `eth_getLogs` 查询的最大区块跨度
+const MAX_BLOCKS_PER_QUERY: u64 = 500;
+
+/// 监听间隔 (秒)
+const LISTEN_INTERVAL_SECS: u64 = 12;
+
+/// 合约收款事件监听服务
+///
+/// 合约收款模式的订单共用同一个合约地址，到账通过事件中的链上订单号区分，
+/// 并核对事件中的商户ID (付款计入该商户的合约余额)。每条配置了收款合约的链运行一个实例
+pub struct ContractListener {
+    ethereum_service: EthereumService,
+    contract: Wopay<Provider<Http>>,
+    /// 该链的原生币 (合约只接收原生币)
+    currency: Currency,
+    provider: Arc<Provider<Http>>,
+    pool: PgPool,
+    cursor: ChainCursor,
+    /// 订单过期后继续监听的宽限期 (分钟)
+    late_payment_grace_minutes: i32,
+}
+
+impl ContractListener {
+    /// 创建新的合约事件监听服务
+    ///
+    /// # Arguments
+    /// * `ethereum_service` - 所监听链的以太坊服务 (需要配置收款合约地址)
+    /// * `pool` - 数据库连接池
+    /// * `late_payment_grace_minutes` - 订单过期后继续监听的宽限期 (分钟)
+    pub fn new(ethereum_service: EthereumService, pool: PgPool, late_payment_grace_minutes: i32) -> Result<Self> {
+        let chain = ethereum_service.chain();
+        let contract_address = ethereum_service.payment_contract()
+            .ok_or_else(|| anyhow::anyhow!("No payment contract configured on {}", chain))?;
+        let provider = ethereum_service.provider();
+
+        Ok(Self {
+            contract: Wopay::new(contract_address, provider.clone()),
+            cursor: ChainCursor::new(chain, CURSOR_NAME, provider.clone(), pool.clone()),
+            currency: chain.native_currency(),
+            provider,
+            ethereum_service,
+            pool,
+            late_payment_grace_minutes,
+        })
+    }
+
+    /// 启动事件监听任务
+    pub async fn start_listening(&self) -> Result<()> {
+        log::info!("Starting payment contract listener on {} ({:?})",
+            self.ethereum_service.chain(), self.contract.address());
+
+        loop {
+            match self.provider.get_block_number().await {
+                Ok(latest_block) => {
+                    let latest_block = latest_block.as_u64();
+
+                    // 从持久化游标继续，首次运行时从最近区块开始
+                    match self.cursor.next_block(latest_block.saturating_sub(MAX_BLOCKS_PER_QUERY)).await {
+                        Ok(next_block) if next_block <= latest_block => {
+                            let to_block = latest_block.min(next_block + MAX_BLOCKS_PER_QUERY - 1);
+
+                            if let Err(e) = self.process_blocks(next_block, to_block).await {
+                                log::error!("Failed to process contract events in blocks {}-{}: {}", next_block, to_block, e);
+                            }
+                        },
+                        Ok(_) => {},
+                        Err(e) => log::error!("Failed to get chain cursor: {}", e),
+                    }
+                },
+                Err(e) => log::warn!("Failed to get latest block: {}", e),
+            }
+
+            sleep(Duration::from_secs(LISTEN_INTERVAL_SECS)).await;
+        }
+    }
+
+    /// 处理区块范围内的PaymentReceived事件并更新支付状态
+    ///
+    /// # Arguments
+    /// * `from_block` - 起始区块 (包含)
+    /// * `to_block` - 结束区块 (包含)
+    ///
+    /// # Returns
+    /// * 匹配到订单的事件数量
+    pub async fn process_blocks(&self, from_block: u64, to_block: u64) -> Result<usize> {
+        let mut detected = 0;
+
+        // 父哈希与游标不一致说明发生重组，回滚后下一轮从分叉点继续
+        let first_block = self.get_block_header(from_block).await?;
+        if let Some(fork_block) = self.cursor.check_parent(from_block, first_block.parent_hash).await? {
+            self.cursor.rollback(fork_block, &self.ethereum_service, &[self.currency.clone()], PaymentMode::Contract).await?;
+            return Ok(detected);
+        }
+
+        let last_block = if to_block == from_block {
+            first_block
+        } else {
+            self.get_block_header(to_block).await?
+        };
+        let last_block_hash = last_block.hash
+            .ok_or_else(|| anyhow::anyhow!("Block {} has no hash", to_block))?;
+
+        let events = self.contract.payment_received_filter()
+            .from_block(from_block)
+            .to_block(to_block)
+            .query_with_meta()
+            .await
+            .context("Failed to get PaymentReceived logs")?;
+
+        if !events.is_empty() {
+            let payments = self.get_active_payments().await?;
+
+            for (payment_id, transfer) in match_payment_events(&events, &payments, self.contract.address(), &self.currency) {
+                log::info!("Detected contract payment of {} wei from {:?} in tx {:?} for payment {}",
+                    transfer.value, transfer.from, transfer.tx_hash, payment_id);
+
+                // 处理失败时不推进游标，下一轮重新处理该区块范围
+                self.ethereum_service.process_transfer(payment_id, &transfer, &self.pool).await
+                    .with_context(|| format!("Failed to process contract payment {:?}", transfer.tx_hash))?;
+
+                detected += 1;
+            }
+        }
+
+        self.cursor.record_block(to_block, last_block_hash, last_block.parent_hash).await?;
+
+        Ok(detected)
+    }
+
+    /// 获取区块头
+    async fn get_block_header(&self, number: u64) -> Result<Block<H256>> {
+        self.provider.get_block(number).await
+            .context("Failed to get block")?
+            .ok_or_else(|| anyhow::anyhow!("Block {} not found", number))
+    }
+
+    /// 获取等待合约付款的订单 (按链上订单号索引，包括宽限期内的过期订单)
+    async fn get_active_payments(&self) -> Result<HashMap<U256, ContractOrder>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, merchant_id, contract_order_id as "contract_order_id!"
+            FROM payments
+            WHERE chain = $1 AND payment_mode = 'contract'
+              AND (status IN ('pending', 'detected', 'underpaid', 'confirmed')
+                   OR (status IN ('expired', 'paid_after_expiry')
+                       AND expires_at > NOW() - make_interval(mins => $2)))
+            "#,
+            self.ethereum_service.chain() as Chain,
+            self.late_payment_grace_minutes
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch active contract payments")?;
+
+        rows.into_iter()
+            .map(|row| {
+                let order_id = U256::from_dec_str(&row.contract_order_id)
+                    .with_context(|| format!("Invalid contract order ID for payment {}", row.id))?;
+                Ok((order_id, ContractOrder { payment_id: row.id, merchant_id: row.merchant_id }))
+            })
+            .collect()
+    }
+}
+
+/// 等待合约付款的订单
+#[derive(Debug, Clone, Copy)]
+struct ContractOrder {
+    payment_id: Uuid,
+    merchant_id: Uuid,
+}
+
+/// 按链上订单号匹配PaymentReceived事件
+///
+/// 付款人即事件中的sender，到账地址记为合约地址。
+/// 商户ID与订单不符的付款计入了其他商户的余额，不作为该订单的到账
+fn match_payment_events(
+    events: &[(PaymentReceivedFilter, LogMeta)],
+    payments: &HashMap<U256, ContractOrder>,
+    contract: Address,
+    currency: &Currency,
+) -> Vec<(Uuid, DetectedTransfer)> {
+    events.iter()
+        .filter_map(|(event, meta)| {
+            let order = payments.get(&event.order_id)?;
+            if event.amount.is_zero() {
+                return None;
+            }
+            if event.merchant_id != contract_merchant_id(order.merchant_id) {
+                log::warn!("Contract payment {:?} for payment {} credited merchant {} instead of {}",
+                    meta.transaction_hash, order.payment_id, event.merchant_id, order.merchant_id);
+                return None;
+            }
+
+            Some((order.payment_id, DetectedTransfer {
+                tx_hash: meta.transaction_hash,
+                from: event.sender,
+                to: contract,
+                value: event.amount,
+                currency: currency.clone(),
+                decimals: NATIVE_DECIMALS,
+                block_number: Some(meta.block_number),
+                log_index: Some(meta.log_index),
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(order_id: u64, merchant_id: Uuid, amount: u64) -> (PaymentReceivedFilter, LogMeta) {
+        let meta = LogMeta {
+            address: Address::repeat_byte(0xcc),
+            block_number: 100.into(),
+            block_hash: H256::repeat_byte(0x01),
+            transaction_hash: H256::repeat_byte(0xab),
+            transaction_index: 0.into(),
+            log_index: U256::from(order_id),
+        };
+        let event = PaymentReceivedFilter {
+            order_id: U256::from(order_id),
+            merchant_id: contract_merchant_id(merchant_id),
+            amount: U256::from(amount),
+            sender: Address::repeat_byte(0x22),
+        };
+        (event, meta)
+    }
+
+    #[test]
+    fn test_match_payment_events() {
+        let contract = Address::repeat_byte(0xcc);
+        let payment_id = Uuid::new_v4();
+        let merchant_id = Uuid::new_v4();
+        let payments = HashMap::from([
+            (U256::from(7), ContractOrder { payment_id, merchant_id }),
+            (U256::from(9), ContractOrder { payment_id: Uuid::new_v4(), merchant_id }),
+        ]);
+
+        // 订单8未登记，订单9的付款计入了其他商户
+        let events = vec![
+            event(7, merchant_id, 1_000_000),
+            event(8, merchant_id, 1_000_000),
+            event(9, Uuid::new_v4(), 1_000_000),
+        ];
+
+        let transfers = match_payment_events(&events, &payments, contract, &Currency::ETH);
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].0, payment_id);
+        assert_eq!(transfers[0].1.from, Address::repeat_byte(0x22));
+        assert_eq!(transfers[0].1.to, contract);
+        assert_eq!(transfers[0].1.value, U256::from(1_000_000u64));
+        assert_eq!(transfers[0].1.log_index, Some(U256::from(7)));
+    }
+}
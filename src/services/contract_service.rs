@@ -0,0 +1,423 @@
+// 合约余额与提款服务
+// 查询商户在Wopay合约中的余额，同步商户提款地址并提交提款交易
+
+use ethers::{
+    prelude::*,
+    providers::{Provider, Http},
+    signers::{LocalWallet, Signer},
+    types::{Address, U256},
+    utils::{format_ether, parse_ether},
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+use anyhow::{Result, Context};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use std::sync::Arc;
+use crate::models::{Chain, Currency, ContractBalance, ContractWithdrawal, CreateWithdrawalRequest, TransactionStatus, NATIVE_DECIMALS};
+use crate::services::EthereumService;
+use crate::utils::{Wopay, contract_merchant_id};
+
+/// 合约管理者签名的客户端
+type OwnerClient = SignerMiddleware<Arc<Provider<Http>>, LocalWallet>;
+
+/// 提款交易在节点中查不到时判定为已丢弃的超时时间 (分钟)
+const WITHDRAWAL_DROP_TIMEOUT_MINUTES: i64 = 30;
+
+/// 合约余额与提款服务
+///
+/// 余额以合约中的 `merchantBalance` 为准，提款资金只会转入合约中登记的提款地址。
+/// 提款交易由合约管理者签名，未配置管理者私钥时只能查询余额
+pub struct ContractService {
+    ethereum_service: EthereumService,
+    contract: Wopay<Provider<Http>>,
+    /// 管理者签名的合约实例 (未配置管理者私钥时为None)
+    owner_contract: Option<Wopay<OwnerClient>>,
+    pool: PgPool,
+}
+
+impl ContractService {
+    /// 创建新的合约服务实例
+    ///
+    /// # Arguments
+    /// * `ethereum_service` - 合约所在链的以太坊服务 (需要配置收款合约地址)
+    /// * `pool` - 数据库连接池
+    /// * `owner_key` - 合约管理者私钥 (可选)
+    pub fn new(ethereum_service: EthereumService, pool: PgPool, owner_key: Option<&str>) -> Result<Self> {
+        let chain = ethereum_service.chain();
+        let contract_address = ethereum_service.payment_contract()
+            .ok_or_else(|| anyhow::anyhow!("No payment contract configured on {}", chain))?;
+        let provider = ethereum_service.provider();
+
+        let owner_contract = match owner_key {
+            Some(key) => {
+                let wallet = key.parse::<LocalWallet>()
+                    .context("Invalid contract owner private key")?
+                    .with_chain_id(ethereum_service.chain_id());
+                let client = SignerMiddleware::new(provider.clone(), wallet);
+                Some(Wopay::new(contract_address, Arc::new(client)))
+            },
+            None => None,
+        };
+
+        Ok(Self {
+            contract: Wopay::new(contract_address, provider),
+            owner_contract,
+            ethereum_service,
+            pool,
+        })
+    }
+
+    /// 获取服务所在的链
+    pub fn chain(&self) -> Chain {
+        self.ethereum_service.chain()
+    }
+
+    /// 查询商户在合约中的余额
+    ///
+    /// # Arguments
+    /// * `merchant_id` - 商户ID
+    pub async fn get_balance(&self, merchant_id: Uuid) -> Result<ContractBalance> {
+        let contract_merchant = contract_merchant_id(merchant_id);
+
+        let balance = self.contract.merchant_balance(contract_merchant).call().await
+            .context("Failed to get merchant contract balance")?;
+        let payout = self.contract.merchant_payout(contract_merchant).call().await
+            .context("Failed to get merchant payout address")?;
+
+        let pending_withdrawals = self.pending_withdrawal_amount(merchant_id).await?;
+
+        Ok(ContractBalance {
+            chain: self.chain(),
+            currency: self.chain().native_currency(),
+            contract_address: format!("{:?}", self.contract.address()),
+            balance: wei_to_decimal(balance)?,
+            pending_withdrawals,
+            payout_address: (!payout.is_zero()).then(|| format!("{:?}", payout)),
+        })
+    }
+
+    /// 提交提款
+    ///
+    /// 合约中登记的提款地址与商户配置不一致时先登记新地址 (等待上链)，
+    /// 再提交提款交易；提款交易的上链结果由 `track_pending_withdrawals` 跟踪。
+    /// 同一条链的提款持有数据库咨询锁串行处理，余额检查、发送交易与记录提款之间不会插入其他提款
+    ///
+    /// # Arguments
+    /// * `merchant_id` - 商户ID
+    /// * `payout_address` - 商户配置的提款地址
+    /// * `request` - 提款请求
+    ///
+    /// # Returns
+    /// * 提款记录
+    pub async fn withdraw(
+        &self,
+        merchant_id: Uuid,
+        payout_address: Option<&str>,
+        request: CreateWithdrawalRequest,
+    ) -> Result<ContractWithdrawal> {
+        let owner_contract = self.owner_contract.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Contract withdrawals are not enabled on {}", self.chain()))?;
+        let payout: Address = payout_address
+            .ok_or_else(|| anyhow::anyhow!("Payout address is not set"))?
+            .parse()
+            .context("Invalid payout address")?;
+
+        let contract_merchant = contract_merchant_id(merchant_id);
+
+        // 锁在事务提交 (提款记录写入) 后释放，多个服务实例之间同样生效
+        let mut tx = self.pool.begin().await
+            .context("Failed to begin transaction")?;
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(format!("contract_withdrawals:{}", self.chain()))
+            .execute(&mut *tx)
+            .await
+            .context("Failed to lock contract withdrawals")?;
+
+        // 已提交未上链的提款尚未从合约余额中扣除
+        let balance = wei_to_decimal(self.contract.merchant_balance(contract_merchant).call().await
+            .context("Failed to get merchant contract balance")?)?;
+        let available = balance - self.pending_withdrawal_amount(merchant_id).await?;
+
+        let amount = request.amount.unwrap_or(available);
+        validate_withdrawal_amount(amount, available)?;
+
+        // 同步提款地址
+        let registered_payout = self.contract.merchant_payout(contract_merchant).call().await
+            .context("Failed to get merchant payout address")?;
+        if registered_payout != payout {
+            let receipt = owner_contract.set_merchant_payout(contract_merchant, payout)
+                .send().await
+                .context("Failed to send payout address update")?
+                .await
+                .context("Failed to wait for payout address update")?
+                .ok_or_else(|| anyhow::anyhow!("Payout address update was dropped"))?;
+
+            if receipt.status != Some(1u64.into()) {
+                anyhow::bail!("Payout address update failed in tx {:?}", receipt.transaction_hash);
+            }
+
+            log::info!("Registered payout address {:?} for merchant {} on {}", payout, merchant_id, self.chain());
+        }
+
+        let wei = parse_ether(amount.to_string())
+            .context("Invalid withdrawal amount")?;
+
+        // 记录提款交易的nonce，交易被丢弃时据此判断
+        let sender = owner_contract.client().address();
+        let nonce = self.ethereum_service.provider()
+            .get_transaction_count(sender, Some(BlockNumber::Pending.into()))
+            .await
+            .context("Failed to get contract owner nonce")?;
+        let pending = owner_contract.withdraw(contract_merchant, wei)
+            .nonce(nonce)
+            .send().await
+            .context("Failed to send withdrawal")?;
+        let tx_hash = pending.tx_hash();
+
+        let withdrawal = sqlx::query_as!(
+            ContractWithdrawal,
+            r#"
+            INSERT INTO contract_withdrawals (merchant_id, chain, currency, amount, payout_address, tx_hash, sender_address, nonce)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, merchant_id, chain as "chain: _", currency as "currency: _", amount,
+                      payout_address, tx_hash, status as "status: _", block_number, created_at, updated_at
+            "#,
+            merchant_id,
+            self.chain() as Chain,
+            self.chain().native_currency() as Currency,
+            amount,
+            format!("{:?}", payout),
+            format!("{:?}", tx_hash),
+            format!("{:?}", sender),
+            nonce.as_u64() as i64
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to record withdrawal")?;
+
+        tx.commit().await
+            .context("Failed to commit withdrawal")?;
+
+        log::info!("Submitted withdrawal of {} {} for merchant {} to {:?} in tx {:?}",
+            amount, withdrawal.currency, merchant_id, payout, tx_hash);
+
+        Ok(withdrawal)
+    }
+
+    /// 查询商户在各链的提款记录
+    ///
+    /// # Arguments
+    /// * `pool` - 数据库连接池
+    /// * `merchant_id` - 商户ID
+    pub async fn list_withdrawals(pool: &PgPool, merchant_id: Uuid) -> Result<Vec<ContractWithdrawal>> {
+        let withdrawals = sqlx::query_as!(
+            ContractWithdrawal,
+            r#"
+            SELECT id, merchant_id, chain as "chain: _", currency as "currency: _", amount,
+                   payout_address, tx_hash, status as "status: _", block_number, created_at, updated_at
+            FROM contract_withdrawals
+            WHERE merchant_id = $1
+            ORDER BY created_at DESC
+            "#,
+            merchant_id
+        )
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch withdrawals")?;
+
+        Ok(withdrawals)
+    }
+
+    /// 跟踪待上链的提款交易
+    ///
+    /// 已被丢弃的提款交易 (见 `is_withdrawal_dropped`) 标记为失败，提款金额重新计入可提款余额
+    ///
+    /// # Returns
+    /// * 本轮已上链或已丢弃的提款数量
+    pub async fn track_pending_withdrawals(&self) -> Result<usize> {
+        let pending = sqlx::query!(
+            r#"
+            SELECT id, tx_hash, sender_address, nonce, created_at
+            FROM contract_withdrawals
+            WHERE chain = $1 AND status = 'pending'
+            ORDER BY created_at ASC
+            "#,
+            self.chain() as Chain
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch pending withdrawals")?;
+
+        let provider = self.ethereum_service.provider();
+        let mut settled = 0;
+
+        for withdrawal in pending {
+            let hash: H256 = withdrawal.tx_hash.parse()
+                .context("Invalid transaction hash")?;
+
+            let (status, block_number) = match provider.get_transaction_receipt(hash).await
+                .context("Failed to get withdrawal receipt")?
+            {
+                Some(receipt) if receipt.status == Some(1u64.into()) => {
+                    (TransactionStatus::Confirmed, receipt.block_number)
+                },
+                Some(receipt) => {
+                    log::warn!("Withdrawal {} failed in tx {:?}", withdrawal.id, hash);
+                    (TransactionStatus::Failed, receipt.block_number)
+                },
+                None => {
+                    let dropped = self.is_withdrawal_dropped(
+                        hash,
+                        withdrawal.sender_address.as_deref(),
+                        withdrawal.nonce,
+                        withdrawal.created_at,
+                    ).await?;
+                    if !dropped {
+                        continue;
+                    }
+                    log::warn!("Withdrawal {} tx {:?} was dropped", withdrawal.id, hash);
+                    (TransactionStatus::Failed, None)
+                },
+            };
+
+            sqlx::query!(
+                r#"
+                UPDATE contract_withdrawals
+                SET status = $2, block_number = $3
+                WHERE id = $1
+                "#,
+                withdrawal.id,
+                status as TransactionStatus,
+                block_number.map(|block| block.as_u64() as i64)
+            )
+            .execute(&self.pool)
+            .await
+            .context("Failed to update withdrawal status")?;
+
+            settled += 1;
+        }
+
+        Ok(settled)
+    }
+
+    /// 判断未上链的提款交易是否已被丢弃
+    ///
+    /// 节点中查不到该交易，且发送账户的nonce已越过该交易 (nonce被其他交易占用) 或提交后超过超时时间。
+    /// 先查询nonce再查询交易，避免交易恰好在两次查询之间上链时被误判
+    ///
+    /// # Arguments
+    /// * `hash` - 提款交易哈希
+    /// * `sender` - 发送提款交易的管理者地址 (早期记录为空)
+    /// * `nonce` - 提款交易的nonce (早期记录为空)
+    /// * `created_at` - 提款提交时间
+    async fn is_withdrawal_dropped(
+        &self,
+        hash: H256,
+        sender: Option<&str>,
+        nonce: Option<i64>,
+        created_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let provider = self.ethereum_service.provider();
+
+        let nonce_passed = match (sender, nonce) {
+            (Some(sender), Some(nonce)) => {
+                let sender: Address = sender.parse()
+                    .context("Invalid withdrawal sender address")?;
+                let latest = provider.get_transaction_count(sender, Some(BlockNumber::Latest.into())).await
+                    .context("Failed to get withdrawal sender nonce")?;
+                latest > U256::from(nonce as u64)
+            },
+            _ => false,
+        };
+
+        if provider.get_transaction(hash).await
+            .context("Failed to get withdrawal transaction")?
+            .is_some()
+        {
+            return Ok(false);
+        }
+
+        Ok(nonce_passed || drop_timed_out(created_at, Utc::now()))
+    }
+
+    /// 已提交未上链的提款金额
+    async fn pending_withdrawal_amount(&self, merchant_id: Uuid) -> Result<Decimal> {
+        let amount = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(amount), 0) as "amount!"
+            FROM contract_withdrawals
+            WHERE merchant_id = $1 AND chain = $2 AND status = 'pending'
+            "#,
+            merchant_id,
+            self.chain() as Chain
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to sum pending withdrawals")?;
+
+        Ok(amount)
+    }
+}
+
+/// 将原生币最小单位换算为金额
+fn wei_to_decimal(value: U256) -> Result<Decimal> {
+    Decimal::from_str(&format_ether(value))
+        .context("Invalid contract balance")
+}
+
+/// 节点中查不到的提款交易是否已超过丢弃超时时间
+fn drop_timed_out(created_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    now - created_at > chrono::Duration::minutes(WITHDRAWAL_DROP_TIMEOUT_MINUTES)
+}
+
+/// 验证提款金额
+///
+/// # Arguments
+/// * `amount` - 提款金额
+/// * `available` - 可提款余额 (合约余额扣除提款中的金额)
+fn validate_withdrawal_amount(amount: Decimal, available: Decimal) -> Result<()> {
+    if amount <= Decimal::ZERO {
+        anyhow::bail!("Withdrawal amount must be positive");
+    }
+    if amount.normalize().scale() > NATIVE_DECIMALS {
+        anyhow::bail!("Withdrawal amount has more than {} decimal places", NATIVE_DECIMALS);
+    }
+    if amount > available {
+        anyhow::bail!("Withdrawal amount exceeds available balance {}", available);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_withdrawal_amount() {
+        let available = Decimal::ONE;
+
+        assert!(validate_withdrawal_amount(Decimal::new(5, 1), available).is_ok());
+        assert!(validate_withdrawal_amount(Decimal::ONE, available).is_ok());
+        assert!(validate_withdrawal_amount(Decimal::new(1_000_000_000_000_000_001, 18), available).is_err());
+        assert!(validate_withdrawal_amount(Decimal::new(1, 19), available).is_err());
+        assert!(validate_withdrawal_amount(Decimal::ZERO, available).is_err());
+        assert!(validate_withdrawal_amount(Decimal::NEGATIVE_ONE, available).is_err());
+    }
+
+    #[test]
+    fn test_drop_timed_out() {
+        let created_at = Utc::now();
+
+        assert!(!drop_timed_out(created_at, created_at));
+        assert!(!drop_timed_out(created_at, created_at + chrono::Duration::minutes(WITHDRAWAL_DROP_TIMEOUT_MINUTES)));
+        assert!(drop_timed_out(created_at, created_at + chrono::Duration::minutes(WITHDRAWAL_DROP_TIMEOUT_MINUTES + 1)));
+    }
+
+    #[test]
+    fn test_wei_to_decimal() {
+        assert_eq!(wei_to_decimal(U256::exp10(18)).unwrap(), Decimal::ONE);
+        assert_eq!(wei_to_decimal(U256::from(1)).unwrap(), Decimal::new(1, 18));
+    }
+}
@@ -0,0 +1,375 @@
+// 区块扫描充值检测服务
+// 逐块扫描交易 (以及节点支持时的内部转账)，匹配活跃的原生币支付地址
+
+use ethers::{
+    prelude::*,
+    providers::{Provider, Http, ProviderError, RpcError},
+    types::{Address, Block, Transaction, Trace, Action, CallType, BlockNumber},
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+use anyhow::{Result, Context};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::time::{sleep, Duration};
+use crate::models::{Chain, Currency, PaymentMode, NATIVE_DECIMALS};
+use crate::services::{EthereumService, ChainCursor, ethereum_service::DetectedTransfer};
+
+/// 扫描游标名称
+const CURSOR_NAME: &str = "native_deposits";
+
+/// 每轮最多扫描的区块数
+const MAX_BLOCKS_PER_SCAN: u64 = 50;
+
+/// 扫描间隔 (秒)
+const SCAN_INTERVAL_SECS: u64 = 12;
+
+/// 区块扫描充值检测服务
+///
+/// 普通原生币转账不产生日志，无法通过 `get_logs` 检测，
+/// 需要遍历每个新区块的交易并匹配收款地址。每条链运行一个实例
+pub struct DepositScanner {
+    ethereum_service: EthereumService,
+    /// 该链的原生币
+    currency: Currency,
+    provider: Arc<Provider<Http>>,
+    pool: PgPool,
+    cursor: ChainCursor,
+    /// 订单过期后继续监听的宽限期 (分钟)
+    late_payment_grace_minutes: i32,
+    /// 节点是否支持 `trace_block`，不支持时只检测外部交易
+    trace_supported: AtomicBool,
+}
+
+impl DepositScanner {
+    /// 创建新的区块扫描服务
+    ///
+    /// # Arguments
+    /// * `ethereum_service` - 所扫描链的以太坊服务
+    /// * `pool` - 数据库连接池
+    /// * `late_payment_grace_minutes` - 订单过期后继续监听收款地址的宽限期 (分钟)
+    pub fn new(ethereum_service: EthereumService, pool: PgPool, late_payment_grace_minutes: i32) -> Self {
+        let provider = ethereum_service.provider();
+        let chain = ethereum_service.chain();
+
+        Self {
+            cursor: ChainCursor::new(chain, CURSOR_NAME, provider.clone(), pool.clone()),
+            currency: chain.native_currency(),
+            provider,
+            ethereum_service,
+            pool,
+            late_payment_grace_minutes,
+            trace_supported: AtomicBool::new(true),
+        }
+    }
+
+    /// 启动区块扫描任务
+    pub async fn start_scanning(&self) -> Result<()> {
+        log::info!("Starting block scanning deposit detector on {}", self.ethereum_service.chain());
+
+        loop {
+            match self.provider.get_block_number().await {
+                Ok(latest_block) => {
+                    let latest_block = latest_block.as_u64();
+
+                    // 从持久化游标继续，首次运行时从最近区块开始
+                    match self.cursor.next_block(latest_block.saturating_sub(MAX_BLOCKS_PER_SCAN)).await {
+                        Ok(next_block) if next_block <= latest_block => {
+                            let to_block = latest_block.min(next_block + MAX_BLOCKS_PER_SCAN - 1);
+
+                            if let Err(e) = self.scan_blocks(next_block, to_block).await {
+                                log::error!("Failed to scan blocks {}-{}: {}", next_block, to_block, e);
+                            }
+                        },
+                        Ok(_) => {},
+                        Err(e) => log::error!("Failed to get chain cursor: {}", e),
+                    }
+                },
+                Err(e) => log::warn!("Failed to get latest block: {}", e),
+            }
+
+            sleep(Duration::from_secs(SCAN_INTERVAL_SECS)).await;
+        }
+    }
+
+    /// 扫描区块范围内的原生币转入交易并更新支付状态
+    ///
+    /// # Arguments
+    /// * `from_block` - 起始区块 (包含)
+    /// * `to_block` - 结束区块 (包含)
+    ///
+    /// # Returns
+    /// * 检测到的转入交易数量
+    pub async fn scan_blocks(&self, from_block: u64, to_block: u64) -> Result<usize> {
+        let addresses = self.get_active_addresses().await?;
+        let mut detected = 0;
+
+        for number in from_block..=to_block {
+            let block = self.provider.get_block_with_txs(number).await
+                .context("Failed to get block")?
+                .ok_or_else(|| anyhow::anyhow!("Block {} not found", number))?;
+            let block_hash = block.hash
+                .ok_or_else(|| anyhow::anyhow!("Block {} has no hash", number))?;
+
+            // 父哈希与游标不一致说明发生重组，回滚后下一轮从分叉点继续
+            if let Some(fork_block) = self.cursor.check_parent(number, block.parent_hash).await? {
+                self.cursor.rollback(fork_block, &self.ethereum_service, &[self.currency.clone()], PaymentMode::Address).await?;
+                return Ok(detected);
+            }
+
+            if !addresses.is_empty() {
+                let mut transfers = match_block_transfers(&block, &addresses, &self.currency);
+                transfers.extend(self.internal_transfers(number, &addresses).await?);
+
+                for (payment_id, transfer) in transfers {
+                    log::info!("Detected {:?} deposit of {} wei to {:?} in tx {:?} for payment {}",
+                        self.currency, transfer.value, transfer.to, transfer.tx_hash, payment_id);
+
+                    // 处理失败时不推进游标，下一轮重新扫描该区块
+                    self.ethereum_service.process_transfer(payment_id, &transfer, &self.pool).await
+                        .with_context(|| format!("Failed to process deposit {:?}", transfer.tx_hash))?;
+
+                    detected += 1;
+                }
+            }
+
+            self.cursor.record_block(number, block_hash, block.parent_hash).await?;
+        }
+
+        Ok(detected)
+    }
+
+    /// 通过 `trace_block` 获取合约内部转账，节点不支持时返回空
+    ///
+    /// 只有节点明确不支持该方法时才关闭内部转账检测，
+    /// 其他错误 (如RPC超时) 返回Err，由调用方保留游标重新扫描该区块
+    async fn internal_transfers(
+        &self,
+        number: u64,
+        addresses: &HashMap<Address, Uuid>,
+    ) -> Result<Vec<(Uuid, DetectedTransfer)>> {
+        if !self.trace_supported.load(Ordering::Relaxed) {
+            return Ok(Vec::new());
+        }
+
+        match self.provider.trace_block(BlockNumber::Number(number.into())).await {
+            Ok(traces) => Ok(match_internal_transfers(&traces, addresses, &self.currency)),
+            Err(e) if is_method_unsupported(&e) => {
+                log::warn!("trace_block not available, internal transfers will not be detected: {}", e);
+                self.trace_supported.store(false, Ordering::Relaxed);
+                Ok(Vec::new())
+            },
+            Err(e) => Err(e).with_context(|| format!("Failed to trace block {}", number)),
+        }
+    }
+
+    /// 获取等待该链原生币付款的支付地址 (包括少付待补款、确认中可能追加付款以及宽限期内的过期订单)
+    async fn get_active_addresses(&self) -> Result<HashMap<Address, Uuid>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT pa.address, pa.payment_id
+            FROM payment_addresses pa
+            JOIN payments p ON p.id = pa.payment_id
+            WHERE p.chain = $1 AND p.currency = $2
+              AND (p.status IN ('pending', 'detected', 'underpaid', 'confirmed')
+                   OR (p.status IN ('expired', 'paid_after_expiry')
+                       AND p.expires_at > NOW() - make_interval(mins => $3)))
+            "#,
+            self.ethereum_service.chain() as Chain,
+            self.currency.clone() as Currency,
+            self.late_payment_grace_minutes
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch active payment addresses")?;
+
+        let mut addresses = HashMap::with_capacity(rows.len());
+        for row in rows {
+            match row.address.parse::<Address>() {
+                Ok(address) => { addresses.insert(address, row.payment_id); },
+                Err(e) => log::warn!("Invalid payment address {}: {}", row.address, e),
+            }
+        }
+
+        Ok(addresses)
+    }
+}
+
+/// 判断RPC错误是否表示节点不支持该方法
+fn is_method_unsupported(error: &ProviderError) -> bool {
+    // JSON-RPC规范中的 "Method not found"
+    if RpcError::as_error_response(error).map_or(false, |response| response.code == -32601) {
+        return true;
+    }
+
+    let message = error.to_string().to_lowercase();
+    ["method not found", "not supported", "does not exist", "not available"]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+/// 匹配区块中转入支付地址的外部交易
+fn match_block_transfers(
+    block: &Block<Transaction>,
+    addresses: &HashMap<Address, Uuid>,
+    currency: &Currency,
+) -> Vec<(Uuid, DetectedTransfer)> {
+    block.transactions.iter()
+        .filter(|tx| !tx.value.is_zero())
+        .filter_map(|tx| {
+            let to = tx.to?;
+            let payment_id = addresses.get(&to)?;
+
+            Some((*payment_id, DetectedTransfer {
+                tx_hash: tx.hash,
+                from: tx.from,
+                to,
+                value: tx.value,
+                currency: currency.clone(),
+                decimals: NATIVE_DECIMALS,
+                block_number: tx.block_number.or(block.number),
+                log_index: None,
+            }))
+        })
+        .collect()
+}
+
+/// 匹配合约内部转入支付地址的调用
+///
+/// 顶层调用 (trace_address为空) 已由外部交易覆盖，执行失败的调用不计入。
+/// 同一交易内多次转入同一地址时合并为一笔到账
+fn match_internal_transfers(
+    traces: &[Trace],
+    addresses: &HashMap<Address, Uuid>,
+    currency: &Currency,
+) -> Vec<(Uuid, DetectedTransfer)> {
+    let mut transfers: Vec<(Uuid, DetectedTransfer)> = Vec::new();
+
+    for trace in traces.iter().filter(|trace| !trace.trace_address.is_empty() && trace.error.is_none()) {
+        let call = match &trace.action {
+            Action::Call(call) if call.call_type == CallType::Call && !call.value.is_zero() => call,
+            _ => continue,
+        };
+        let (Some(payment_id), Some(tx_hash)) = (addresses.get(&call.to), trace.transaction_hash) else {
+            continue;
+        };
+
+        match transfers.iter_mut().find(|(_, t)| t.tx_hash == tx_hash && t.to == call.to) {
+            Some((_, existing)) => existing.value += call.value,
+            None => transfers.push((*payment_id, DetectedTransfer {
+                tx_hash,
+                from: call.from,
+                to: call.to,
+                value: call.value,
+                currency: currency.clone(),
+                decimals: NATIVE_DECIMALS,
+                block_number: Some(trace.block_number.into()),
+                log_index: None,
+            })),
+        }
+    }
+
+    transfers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payment_address() -> Address {
+        "0x1111111111111111111111111111111111111111".parse().unwrap()
+    }
+
+    fn call_trace(trace_address: Vec<usize>, to: Address, value: u64, error: Option<&str>) -> Trace {
+        serde_json::from_value(serde_json::json!({
+            "action": {
+                "from": "0x2222222222222222222222222222222222222222",
+                "to": format!("{:?}", to),
+                "value": format!("{:#x}", value),
+                "gas": "0x0",
+                "input": "0x",
+                "callType": "call"
+            },
+            "result": null,
+            "traceAddress": trace_address,
+            "subtraces": 0,
+            "transactionPosition": 0,
+            "transactionHash": format!("{:?}", H256::repeat_byte(0xab)),
+            "blockNumber": 100,
+            "blockHash": format!("{:?}", H256::zero()),
+            "type": "call",
+            "error": error
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_match_block_transfers() {
+        let payment_id = Uuid::new_v4();
+        let addresses = HashMap::from([(payment_address(), payment_id)]);
+
+        let deposit = Transaction {
+            hash: H256::repeat_byte(1),
+            to: Some(payment_address()),
+            value: U256::from(1000),
+            ..Default::default()
+        };
+        let zero_value = Transaction {
+            hash: H256::repeat_byte(2),
+            to: Some(payment_address()),
+            ..Default::default()
+        };
+        let other = Transaction {
+            hash: H256::repeat_byte(3),
+            to: Some(Address::repeat_byte(0x33)),
+            value: U256::from(1000),
+            ..Default::default()
+        };
+
+        let block = Block {
+            number: Some(100.into()),
+            transactions: vec![deposit, zero_value, other],
+            ..Default::default()
+        };
+
+        let transfers = match_block_transfers(&block, &addresses, &Currency::ETH);
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].0, payment_id);
+        assert_eq!(transfers[0].1.tx_hash, H256::repeat_byte(1));
+        assert_eq!(transfers[0].1.value, U256::from(1000));
+        assert_eq!(transfers[0].1.block_number, Some(100.into()));
+    }
+
+    #[test]
+    fn test_match_internal_transfers() {
+        let payment_id = Uuid::new_v4();
+        let addresses = HashMap::from([(payment_address(), payment_id)]);
+
+        let traces = vec![
+            // 顶层调用由外部交易匹配
+            call_trace(vec![], payment_address(), 1000, None),
+            call_trace(vec![0], payment_address(), 2000, None),
+            // 执行失败的内部调用
+            call_trace(vec![1], payment_address(), 3000, Some("Reverted")),
+            call_trace(vec![2], Address::repeat_byte(0x33), 4000, None),
+            // 同一交易内的第二次转入合并计算
+            call_trace(vec![3], payment_address(), 500, None),
+        ];
+
+        let transfers = match_internal_transfers(&traces, &addresses, &Currency::BNB);
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].0, payment_id);
+        assert_eq!(transfers[0].1.value, U256::from(2500));
+        assert_eq!(transfers[0].1.currency, Currency::BNB);
+        assert_eq!(transfers[0].1.block_number, Some(100.into()));
+    }
+
+    #[test]
+    fn test_is_method_unsupported() {
+        let unsupported = ProviderError::CustomError("the method trace_block does not exist/is not available".to_string());
+        assert!(is_method_unsupported(&unsupported));
+
+        let timeout = ProviderError::CustomError("request timed out".to_string());
+        assert!(!is_method_unsupported(&timeout));
+    }
+}
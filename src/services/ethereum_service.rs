@@ -1,26 +1,35 @@
 // 以太坊区块链服务
-// 负责与以太坊网络交互，包括地址生成、交易监听、余额查询等
+// 负责与以太坊网络交互，包括交易处理、确认数更新、余额查询等
 
 use ethers::{
     prelude::*,
     providers::{Provider, Ws, Http},
-    types::{Address, U256, H256, Filter, Log, TransactionRequest, Bytes},
-    utils::parse_ether,
+    types::{Address, U256, H256, TransactionRequest, Bytes},
+    utils::{parse_ether, format_ether, format_units},
 };
+use rust_decimal::Decimal;
+use std::str::FromStr;
 use sqlx::PgPool;
 use uuid::Uuid;
 use anyhow::{Result, Context};
 use std::sync::Arc;
-use tokio::time::{sleep, Duration};
-use crate::models::{PaymentStatus, Currency, BlockchainTransaction, TransactionStatus};
+use crate::config::ChainConfig;
+use crate::models::{PaymentStatus, PaymentMode, Chain, Currency, BlockchainTransaction, TransactionStatus, PaymentTolerance, ToleranceType, Token};
+use crate::services::WebhookService;
+
+/// 未配置时的默认确认数
+const DEFAULT_CONFIRMATION_BLOCKS: u64 = 12;
 
 /// 以太坊服务
 #[derive(Clone)]
 pub struct EthereumService {
     http_provider: Arc<Provider<Http>>,
     ws_provider: Option<Arc<Provider<Ws>>>,
+    chain: Chain,
     chain_id: u64,
     confirmation_blocks: u64,
+    /// Wopay收款合约地址 (未配置时不支持合约收款模式)
+    payment_contract: Option<Address>,
 }
 
 impl EthereumService {
@@ -59,60 +68,92 @@ impl EthereumService {
             anyhow::bail!("Chain ID mismatch: expected {}, got {}", chain_id, network);
         }
 
-        let confirmation_blocks = match chain_id {
-            1 => 12,  // 主网需要更多确认
-            5 => 6,   // Goerli测试网
-            _ => 6,   // 默认6个确认
-        };
-
         log::info!("Connected to Ethereum network (chain_id: {})", chain_id);
 
         Ok(Self {
             http_provider: Arc::new(http_provider),
             ws_provider,
+            chain: Chain::Ethereum,
             chain_id,
-            confirmation_blocks,
+            confirmation_blocks: DEFAULT_CONFIRMATION_BLOCKS,
+            payment_contract: None,
         })
     }
 
-    /// 生成支付地址
-    /// 
-    /// # Returns
-    /// * 新生成的以太坊地址
-    pub async fn generate_payment_address(&self) -> Result<String> {
-        // 在实际应用中，这里应该从HD钱包派生地址
-        // 为了演示，我们生成一个随机地址
-        let wallet = LocalWallet::new(&mut rand::thread_rng());
-        let address = wallet.address();
-        
-        log::debug!("Generated payment address: {:?}", address);
-        Ok(format!("{:?}", address))
+    /// 根据链配置创建服务实例
+    ///
+    /// # Arguments
+    /// * `config` - 已启用链的配置
+    pub async fn from_chain_config(config: &ChainConfig) -> Result<Self> {
+        let mut service = Self::new_with_config(
+            config.rpc_url.clone(),
+            config.ws_url.clone(),
+            config.chain_id,
+        ).await
+        .with_context(|| format!("Failed to connect to chain {}", config.chain))?;
+
+        service.chain = config.chain;
+        service.payment_contract = config.payment_contract.as_deref()
+            .map(str::parse)
+            .transpose()
+            .context("Invalid payment contract address")?;
+        Ok(service.with_confirmation_blocks(config.default_confirmations))
+    }
+
+    /// 获取服务所连接的链
+    pub fn chain(&self) -> Chain {
+        self.chain
+    }
+
+    /// 获取链ID
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// 获取Wopay收款合约地址
+    pub fn payment_contract(&self) -> Option<Address> {
+        self.payment_contract
+    }
+
+    /// 设置默认确认数 (商户规则与订单均未指定时使用)
+    ///
+    /// # Arguments
+    /// * `confirmation_blocks` - 配置的默认确认数 (`BlockchainConfig.default_confirmations`)
+    pub fn with_confirmation_blocks(mut self, confirmation_blocks: i32) -> Self {
+        self.confirmation_blocks = confirmation_blocks.max(1) as u64;
+        self
+    }
+
+    /// 获取默认确认数
+    pub fn confirmation_blocks(&self) -> u64 {
+        self.confirmation_blocks
     }
 
     /// 获取地址余额
     /// 
     /// # Arguments
     /// * `address` - 以太坊地址
-    /// * `currency` - 币种类型
+    /// * `token` - 代币
     /// 
     /// # Returns
     /// * 余额 (以最小单位计算)
-    pub async fn get_balance(&self, address: &str, currency: &Currency) -> Result<U256> {
+    pub async fn get_balance(&self, address: &str, token: &Token) -> Result<U256> {
         let address: Address = address.parse()
             .context("Invalid Ethereum address")?;
 
-        match currency {
-            Currency::ETH => {
+        if token.chain != self.chain {
+            anyhow::bail!("Currency {:?} is not supported on chain {}", token.symbol, self.chain);
+        }
+
+        match &token.contract_address {
+            None => {
                 let balance = self.http_provider.get_balance(address, None).await
-                    .context("Failed to get ETH balance")?;
+                    .context("Failed to get native balance")?;
                 Ok(balance)
             },
-            Currency::USDT => {
-                // USDT是ERC20代币，需要调用合约
-                let contract_address = currency.contract_address()
-                    .ok_or_else(|| anyhow::anyhow!("No contract address for currency"))?;
-                
-                self.get_erc20_balance(address, &contract_address).await
+            Some(contract_address) => {
+                // ERC20代币，需要调用合约
+                self.get_erc20_balance(address, contract_address).await
             }
         }
     }
@@ -126,12 +167,8 @@ impl EthereumService {
         let call = self.http_provider.call(
             &TransactionRequest::new()
                 .to(contract_addr)
-                .data(
-                    // balanceOf(address) function selector + address parameter
-                    format!("0x70a08231{:0>64}", format!("{:x}", address))
-                        .parse::<Bytes>()
-                        .context("Failed to encode function call")?
-                ),
+                .data(crate::utils::encode_erc20_balance_of(address))
+                .into(),
             None
         ).await
         .context("Failed to call balanceOf")?;
@@ -141,151 +178,19 @@ impl EthereumService {
         Ok(balance)
     }
 
-    /// 监听支付地址的交易
-    /// 
+    /// 处理检测到的转入交易，记录区块链交易并更新支付状态
+    ///
     /// # Arguments
     /// * `payment_id` - 支付订单ID
-    /// * `payment_address` - 支付地址
+    /// * `transfer` - 转入交易 (外部交易或合约内部转账)
     /// * `pool` - 数据库连接池
-    /// 
-    /// # Returns
-    /// * 监听结果
-    pub async fn monitor_payment(
-        &self,
-        payment_id: Uuid,
-        payment_address: &str,
-        pool: PgPool,
-    ) -> Result<()> {
-        let address: Address = payment_address.parse()
-            .context("Invalid payment address")?;
-
-        log::info!("Starting payment monitoring for address: {:?}", address);
-
-        // 获取当前区块号
-        let current_block = self.http_provider.get_block_number().await
-            .context("Failed to get current block number")?;
-
-        // 创建过滤器监听转入交易
-        let filter = Filter::new()
-            .address(address)
-            .from_block(current_block);
-
-        // 如果有WebSocket连接，使用实时监听
-        if let Some(ws_provider) = &self.ws_provider {
-            self.monitor_with_websocket(payment_id, address, pool, ws_provider.clone()).await
-        } else {
-            // 否则使用轮询方式
-            self.monitor_with_polling(payment_id, address, pool, current_block).await
-        }
-    }
-
-    /// 使用WebSocket实时监听
-    async fn monitor_with_websocket(
-        &self,
-        payment_id: Uuid,
-        address: Address,
-        pool: PgPool,
-        ws_provider: Arc<Provider<Ws>>,
-    ) -> Result<()> {
-        let mut stream = ws_provider.subscribe_logs(
-            &Filter::new().address(address)
-        ).await
-        .context("Failed to subscribe to logs")?;
-
-        // 设置超时时间 (1小时)
-        let timeout = tokio::time::timeout(Duration::from_secs(3600), async {
-            while let Some(log) = stream.next().await {
-                if let Err(e) = self.process_transaction_log(payment_id, log, &pool).await {
-                    log::error!("Failed to process transaction log: {}", e);
-                }
-            }
-        });
-
-        match timeout.await {
-            Ok(_) => log::info!("WebSocket monitoring completed for payment: {}", payment_id),
-            Err(_) => log::warn!("WebSocket monitoring timed out for payment: {}", payment_id),
-        }
-
-        Ok(())
-    }
-
-    /// 使用轮询方式监听
-    async fn monitor_with_polling(
+    pub async fn process_transfer(
         &self,
         payment_id: Uuid,
-        address: Address,
-        pool: PgPool,
-        start_block: U64,
-    ) -> Result<()> {
-        let mut last_checked_block = start_block;
-        let mut attempts = 0;
-        const MAX_ATTEMPTS: u32 = 720; // 1小时 (每5秒检查一次)
-
-        while attempts < MAX_ATTEMPTS {
-            sleep(Duration::from_secs(5)).await;
-            attempts += 1;
-
-            // 获取最新区块
-            let latest_block = match self.http_provider.get_block_number().await {
-                Ok(block) => block,
-                Err(e) => {
-                    log::warn!("Failed to get latest block: {}", e);
-                    continue;
-                }
-            };
-
-            if latest_block <= last_checked_block {
-                continue;
-            }
-
-            // 查询新区块中的交易
-            let filter = Filter::new()
-                .address(address)
-                .from_block(last_checked_block + 1)
-                .to_block(latest_block);
-
-            match self.http_provider.get_logs(&filter).await {
-                Ok(logs) => {
-                    for log in logs {
-                        if let Err(e) = self.process_transaction_log(payment_id, log, &pool).await {
-                            log::error!("Failed to process transaction log: {}", e);
-                        }
-                    }
-                },
-                Err(e) => {
-                    log::warn!("Failed to get logs: {}", e);
-                }
-            }
-
-            last_checked_block = latest_block;
-
-            // 检查支付状态，如果已完成则停止监听
-            if let Ok(Some(payment)) = self.get_payment_from_db(payment_id, &pool).await {
-                if payment.status == PaymentStatus::Completed || payment.status == PaymentStatus::Failed {
-                    log::info!("Payment {} completed, stopping monitoring", payment_id);
-                    break;
-                }
-            }
-        }
-
-        log::info!("Polling monitoring completed for payment: {}", payment_id);
-        Ok(())
-    }
-
-    /// 处理交易日志
-    async fn process_transaction_log(
-        &self,
-        payment_id: Uuid,
-        log: Log,
+        transfer: &DetectedTransfer,
         pool: &PgPool,
     ) -> Result<()> {
-        let tx_hash = log.transaction_hash
-            .ok_or_else(|| anyhow::anyhow!("No transaction hash in log"))?;
-
-        // 获取交易详情
-        let tx = self.http_provider.get_transaction(tx_hash).await
-            .context("Failed to get transaction")?
-            .ok_or_else(|| anyhow::anyhow!("Transaction not found"))?;
+        let tx_hash = transfer.tx_hash;
 
         // 获取交易回执
         let receipt = self.http_provider.get_transaction_receipt(tx_hash).await
@@ -294,91 +199,332 @@ impl EthereumService {
 
         // 检查交易是否成功
         let status = if receipt.status == Some(U64::from(1)) {
-            TransactionStatus::Success
+            TransactionStatus::Confirmed
         } else {
             TransactionStatus::Failed
         };
 
+        // 检查确认数
+        let current_block = self.http_provider.get_block_number().await?;
+        let confirmations = match receipt.block_number {
+            Some(tx_block) => current_block.saturating_sub(tx_block).as_u64() as i32,
+            None => 0,
+        };
+
+        // 按币种精度换算金额
+        let amount = Decimal::from_str(&format_units(transfer.value, transfer.decimals)?)
+            .context("Invalid transfer amount")?;
+        let gas_fee = match (receipt.gas_used, receipt.effective_gas_price) {
+            (Some(gas_used), Some(gas_price)) => Decimal::from_str(&format_ether(gas_used * gas_price)).ok(),
+            _ => None,
+        };
+
         // 记录区块链交易
-        let blockchain_tx_id = Uuid::new_v4();
         sqlx::query!(
             r#"
             INSERT INTO blockchain_transactions (
-                id, payment_id, tx_hash, from_address, to_address,
-                amount, gas_used, gas_price, block_number, block_hash,
-                status, created_at, updated_at
+                payment_id, blockchain, transaction_hash, log_index, from_address, to_address,
+                amount, gas_fee, block_number, block_hash, confirmations, status
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW(), NOW())
-            ON CONFLICT (tx_hash) DO NOTHING
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (transaction_hash, log_index, to_address) DO NOTHING
             "#,
-            blockchain_tx_id,
             payment_id,
+            self.chain.as_str(),
             format!("{:?}", tx_hash),
-            format!("{:?}", tx.from),
-            format!("{:?}", tx.to.unwrap_or_default()),
-            tx.value.to_string(),
-            receipt.gas_used.map(|g| g.as_u64() as i64),
-            tx.gas_price.map(|g| g.to_string()),
+            transfer.log_index.map_or(-1, |index| index.as_u64() as i64),
+            format!("{:?}", transfer.from),
+            format!("{:?}", transfer.to),
+            amount,
+            gas_fee,
             receipt.block_number.map(|b| b.as_u64() as i64),
             receipt.block_hash.map(|h| format!("{:?}", h)),
-            status as TransactionStatus
+            confirmations,
+            status.clone() as TransactionStatus
         )
         .execute(pool)
         .await
         .context("Failed to insert blockchain transaction")?;
 
         // 更新支付状态
-        if status == TransactionStatus::Success {
-            // 检查确认数
-            let current_block = self.http_provider.get_block_number().await?;
-            let confirmations = if let Some(tx_block) = receipt.block_number {
-                (current_block.as_u64() - tx_block.as_u64()) as i32
-            } else {
-                0
-            };
-
-            let payment_status = if confirmations >= self.confirmation_blocks as i32 {
-                PaymentStatus::Completed
-            } else {
-                PaymentStatus::Confirmed
-            };
-
-            sqlx::query!(
+        if status == TransactionStatus::Confirmed {
+            let payment_status = self.settle_payment(payment_id, pool).await?;
+
+            // 过期订单的迟到付款单独通知商户，由商户决定接受或退款
+            if payment_status == PaymentStatus::PaidAfterExpiry {
+                log::warn!("Payment {} received deposit {:?} after expiry", payment_id, tx_hash);
+
+                let webhook_service = WebhookService::new(pool.clone(), 5);
+                tokio::spawn(async move {
+                    if let Err(e) = webhook_service.notify_payment_status(payment_id).await {
+                        log::error!("Failed to notify late payment {}: {}", payment_id, e);
+                    }
+                });
+            }
+        } else {
+            // 交易失败 (已有到账的订单不受影响)
+            let updated = sqlx::query!(
                 r#"
                 UPDATE payments 
-                SET status = $1, transaction_hash = $2, confirmations = $3, updated_at = NOW()
-                WHERE id = $4
+                SET status = 'failed', transaction_hash = $1, updated_at = NOW()
+                WHERE id = $2 AND status = 'pending' AND amount_received = 0
                 "#,
-                payment_status as PaymentStatus,
                 format!("{:?}", tx_hash),
-                confirmations,
                 payment_id
             )
             .execute(pool)
             .await
-            .context("Failed to update payment status")?;
+            .context("Failed to update payment status to failed")?
+            .rows_affected();
+
+            if updated > 0 {
+                log::warn!("Payment {} marked as failed due to transaction failure", payment_id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 按累计到账金额与商户容差结算支付状态
+    ///
+    /// 订单状态完全由 `blockchain_transactions` 中的到账记录推导:
+    /// 累计成功到账金额决定少付/足额/多付，所有到账交易都达到确认数后才完成
+    ///
+    /// # Arguments
+    /// * `payment_id` - 支付订单ID
+    /// * `pool` - 数据库连接池
+    ///
+    /// # Returns
+    /// * 结算后的支付状态
+    pub async fn settle_payment(&self, payment_id: Uuid, pool: &PgPool) -> Result<PaymentStatus> {
+        let payment = sqlx::query!(
+            r#"
+            SELECT p.amount, p.status as "status: PaymentStatus", p.late_payment_action, p.required_confirmations,
+                   m.payment_tolerance_type as "payment_tolerance_type: ToleranceType",
+                   m.payment_tolerance,
+                   COALESCE(d.amount_received, 0) as "amount_received!",
+                   COALESCE(d.min_confirmations, 0) as "min_confirmations!",
+                   d.latest_hash
+            FROM payments p
+            JOIN merchants m ON m.id = p.merchant_id
+            LEFT JOIN LATERAL (
+                SELECT SUM(bt.amount) as amount_received,
+                       MIN(bt.confirmations) as min_confirmations,
+                       (ARRAY_AGG(bt.transaction_hash ORDER BY bt.block_number DESC))[1] as latest_hash
+                FROM blockchain_transactions bt
+                WHERE bt.payment_id = p.id AND bt.status = 'confirmed'
+            ) d ON true
+            WHERE p.id = $1
+            "#,
+            payment_id
+        )
+        .fetch_one(pool)
+        .await
+        .context("Failed to fetch payment deposits")?;
+
+        let tolerance = PaymentTolerance::new(payment.payment_tolerance_type, payment.payment_tolerance);
+        let amount_match = tolerance.classify(payment.amount, payment.amount_received);
+        let fully_confirmed = payment.min_confirmations >= payment.required_confirmations;
 
-            log::info!("Payment {} updated to {:?} with {} confirmations", 
-                payment_id, payment_status, confirmations);
+        // 过期订单的迟到付款在商户接受前不参与结算
+        let awaiting_merchant = matches!(payment.status, PaymentStatus::Expired | PaymentStatus::PaidAfterExpiry)
+            && payment.late_payment_action.is_none();
+        let payment_status = if awaiting_merchant {
+            PaymentStatus::PaidAfterExpiry
         } else {
-            // 交易失败
-            sqlx::query!(
+            amount_match.payment_status(fully_confirmed)
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE payments 
+            SET status = $1, amount_received = $2, transaction_hash = COALESCE($3, transaction_hash),
+                confirmations = $4, updated_at = NOW()
+            WHERE id = $5
+            "#,
+            payment_status.clone() as PaymentStatus,
+            payment.amount_received,
+            payment.latest_hash,
+            payment.min_confirmations,
+            payment_id
+        )
+        .execute(pool)
+        .await
+        .context("Failed to update payment status")?;
+
+        if payment_status != payment.status {
+            log::info!("Payment {} updated to {:?} ({} of {} received) with {} confirmations",
+                payment_id, payment_status, payment.amount_received, payment.amount, payment.min_confirmations);
+        }
+
+        Ok(payment_status)
+    }
+
+    /// 回滚孤块中的到账交易
+    ///
+    /// 删除分叉点之后区块中的交易记录，没有其他有效交易的支付恢复为待支付 (已过期则为expired)，
+    /// 其余支付按剩余到账金额重新结算
+    ///
+    /// # Arguments
+    /// * `fork_block` - 分叉点 (仍在主链上的最后一个区块)
+    /// * `currencies` - 需要回滚的币种 (各扫描任务只回滚自己检测的币种)
+    /// * `mode` - 需要回滚的收款模式 (合约收款与独立地址收款由不同任务检测)
+    /// * `pool` - 数据库连接池
+    ///
+    /// # Returns
+    /// * 状态被回滚的支付订单ID
+    pub async fn rollback_transfers_after(
+        &self,
+        fork_block: u64,
+        currencies: &[Currency],
+        mode: PaymentMode,
+        pool: &PgPool,
+    ) -> Result<Vec<Uuid>> {
+        let mut reverted = Vec::new();
+
+        for currency in currencies {
+            let mut tx = pool.begin().await
+                .context("Failed to begin transaction")?;
+
+            let orphaned = sqlx::query_scalar!(
                 r#"
-                UPDATE payments 
-                SET status = 'failed', transaction_hash = $1, updated_at = NOW()
-                WHERE id = $2
+                DELETE FROM blockchain_transactions bt
+                USING payments p
+                WHERE bt.payment_id = p.id AND bt.block_number > $1 AND p.chain = $2 AND p.currency = $3
+                  AND p.payment_mode = $4
+                RETURNING bt.payment_id
                 "#,
-                format!("{:?}", tx_hash),
-                payment_id
+                fork_block as i64,
+                self.chain as Chain,
+                currency.clone() as Currency,
+                mode as PaymentMode
             )
-            .execute(pool)
+            .fetch_all(&mut *tx)
+            .await
+            .context("Failed to delete orphaned transactions")?;
+
+            if orphaned.is_empty() {
+                continue;
+            }
+
+            let payment_ids = sqlx::query_scalar!(
+                r#"
+                UPDATE payments
+                SET status = CASE WHEN expires_at < NOW() THEN 'expired' ELSE 'pending' END,
+                    amount_received = 0, transaction_hash = NULL, confirmations = 0,
+                    late_payment_action = NULL, late_payment_resolved_at = NULL, refund_address = NULL,
+                    updated_at = NOW()
+                WHERE id = ANY($1)
+                  AND NOT EXISTS (
+                      SELECT 1 FROM blockchain_transactions bt
+                      WHERE bt.payment_id = payments.id AND bt.status = 'confirmed'
+                  )
+                RETURNING id
+                "#,
+                &orphaned
+            )
+            .fetch_all(&mut *tx)
             .await
-            .context("Failed to update payment status to failed")?;
+            .context("Failed to revert payment status")?;
 
-            log::warn!("Payment {} marked as failed due to transaction failure", payment_id);
+            tx.commit().await
+                .context("Failed to commit rollback")?;
+
+            log::warn!("Rolled back {} orphaned {:?} transactions after {} block {}",
+                orphaned.len(), currency, self.chain, fork_block);
+
+            // 仍有其他到账交易的订单按剩余金额重新结算，由确认数更新任务重新确认
+            let mut remaining: Vec<Uuid> = orphaned.into_iter()
+                .filter(|payment_id| !payment_ids.contains(payment_id))
+                .collect();
+            remaining.sort();
+            remaining.dedup();
+
+            for payment_id in &remaining {
+                self.settle_payment(*payment_id, pool).await?;
+            }
+
+            reverted.extend(payment_ids);
+            reverted.extend(remaining);
         }
 
-        Ok(())
+        Ok(reverted)
+    }
+
+    /// 获取HTTP Provider
+    pub fn provider(&self) -> Arc<Provider<Http>> {
+        self.http_provider.clone()
+    }
+
+    /// 获取WebSocket Provider (未配置WebSocket URL时为None)
+    pub fn ws_provider(&self) -> Option<Arc<Provider<Ws>>> {
+        self.ws_provider.clone()
+    }
+
+    /// 将支付标记为已检测 (内存池中发现足额付款交易)
+    ///
+    /// # Arguments
+    /// * `payment_id` - 支付订单ID
+    /// * `tx_hash` - 内存池中的付款交易哈希
+    /// * `pool` - 数据库连接池
+    ///
+    /// # Returns
+    /// * 是否更新了状态 (订单已不在待付款状态时返回false)
+    pub async fn mark_payment_detected(&self, payment_id: Uuid, tx_hash: H256, pool: &PgPool) -> Result<bool> {
+        let updated = sqlx::query!(
+            r#"
+            UPDATE payments
+            SET status = 'detected', detected_tx_hash = $1, detected_at = NOW(), updated_at = NOW()
+            WHERE id = $2 AND status IN ('pending', 'underpaid')
+            "#,
+            format!("{:?}", tx_hash),
+            payment_id
+        )
+        .execute(pool)
+        .await
+        .context("Failed to mark payment as detected")?
+        .rows_affected();
+
+        Ok(updated > 0)
+    }
+
+    /// 回退超时未上链的零确认检测
+    ///
+    /// 内存池交易可能被替换或丢弃，超过等待时间仍没有链上到账的订单恢复为待付款 (或少付)
+    ///
+    /// # Arguments
+    /// * `timeout_minutes` - 检测后等待上链的时间 (分钟)
+    /// * `pool` - 数据库连接池
+    ///
+    /// # Returns
+    /// * 状态被回退的支付订单ID
+    pub async fn revert_stale_detections(&self, timeout_minutes: i32, pool: &PgPool) -> Result<Vec<Uuid>> {
+        let reverted = sqlx::query_scalar!(
+            r#"
+            UPDATE payments
+            SET status = CASE WHEN amount_received > 0 THEN 'underpaid' ELSE 'pending' END,
+                detected_tx_hash = NULL, detected_at = NULL, updated_at = NOW()
+            WHERE status = 'detected'
+              AND detected_at < NOW() - make_interval(mins => $1)
+              AND NOT EXISTS (
+                  SELECT 1 FROM blockchain_transactions bt
+                  WHERE bt.payment_id = payments.id AND bt.transaction_hash = payments.detected_tx_hash
+                    AND bt.status = 'confirmed'
+              )
+            RETURNING id
+            "#,
+            timeout_minutes
+        )
+        .fetch_all(pool)
+        .await
+        .context("Failed to revert stale detections")?;
+
+        if !reverted.is_empty() {
+            log::warn!("Reverted {} zero-confirmation detections not mined within {} minutes",
+                reverted.len(), timeout_minutes);
+        }
+
+        Ok(reverted)
     }
 
     /// 验证交易确认数
@@ -483,9 +629,10 @@ impl EthereumService {
         let payment = sqlx::query_as!(
             crate::models::Payment,
             r#"
-            SELECT id, merchant_id, order_id, amount, 
-                   currency as "currency: _", payment_address,
-                   status as "status: _", transaction_hash, confirmations,
+            SELECT id, merchant_id, order_id, amount, amount_received,
+                   chain as "chain: _", currency as "currency: _", payment_mode as "payment_mode: _",
+                   payment_address, contract_order_id, contract_signature, success_url, cancel_url,
+                   status as "status: _", transaction_hash, confirmations, required_confirmations,
                    expires_at, created_at, updated_at
             FROM payments 
             WHERE id = $1
@@ -507,13 +654,37 @@ impl EthereumService {
     /// # Returns
     /// * 更新的支付订单数量
     pub async fn update_confirmations(&self, pool: &PgPool) -> Result<u64> {
+        let current_block = self.http_provider.get_block_number().await
+            .context("Failed to get current block number")?
+            .as_u64() as i64;
+
+        // 按当前区块高度刷新进行中订单的每笔到账交易确认数
+        sqlx::query!(
+            r#"
+            UPDATE blockchain_transactions bt
+            SET confirmations = GREATEST($1 - bt.block_number, 0)::INT
+            FROM payments p
+            WHERE p.id = bt.payment_id
+              AND p.chain = $2
+              AND p.status IN ('confirmed', 'underpaid', 'paid_after_expiry')
+              AND bt.status = 'confirmed'
+              AND bt.block_number IS NOT NULL
+            "#,
+            current_block,
+            self.chain as Chain
+        )
+        .execute(pool)
+        .await
+        .context("Failed to update transaction confirmations")?;
+
         // 获取所有已确认但未完成的支付
         let payments = sqlx::query!(
             r#"
-            SELECT id, transaction_hash, confirmations
+            SELECT id
             FROM payments 
-            WHERE status = 'confirmed' AND transaction_hash IS NOT NULL
-            "#
+            WHERE status = 'confirmed' AND chain = $1
+            "#,
+            self.chain as Chain
         )
         .fetch_all(pool)
         .await
@@ -522,48 +693,15 @@ impl EthereumService {
         let mut updated_count = 0;
 
         for payment in payments {
-            if let Some(tx_hash) = payment.transaction_hash {
-                match self.get_transaction_confirmations(&tx_hash).await {
-                    Ok(confirmations) => {
-                        let confirmations_i32 = confirmations as i32;
-                        
-                        // 如果确认数达到要求，标记为完成
-                        if confirmations >= self.confirmation_blocks {
-                            sqlx::query!(
-                                r#"
-                                UPDATE payments 
-                                SET status = 'completed', confirmations = $1, updated_at = NOW()
-                                WHERE id = $2
-                                "#,
-                                confirmations_i32,
-                                payment.id
-                            )
-                            .execute(pool)
-                            .await
-                            .context("Failed to update payment to completed")?;
-
-                            log::info!("Payment {} completed with {} confirmations", 
-                                payment.id, confirmations);
-                            updated_count += 1;
-                        } else if confirmations_i32 != payment.confirmations.unwrap_or(0) {
-                            // 更新确认数
-                            sqlx::query!(
-                                r#"
-                                UPDATE payments 
-                                SET confirmations = $1, updated_at = NOW()
-                                WHERE id = $2
-                                "#,
-                                confirmations_i32,
-                                payment.id
-                            )
-                            .execute(pool)
-                            .await
-                            .context("Failed to update payment confirmations")?;
-                        }
-                    },
-                    Err(e) => {
-                        log::warn!("Failed to get confirmations for payment {}: {}", payment.id, e);
-                    }
+            // 每笔到账交易都达到确认数后按累计金额标记为完成或多付
+            match self.settle_payment(payment.id, pool).await {
+                Ok(PaymentStatus::Confirmed) => {},
+                Ok(status) => {
+                    log::info!("Payment {} settled as {:?}", payment.id, status);
+                    updated_count += 1;
+                },
+                Err(e) => {
+                    log::warn!("Failed to settle payment {}: {}", payment.id, e);
                 }
             }
         }
@@ -586,6 +724,7 @@ impl EthereumService {
             .context("Failed to get sync status")?;
 
         Ok(NetworkStatus {
+            chain: self.chain,
             chain_id: self.chain_id,
             block_number: block_number.as_u64(),
             gas_price: gas_price.as_u64(),
@@ -595,9 +734,32 @@ impl EthereumService {
     }
 }
 
+/// 检测到的转入交易
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedTransfer {
+    /// 交易哈希
+    pub tx_hash: H256,
+    /// 发送方地址 (内部转账为调用合约地址)
+    pub from: Address,
+    /// 接收地址
+    pub to: Address,
+    /// 转账金额 (币种最小单位)
+    pub value: U256,
+    /// 币种
+    pub currency: Currency,
+    /// 币种精度
+    pub decimals: u32,
+    /// 所在区块号
+    pub block_number: Option<U64>,
+    /// 代币Transfer事件日志序号 (原生币转账为None)
+    pub log_index: Option<U256>,
+}
+
 /// 网络状态信息
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct NetworkStatus {
+    /// 链
+    pub chain: Chain,
     /// 链ID
     pub chain_id: u64,
     /// 当前区块号
@@ -625,20 +787,6 @@ mod tests {
         assert!(service.is_ok());
     }
 
-    #[tokio::test]
-    async fn test_generate_payment_address() {
-        let service = EthereumService::new_with_config(
-            "https://eth-goerli.alchemyapi.io/v2/demo".to_string(),
-            None,
-            5,
-        ).await.unwrap();
-
-        let address = service.generate_payment_address().await.unwrap();
-        
-        assert!(address.starts_with("0x"));
-        assert_eq!(address.len(), 42);
-    }
-
     #[tokio::test]
     async fn test_validate_transaction_hash() {
         let service = EthereumService::new_with_config(
@@ -0,0 +1,227 @@
+// Gas费用估算服务
+// 基于eth_feeHistory估算EIP-1559费用，网络拥堵超过配置上限时延迟发送交易
+
+use ethers::{
+    providers::{Middleware, Provider, Http},
+    types::{BlockNumber, FeeHistory, U256},
+    utils::parse_units,
+};
+use anyhow::{Result, Context};
+use std::sync::Arc;
+
+/// 参与估算的历史区块数
+const FEE_HISTORY_BLOCKS: u64 = 10;
+
+/// 小费取历史区块的百分位
+const PRIORITY_FEE_PERCENTILE: f64 = 50.0;
+
+/// 历史区块均为空块时使用的默认小费 (1 Gwei)
+const DEFAULT_PRIORITY_FEE: u64 = 1_000_000_000;
+
+/// 未配置时的默认费用上限 (Gwei)
+pub const DEFAULT_MAX_FEE_GWEI: u64 = 100;
+
+/// 替换交易的费用涨幅 (千分比，节点要求至少10%，取12.5%留出余量)
+const REPLACEMENT_FEE_BUMP_PERMILLE: u64 = 1125;
+
+/// EIP-1559交易费用
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Eip1559Fees {
+    /// 下一区块的基础费用
+    pub base_fee_per_gas: U256,
+    /// 最大费用 (基础费用 + 小费的上限)
+    pub max_fee_per_gas: U256,
+    /// 小费
+    pub max_priority_fee_per_gas: U256,
+}
+
+impl Eip1559Fees {
+    /// 按最大费用计算的gas成本上限
+    pub fn max_cost(&self, gas_limit: U256) -> U256 {
+        gas_limit * self.max_fee_per_gas
+    }
+}
+
+/// Gas费用估算器
+#[derive(Clone)]
+pub struct GasOracle {
+    provider: Arc<Provider<Http>>,
+    max_fee_cap: U256,
+}
+
+impl GasOracle {
+    /// 创建费用估算器
+    ///
+    /// # Arguments
+    /// * `provider` - 以太坊提供者
+    /// * `max_fee_gwei` - 最大费用上限 (Gwei)
+    pub fn new(provider: Arc<Provider<Http>>, max_fee_gwei: u64) -> Result<Self> {
+        let max_fee_cap = parse_units(max_fee_gwei, "gwei")
+            .context("Invalid max fee cap")?
+            .into();
+
+        Ok(Self { provider, max_fee_cap })
+    }
+
+    /// 最大费用上限 (wei)
+    pub fn max_fee_cap(&self) -> U256 {
+        self.max_fee_cap
+    }
+
+    /// 获取建议的EIP-1559费用
+    ///
+    /// # Returns
+    /// * 建议费用，网络费用超过上限时返回None (调用方应延迟交易)
+    pub async fn suggest_fees(&self) -> Result<Option<Eip1559Fees>> {
+        let history = self.provider
+            .fee_history(FEE_HISTORY_BLOCKS, BlockNumber::Latest, &[PRIORITY_FEE_PERCENTILE])
+            .await
+            .context("Failed to get fee history")?;
+
+        fees_from_history(&history, self.max_fee_cap)
+    }
+}
+
+/// 根据历史费用计算EIP-1559费用
+///
+/// 最大费用取 `2 * 基础费用 + 小费`，可承受连续6个满块的基础费用上涨，
+/// 并受上限约束；基础费用加小费已超过上限时返回None
+///
+/// # Arguments
+/// * `history` - eth_feeHistory结果
+/// * `max_fee_cap` - 最大费用上限 (wei)
+///
+/// # Returns
+/// * 建议费用
+pub fn fees_from_history(history: &FeeHistory, max_fee_cap: U256) -> Result<Option<Eip1559Fees>> {
+    // base_fee_per_gas比区块数多一项，最后一项为下一区块的基础费用
+    let base_fee_per_gas = *history.base_fee_per_gas.last()
+        .ok_or_else(|| anyhow::anyhow!("Fee history has no base fee (EIP-1559 not supported)"))?;
+
+    let mut rewards: Vec<U256> = history.reward.iter()
+        .filter_map(|block| block.first().copied())
+        .filter(|reward| !reward.is_zero())
+        .collect();
+    rewards.sort();
+
+    let max_priority_fee_per_gas = match rewards.len() {
+        0 => U256::from(DEFAULT_PRIORITY_FEE),
+        len => rewards[len / 2],
+    };
+
+    if base_fee_per_gas + max_priority_fee_per_gas > max_fee_cap {
+        return Ok(None);
+    }
+
+    let max_fee_per_gas = std::cmp::min(base_fee_per_gas * 2 + max_priority_fee_per_gas, max_fee_cap);
+
+    Ok(Some(Eip1559Fees {
+        base_fee_per_gas,
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+    }))
+}
+
+/// 计算替换交易 (replace-by-fee) 的费用
+///
+/// 在原费用基础上提高12.5%，并且不低于当前网络的建议费用
+///
+/// # Arguments
+/// * `previous` - 原交易费用
+/// * `suggested` - 当前建议费用 (网络费用超过上限时为None)
+///
+/// # Returns
+/// * 替换交易费用
+pub fn bump_fees(previous: &Eip1559Fees, suggested: Option<&Eip1559Fees>) -> Eip1559Fees {
+    let bump = |fee: U256| fee * REPLACEMENT_FEE_BUMP_PERMILLE / 1000 + 1;
+
+    let mut fees = Eip1559Fees {
+        base_fee_per_gas: previous.base_fee_per_gas,
+        max_fee_per_gas: bump(previous.max_fee_per_gas),
+        max_priority_fee_per_gas: bump(previous.max_priority_fee_per_gas),
+    };
+
+    if let Some(suggested) = suggested {
+        fees.base_fee_per_gas = suggested.base_fee_per_gas;
+        fees.max_fee_per_gas = std::cmp::max(fees.max_fee_per_gas, suggested.max_fee_per_gas);
+        fees.max_priority_fee_per_gas = std::cmp::max(fees.max_priority_fee_per_gas, suggested.max_priority_fee_per_gas);
+    }
+
+    fees.max_fee_per_gas = std::cmp::max(fees.max_fee_per_gas, fees.max_priority_fee_per_gas);
+    fees
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GWEI: u64 = 1_000_000_000;
+
+    fn history(base_fees: &[u64], rewards: &[u64]) -> FeeHistory {
+        FeeHistory {
+            base_fee_per_gas: base_fees.iter().map(|fee| U256::from(fee * GWEI)).collect(),
+            gas_used_ratio: vec![0.5; rewards.len()],
+            oldest_block: U256::from(100),
+            reward: rewards.iter().map(|reward| vec![U256::from(reward * GWEI)]).collect(),
+        }
+    }
+
+    #[test]
+    fn test_fees_from_history() {
+        let fees = fees_from_history(&history(&[20, 21, 22, 30], &[1, 3, 2]), U256::from(100 * GWEI))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(fees.base_fee_per_gas, U256::from(30 * GWEI));
+        assert_eq!(fees.max_priority_fee_per_gas, U256::from(2 * GWEI)); // 中位数
+        assert_eq!(fees.max_fee_per_gas, U256::from(62 * GWEI));
+        assert_eq!(fees.max_cost(U256::from(21000)), U256::from(21000 * 62 * GWEI));
+    }
+
+    #[test]
+    fn test_fees_capped() {
+        // 2 * 40 + 2 超过上限，最大费用取上限
+        let fees = fees_from_history(&history(&[40, 40], &[2]), U256::from(50 * GWEI))
+            .unwrap()
+            .unwrap();
+        assert_eq!(fees.max_fee_per_gas, U256::from(50 * GWEI));
+
+        // 基础费用加小费超过上限，延迟交易
+        let fees = fees_from_history(&history(&[40, 49], &[2]), U256::from(50 * GWEI)).unwrap();
+        assert!(fees.is_none());
+    }
+
+    #[test]
+    fn test_bump_fees() {
+        let previous = Eip1559Fees {
+            base_fee_per_gas: U256::from(20 * GWEI),
+            max_fee_per_gas: U256::from(40 * GWEI),
+            max_priority_fee_per_gas: U256::from(2 * GWEI),
+        };
+
+        // 网络费用未上涨，按12.5%提高
+        let fees = bump_fees(&previous, None);
+        assert_eq!(fees.max_fee_per_gas, U256::from(45 * GWEI + 1));
+        assert_eq!(fees.max_priority_fee_per_gas, U256::from(2_250_000_001u64));
+
+        // 网络费用已超过涨幅，使用当前建议费用
+        let suggested = Eip1559Fees {
+            base_fee_per_gas: U256::from(50 * GWEI),
+            max_fee_per_gas: U256::from(103 * GWEI),
+            max_priority_fee_per_gas: U256::from(3 * GWEI),
+        };
+        let fees = bump_fees(&previous, Some(&suggested));
+        assert_eq!(fees.max_fee_per_gas, U256::from(103 * GWEI));
+        assert_eq!(fees.max_priority_fee_per_gas, U256::from(3 * GWEI));
+    }
+
+    #[test]
+    fn test_fees_empty_blocks() {
+        let fees = fees_from_history(&history(&[10, 10, 10], &[0, 0]), U256::from(100 * GWEI))
+            .unwrap()
+            .unwrap();
+        assert_eq!(fees.max_priority_fee_per_gas, U256::from(DEFAULT_PRIORITY_FEE));
+
+        assert!(fees_from_history(&history(&[], &[]), U256::from(100 * GWEI)).is_err());
+    }
+}
@@ -0,0 +1,268 @@
+// 内存池零确认检测服务
+// 通过WebSocket订阅待打包交易，按商户零确认规则将足额付款的订单提前标记为detected
+
+use ethers::{
+    prelude::*,
+    providers::{Provider, Ws},
+    types::{Address, Transaction, H256},
+    utils::format_units,
+};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+use anyhow::{Result, Context};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+use crate::models::{Chain, Currency, AmountMatch, PaymentTolerance, ToleranceType};
+use crate::services::{EthereumService, WebhookService};
+use crate::utils::decode_erc20_transfer_call;
+
+/// 候选订单刷新间隔 (秒)
+const CANDIDATE_REFRESH_SECS: u64 = 10;
+
+/// 允许零确认的待付款订单
+#[derive(Debug, Clone)]
+struct ZeroConfCandidate {
+    payment_id: Uuid,
+    currency: Currency,
+    /// 代币合约地址 (原生币为None)
+    contract: Option<Address>,
+    decimals: u32,
+    amount: Decimal,
+    amount_received: Decimal,
+    tolerance: PaymentTolerance,
+}
+
+/// 内存池零确认检测服务
+///
+/// 只负责提前通知，订单状态最终仍由区块扫描与确认数更新任务决定。每条配置了WebSocket URL的链运行一个实例
+pub struct MempoolWatcher {
+    ethereum_service: EthereumService,
+    ws_provider: Arc<Provider<Ws>>,
+    pool: PgPool,
+}
+
+impl MempoolWatcher {
+    /// 创建新的内存池检测服务
+    ///
+    /// # Arguments
+    /// * `ethereum_service` - 所监听链的以太坊服务 (需要配置WebSocket URL)
+    /// * `pool` - 数据库连接池
+    pub fn new(ethereum_service: EthereumService, pool: PgPool) -> Result<Self> {
+        let ws_provider = ethereum_service.ws_provider()
+            .ok_or_else(|| anyhow::anyhow!("Zero-confirmation detection on {} requires a WebSocket URL",
+                ethereum_service.chain()))?;
+
+        Ok(Self { ethereum_service, ws_provider, pool })
+    }
+
+    /// 启动内存池订阅
+    pub async fn start_watching(&self) -> Result<()> {
+        log::info!("Starting mempool zero-confirmation watcher on {}", self.ethereum_service.chain());
+
+        let mut stream = self.ws_provider.subscribe_pending_txs().await
+            .context("Failed to subscribe to pending transactions")?;
+
+        let mut candidates = self.get_candidates().await?;
+        let mut refreshed_at = Instant::now();
+
+        while let Some(tx_hash) = stream.next().await {
+            if refreshed_at.elapsed() >= Duration::from_secs(CANDIDATE_REFRESH_SECS) {
+                match self.get_candidates().await {
+                    Ok(latest) => candidates = latest,
+                    Err(e) => log::error!("Failed to refresh zero-confirmation candidates: {}", e),
+                }
+                refreshed_at = Instant::now();
+            }
+
+            if candidates.is_empty() {
+                continue;
+            }
+
+            if let Err(e) = self.process_pending_transaction(tx_hash, &candidates).await {
+                log::warn!("Failed to process pending transaction {:?}: {}", tx_hash, e);
+            }
+        }
+
+        anyhow::bail!("Pending transaction subscription closed")
+    }
+
+    /// 检查待打包交易是否为候选订单的足额付款
+    async fn process_pending_transaction(
+        &self,
+        tx_hash: H256,
+        candidates: &HashMap<Address, ZeroConfCandidate>,
+    ) -> Result<()> {
+        // 交易可能已被打包或丢弃
+        let Some(tx) = self.ws_provider.get_transaction(tx_hash).await
+            .context("Failed to get pending transaction")? else {
+            return Ok(());
+        };
+
+        let Some((candidate, value)) = match_pending_transaction(&tx, candidates) else {
+            return Ok(());
+        };
+
+        let amount = Decimal::from_str(&format_units(value, candidate.decimals)?)
+            .context("Invalid transfer amount")?;
+        let received = candidate.amount_received + amount;
+
+        // 只有补足订单金额的付款才能提前展示为已支付
+        if candidate.tolerance.classify(candidate.amount, received) == AmountMatch::Underpaid {
+            log::info!("Pending deposit {:?} does not cover payment {}", tx_hash, candidate.payment_id);
+            return Ok(());
+        }
+
+        if !self.ethereum_service.mark_payment_detected(candidate.payment_id, tx_hash, &self.pool).await? {
+            return Ok(());
+        }
+
+        log::info!("Payment {} detected in mempool by tx {:?}", candidate.payment_id, tx_hash);
+
+        let payment_id = candidate.payment_id;
+        let webhook_service = WebhookService::new(self.pool.clone(), 5);
+        tokio::spawn(async move {
+            if let Err(e) = webhook_service.notify_payment_status(payment_id).await {
+                log::error!("Failed to notify detected payment {}: {}", payment_id, e);
+            }
+        });
+
+        Ok(())
+    }
+
+    /// 获取满足商户零确认规则的待付款订单
+    async fn get_candidates(&self) -> Result<HashMap<Address, ZeroConfCandidate>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT pa.address, p.id, p.currency as "currency: Currency", p.amount, p.amount_received,
+                   t.contract_address, t.decimals,
+                   m.payment_tolerance_type as "payment_tolerance_type: ToleranceType",
+                   m.payment_tolerance
+            FROM payment_addresses pa
+            JOIN payments p ON p.id = pa.payment_id
+            JOIN tokens t ON t.chain = p.chain AND t.symbol = p.currency
+            JOIN merchants m ON m.id = p.merchant_id
+            JOIN merchant_zero_conf_rules r ON r.merchant_id = p.merchant_id AND r.chain = p.chain AND r.currency = p.currency
+            WHERE p.chain = $1
+              AND p.status IN ('pending', 'underpaid')
+              AND p.amount <= r.max_amount
+              AND (p.expires_at IS NULL OR p.expires_at > NOW())
+            "#,
+            self.ethereum_service.chain() as Chain
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch zero-confirmation candidates")?;
+
+        let mut candidates = HashMap::with_capacity(rows.len());
+        for row in rows {
+            let contract = match row.contract_address.as_deref().map(str::parse::<Address>).transpose() {
+                Ok(contract) => contract,
+                Err(e) => {
+                    log::warn!("Invalid contract address for {:?}: {}", row.currency, e);
+                    continue;
+                }
+            };
+
+            match row.address.parse::<Address>() {
+                Ok(address) => {
+                    candidates.insert(address, ZeroConfCandidate {
+                        payment_id: row.id,
+                        currency: row.currency,
+                        contract,
+                        decimals: row.decimals as u32,
+                        amount: row.amount,
+                        amount_received: row.amount_received,
+                        tolerance: PaymentTolerance::new(row.payment_tolerance_type, row.payment_tolerance),
+                    });
+                },
+                Err(e) => log::warn!("Invalid payment address {}: {}", row.address, e),
+            }
+        }
+
+        Ok(candidates)
+    }
+}
+
+/// 匹配待打包交易的收款地址
+///
+/// 原生币直接转账匹配交易接收方，代币匹配 `transfer` 调用的收款地址
+///
+/// # Arguments
+/// * `tx` - 待打包交易
+/// * `candidates` - 按收款地址索引的候选订单
+///
+/// # Returns
+/// * 匹配的候选订单与转账数量 (最小单位)
+fn match_pending_transaction<'a>(
+    tx: &Transaction,
+    candidates: &'a HashMap<Address, ZeroConfCandidate>,
+) -> Option<(&'a ZeroConfCandidate, U256)> {
+    let to = tx.to?;
+
+    if let Some(candidate) = candidates.get(&to) {
+        if candidate.contract.is_none() && !tx.value.is_zero() {
+            return Some((candidate, tx.value));
+        }
+    }
+
+    let (recipient, amount) = decode_erc20_transfer_call(&tx.input)?;
+    let candidate = candidates.get(&recipient)?;
+    let contract = candidate.contract?;
+
+    (contract == to && !amount.is_zero()).then_some((candidate, amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::encode_erc20_transfer;
+
+    fn candidate(currency: Currency, contract: Option<Address>, decimals: u32) -> ZeroConfCandidate {
+        ZeroConfCandidate {
+            payment_id: Uuid::new_v4(),
+            currency,
+            contract,
+            decimals,
+            amount: Decimal::ONE,
+            amount_received: Decimal::ZERO,
+            tolerance: PaymentTolerance::new(ToleranceType::Absolute, Decimal::ZERO),
+        }
+    }
+
+    #[test]
+    fn test_match_pending_transaction() {
+        let eth_address = Address::repeat_byte(0x11);
+        let usdt_address = Address::repeat_byte(0x22);
+        let usdt_contract = Address::repeat_byte(0xdd);
+        let candidates = HashMap::from([
+            (eth_address, candidate(Currency::ETH, None, 18)),
+            (usdt_address, candidate(Currency::USDT, Some(usdt_contract), 6)),
+        ]);
+
+        // ETH直接转账
+        let eth_tx = Transaction { to: Some(eth_address), value: U256::from(1000), ..Default::default() };
+        let (matched, value) = match_pending_transaction(&eth_tx, &candidates).unwrap();
+        assert_eq!(matched.currency, Currency::ETH);
+        assert_eq!(value, U256::from(1000));
+
+        // 代币transfer调用
+        let token_tx = Transaction {
+            to: Some(usdt_contract),
+            input: encode_erc20_transfer(usdt_address, U256::from(1_000_000u64)),
+            ..Default::default()
+        };
+        let (matched, value) = match_pending_transaction(&token_tx, &candidates).unwrap();
+        assert_eq!(matched.currency, Currency::USDT);
+        assert_eq!(value, U256::from(1_000_000u64));
+
+        // 其他合约的transfer调用与向代币订单地址直接转ETH都不匹配
+        let other_token_tx = Transaction { to: Some(Address::repeat_byte(0xee)), ..token_tx.clone() };
+        assert!(match_pending_transaction(&other_token_tx, &candidates).is_none());
+        let eth_to_token_order = Transaction { to: Some(usdt_address), value: U256::from(1000), ..Default::default() };
+        assert!(match_pending_transaction(&eth_to_token_order, &candidates).is_none());
+
+    }
+}
@@ -6,8 +6,10 @@ use uuid::Uuid;
 use anyhow::{Result, Context};
 use crate::models::{
     Merchant, MerchantStatus, CreateMerchantRequest, CreateMerchantResponse,
-    UpdateMerchantRequest, RegenerateApiKeyResponse
+    UpdateMerchantRequest, RegenerateApiKeyResponse, ToleranceType, Currency,
+    ConfirmationRule, ZeroConfRule, Chain, MAX_REQUIRED_CONFIRMATIONS
 };
+use rust_decimal::Decimal;
 use crate::utils::{generate_api_key_pair, validate_merchant_name, validate_email, validate_url, InputValidator};
 
 /// 商户管理服务
@@ -82,8 +84,9 @@ impl MerchantService {
         let merchant = sqlx::query_as!(
             Merchant,
             r#"
-            SELECT id, name, email, api_key, api_secret, webhook_url,
-                   status as "status: _", created_at, updated_at
+            SELECT id, name, email, api_key, api_secret, webhook_url, payout_address,
+                   status as "status: _", payment_tolerance_type as "payment_tolerance_type: _",
+                   payment_tolerance, created_at, updated_at
             FROM merchants 
             WHERE id = $1
             "#,
@@ -107,8 +110,9 @@ impl MerchantService {
         let merchant = sqlx::query_as!(
             Merchant,
             r#"
-            SELECT id, name, email, api_key, api_secret, webhook_url,
-                   status as "status: _", created_at, updated_at
+            SELECT id, name, email, api_key, api_secret, webhook_url, payout_address,
+                   status as "status: _", payment_tolerance_type as "payment_tolerance_type: _",
+                   payment_tolerance, created_at, updated_at
             FROM merchants 
             WHERE api_key = $1 AND status = 'active'
             "#,
@@ -144,23 +148,95 @@ impl MerchantService {
         // 构建更新查询
         let name = request.name.unwrap_or(existing_merchant.name);
         let webhook_url = request.webhook_url.or(existing_merchant.webhook_url);
+        let payout_address = request.payout_address.or(existing_merchant.payout_address);
         let status = request.status.unwrap_or(existing_merchant.status);
+        let payment_tolerance_type = request.payment_tolerance_type.unwrap_or(existing_merchant.payment_tolerance_type);
+        let payment_tolerance = request.payment_tolerance.unwrap_or(existing_merchant.payment_tolerance);
+
+        if payment_tolerance_type == ToleranceType::Percentage && payment_tolerance > Decimal::ONE_HUNDRED {
+            anyhow::bail!("Percentage tolerance cannot exceed 100");
+        }
+
+        let mut tx = self.pool.begin().await
+            .context("Failed to begin transaction")?;
 
         sqlx::query!(
             r#"
             UPDATE merchants 
-            SET name = $1, webhook_url = $2, status = $3, updated_at = NOW()
-            WHERE id = $4
+            SET name = $1, webhook_url = $2, status = $3,
+                payment_tolerance_type = $4, payment_tolerance = $5, payout_address = $7, updated_at = NOW()
+            WHERE id = $6
             "#,
             name,
             webhook_url,
             status as MerchantStatus,
-            merchant_id
+            payment_tolerance_type as ToleranceType,
+            payment_tolerance,
+            merchant_id,
+            payout_address
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await
         .context("Failed to update merchant")?;
 
+        // 确认数规则整体替换，只影响之后创建的订单
+        if let Some(rules) = request.confirmation_rules {
+            sqlx::query!(
+                "DELETE FROM merchant_confirmation_rules WHERE merchant_id = $1",
+                merchant_id
+            )
+            .execute(&mut *tx)
+            .await
+            .context("Failed to clear confirmation rules")?;
+
+            for rule in rules {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO merchant_confirmation_rules (merchant_id, chain, currency, min_amount, confirmations)
+                    VALUES ($1, $2, $3, $4, $5)
+                    "#,
+                    merchant_id,
+                    rule.chain as Chain,
+                    rule.currency as Currency,
+                    rule.min_amount,
+                    rule.confirmations
+                )
+                .execute(&mut *tx)
+                .await
+                .context("Failed to insert confirmation rule")?;
+            }
+        }
+
+        // 零确认规则整体替换，只影响之后在内存池中检测到的付款
+        if let Some(rules) = request.zero_conf_rules {
+            sqlx::query!(
+                "DELETE FROM merchant_zero_conf_rules WHERE merchant_id = $1",
+                merchant_id
+            )
+            .execute(&mut *tx)
+            .await
+            .context("Failed to clear zero-confirmation rules")?;
+
+            for rule in rules {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO merchant_zero_conf_rules (merchant_id, chain, currency, max_amount)
+                    VALUES ($1, $2, $3, $4)
+                    "#,
+                    merchant_id,
+                    rule.chain as Chain,
+                    rule.currency as Currency,
+                    rule.max_amount
+                )
+                .execute(&mut *tx)
+                .await
+                .context("Failed to insert zero-confirmation rule")?;
+            }
+        }
+
+        tx.commit().await
+            .context("Failed to commit merchant update")?;
+
         log::info!("Updated merchant: {}", merchant_id);
 
         // 返回更新后的商户信息
@@ -168,6 +244,53 @@ impl MerchantService {
             .ok_or_else(|| anyhow::anyhow!("Failed to fetch updated merchant"))
     }
 
+    /// 获取商户的确认数规则
+    ///
+    /// # Arguments
+    /// * `merchant_id` - 商户ID
+    ///
+    /// # Returns
+    /// * 按链、币种和起始金额排序的规则列表
+    pub async fn get_confirmation_rules(&self, merchant_id: Uuid) -> Result<Vec<ConfirmationRule>> {
+        let rules = sqlx::query_as!(
+            ConfirmationRule,
+            r#"
+            SELECT chain as "chain: _", currency as "currency: _", min_amount, confirmations
+            FROM merchant_confirmation_rules
+            WHERE merchant_id = $1
+            ORDER BY chain, currency, min_amount
+            "#,
+            merchant_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch confirmation rules")?;
+
+        Ok(rules)
+    }
+
+    /// 获取商户的零确认规则
+    ///
+    /// # Arguments
+    /// * `merchant_id` - 商户ID
+    pub async fn get_zero_conf_rules(&self, merchant_id: Uuid) -> Result<Vec<ZeroConfRule>> {
+        let rules = sqlx::query_as!(
+            ZeroConfRule,
+            r#"
+            SELECT chain as "chain: _", currency as "currency: _", max_amount
+            FROM merchant_zero_conf_rules
+            WHERE merchant_id = $1
+            ORDER BY chain, currency
+            "#,
+            merchant_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch zero-confirmation rules")?;
+
+        Ok(rules)
+    }
+
     /// 重新生成API密钥
     /// 
     /// # Arguments
@@ -322,7 +445,9 @@ impl MerchantService {
             }
         }
 
-        validator.into_result()
+        validator.into_result()?;
+
+        Ok(())
     }
 
     /// 验证更新商户请求
@@ -340,7 +465,61 @@ impl MerchantService {
             }
         }
 
-        validator.into_result()
+        if let Some(payout_address) = &request.payout_address {
+            validator.validate_ethereum_address_field("payout_address", payout_address);
+        }
+
+        validator.into_result()?;
+
+        // 验证付款容差
+        if let Some(tolerance) = request.payment_tolerance {
+            if tolerance.is_sign_negative() {
+                anyhow::bail!("Payment tolerance cannot be negative");
+            }
+        }
+
+        // 验证确认数规则
+        if let Some(rules) = &request.confirmation_rules {
+            Self::validate_confirmation_rules(rules)?;
+        }
+
+        // 验证零确认规则
+        if let Some(rules) = &request.zero_conf_rules {
+            Self::validate_zero_conf_rules(rules)?;
+        }
+
+        Ok(())
+    }
+
+    /// 验证确认数规则
+    fn validate_confirmation_rules(rules: &[ConfirmationRule]) -> Result<()> {
+        for (index, rule) in rules.iter().enumerate() {
+            if rule.min_amount.is_sign_negative() {
+                anyhow::bail!("Confirmation rule minimum amount cannot be negative");
+            }
+            if !(1..=MAX_REQUIRED_CONFIRMATIONS).contains(&rule.confirmations) {
+                anyhow::bail!("Confirmations must be between 1 and {}", MAX_REQUIRED_CONFIRMATIONS);
+            }
+            if rules[..index].iter().any(|other| other.chain == rule.chain && other.currency == rule.currency && other.min_amount == rule.min_amount) {
+                anyhow::bail!("Duplicate confirmation rule for {:?} on {} from {}", rule.currency, rule.chain, rule.min_amount);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 验证零确认规则
+    fn validate_zero_conf_rules(rules: &[ZeroConfRule]) -> Result<()> {
+        for (index, rule) in rules.iter().enumerate() {
+            if rule.max_amount <= Decimal::ZERO {
+                anyhow::bail!("Zero-confirmation maximum amount must be positive");
+            }
+            if rules[..index].iter().any(|other| other.chain == rule.chain && other.currency == rule.currency) {
+                anyhow::bail!("Duplicate zero-confirmation rule for {:?} on {}", rule.currency, rule.chain);
+            }
+        }
+
+        Ok(())
     }
 
     /// 检查邮箱是否已存在
@@ -432,4 +611,5 @@ mod tests {
         assert_eq!(merchant.id, create_response.merchant_id);
         assert_eq!(merchant.name, "Test Merchant");
     }
+
 }
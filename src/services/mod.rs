@@ -5,9 +5,31 @@ pub mod merchant_service;
 pub mod payment_service;
 pub mod ethereum_service;
 pub mod webhook_service;
+pub mod wallet_manager;
+pub mod collection_service;
+pub mod gas_oracle;
+pub mod nonce_manager;
+pub mod deposit_scanner;
+pub mod token_indexer;
+pub mod chain_cursor;
+pub mod mempool_watcher;
+pub mod token_registry;
+pub mod contract_listener;
+pub mod contract_service;
 
 // 重新导出服务
 pub use merchant_service::MerchantService;
 pub use payment_service::PaymentService;
 pub use ethereum_service::EthereumService;
 pub use webhook_service::WebhookService;
+pub use wallet_manager::WalletManager;
+pub use collection_service::CollectionService;
+pub use gas_oracle::GasOracle;
+pub use nonce_manager::NonceManager;
+pub use deposit_scanner::DepositScanner;
+pub use token_indexer::TokenIndexer;
+pub use chain_cursor::ChainCursor;
+pub use mempool_watcher::MempoolWatcher;
+pub use token_registry::TokenRegistry;
+pub use contract_listener::ContractListener;
+pub use contract_service::ContractService;
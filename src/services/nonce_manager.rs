@@ -0,0 +1,91 @@
+// 交易nonce管理
+// 为每个发送地址分配连续的nonce，避免并发或重试的归集交易互相冲突
+
+use ethers::{
+    providers::{Middleware, Provider, Http},
+    types::{Address, BlockNumber, U256},
+};
+use anyhow::{Result, Context};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// 按地址分配nonce的管理器
+pub struct NonceManager {
+    provider: Arc<Provider<Http>>,
+    /// 每个地址下一个可用的nonce
+    next_nonces: Mutex<HashMap<Address, U256>>,
+}
+
+impl NonceManager {
+    /// 创建nonce管理器
+    pub fn new(provider: Arc<Provider<Http>>) -> Self {
+        Self {
+            provider,
+            next_nonces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 为地址分配下一个nonce
+    ///
+    /// 取本地已分配的nonce与链上pending nonce中较大者，
+    /// 分配期间持有锁，同一地址的并发请求不会拿到相同的nonce
+    ///
+    /// # Arguments
+    /// * `address` - 发送地址
+    ///
+    /// # Returns
+    /// * 可用的nonce
+    pub async fn next_nonce(&self, address: Address) -> Result<U256> {
+        let mut next_nonces = self.next_nonces.lock().await;
+
+        let chain_nonce = self.provider
+            .get_transaction_count(address, Some(BlockNumber::Pending.into()))
+            .await
+            .context("Failed to get nonce")?;
+
+        Ok(reserve_nonce(&mut next_nonces, address, chain_nonce))
+    }
+
+    /// 清除地址的本地nonce (交易发送失败时调用，下次重新从链上读取)
+    pub async fn reset(&self, address: Address) {
+        self.next_nonces.lock().await.remove(&address);
+    }
+}
+
+fn reserve_nonce(next_nonces: &mut HashMap<Address, U256>, address: Address, chain_nonce: U256) -> U256 {
+    let nonce = next_nonces.get(&address)
+        .map_or(chain_nonce, |local| std::cmp::max(*local, chain_nonce));
+
+    next_nonces.insert(address, nonce + 1);
+    nonce
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve_nonce_sequential() {
+        let mut next_nonces = HashMap::new();
+        let address = Address::repeat_byte(1);
+
+        // 链上pending nonce尚未包含刚发送的交易时，仍按本地顺序递增
+        assert_eq!(reserve_nonce(&mut next_nonces, address, U256::from(5)), U256::from(5));
+        assert_eq!(reserve_nonce(&mut next_nonces, address, U256::from(5)), U256::from(6));
+        assert_eq!(reserve_nonce(&mut next_nonces, address, U256::from(6)), U256::from(7));
+    }
+
+    #[test]
+    fn test_reserve_nonce_follows_chain() {
+        let mut next_nonces = HashMap::new();
+        let address = Address::repeat_byte(1);
+        let other = Address::repeat_byte(2);
+
+        assert_eq!(reserve_nonce(&mut next_nonces, address, U256::from(1)), U256::from(1));
+        // 地址在其他地方发送了交易，链上nonce超过本地记录
+        assert_eq!(reserve_nonce(&mut next_nonces, address, U256::from(10)), U256::from(10));
+        // 不同地址互不影响
+        assert_eq!(reserve_nonce(&mut next_nonces, other, U256::zero()), U256::zero());
+    }
+}
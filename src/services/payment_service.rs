@@ -7,22 +7,51 @@ use anyhow::{Result, Context};
 use chrono::{DateTime, Utc, Duration};
 use rust_decimal::Decimal;
 use crate::models::{
-    Payment, PaymentStatus, Currency, CreatePaymentRequest, CreatePaymentResponse,
-    PaymentResponse, PaymentListQuery, PaymentListResponse, PaginationInfo
+    Payment, PaymentStatus, PaymentMode, Chain, Currency, CreatePaymentRequest, CreatePaymentResponse,
+    PaymentResponse, PaymentListQuery, PaymentListResponse, PaginationInfo,
+    PaymentDeposit, TransactionStatus, LatePaymentAction, ResolveLatePaymentRequest,
+    ConfirmationRule, Token, MAX_REQUIRED_CONFIRMATIONS
 };
-use crate::utils::{validate_order_id, validate_payment_amount, generate_payment_qr_code};
-use crate::services::EthereumService;
+use crate::utils::{
+    validate_order_id, validate_payment_amount, validate_ethereum_address, generate_payment_qr_code,
+    generate_contract_order_id, sign_contract_payment, contract_merchant_id, ContractOrderArgs, PaymentUri
+};
+use ethers::signers::LocalWallet;
+use ethers::types::U256;
+use crate::services::{EthereumService, WalletManager, MerchantService, WebhookService, TokenRegistry};
+use std::collections::HashMap;
+use std::sync::Arc;
 
 /// 支付服务
 pub struct PaymentService {
     pool: PgPool,
     ethereum_service: EthereumService,
+    wallet_manager: Arc<WalletManager>,
+    token_registry: TokenRegistry,
+    /// 合约收款订单签名者 (未配置时不支持合约收款模式)
+    contract_signer: Option<LocalWallet>,
 }
 
 impl PaymentService {
     /// 创建新的支付服务实例
-    pub fn new(pool: PgPool, ethereum_service: EthereumService) -> Self {
-        Self { pool, ethereum_service }
+    pub fn new(
+        pool: PgPool,
+        ethereum_service: EthereumService,
+        wallet_manager: Arc<WalletManager>,
+        token_registry: TokenRegistry,
+    ) -> Self {
+        Self { pool, ethereum_service, wallet_manager, token_registry, contract_signer: None }
+    }
+
+    /// 设置合约收款订单签名者私钥 (需与合约的 `paymentSigner` 一致)
+    ///
+    /// # Arguments
+    /// * `signer_key` - 签名者私钥 (可选)
+    pub fn with_contract_signer(mut self, signer_key: Option<&str>) -> Result<Self> {
+        self.contract_signer = signer_key
+            .map(|key| key.parse::<LocalWallet>().context("Invalid contract signer private key"))
+            .transpose()?;
+        Ok(self)
     }
 
     /// 创建支付订单
@@ -39,14 +68,11 @@ impl PaymentService {
         request: CreatePaymentRequest,
     ) -> Result<CreatePaymentResponse> {
         // 输入验证
-        self.validate_create_request(&request)?;
+        let token = self.validate_create_request(&request)?;
 
         // 检查订单ID是否已存在
         self.check_order_id_exists(merchant_id, &request.order_id).await?;
 
-        // 生成支付地址
-        let payment_address = self.ethereum_service.generate_payment_address().await?;
-
         // 计算过期时间
         let expires_at = request.expires_in.map(|seconds| {
             Utc::now() + Duration::seconds(seconds)
@@ -54,55 +80,108 @@ impl PaymentService {
             Utc::now() + Duration::hours(1) // 默认1小时过期
         });
 
+        // 确认数: 订单指定 > 商户按币种与金额配置的规则 > 系统默认
+        let required_confirmations = match request.required_confirmations {
+            Some(confirmations) => confirmations,
+            None => {
+                let rules = MerchantService::new(self.pool.clone())
+                    .get_confirmation_rules(merchant_id)
+                    .await?;
+                ConfirmationRule::resolve(&rules, &request.chain, &request.currency, request.amount)
+                    .unwrap_or(self.ethereum_service.confirmation_blocks() as i32)
+            }
+        };
+
         // 创建支付订单
         let payment_id = Uuid::new_v4();
         let created_at = Utc::now();
 
+        let mut tx = self.pool.begin().await
+            .context("Failed to begin transaction")?;
+
+        let (payment_address, contract_order_id, contract_signature) = match request.payment_mode {
+            // 从HD钱包分配支付地址 (与订单在同一事务中提交，保证每个地址都可归集)
+            PaymentMode::Address => {
+                (self.wallet_manager.generate_payment_address(&mut tx, payment_id).await?, None, None)
+            },
+            // 付款人调用合约的receivePayment并传入商户ID、链上订单号、订单金额与服务端签名
+            PaymentMode::Contract => {
+                let (contract, signer) = self.ethereum_service.payment_contract()
+                    .zip(self.contract_signer.as_ref())
+                    .ok_or_else(|| anyhow::anyhow!("Contract payments are not enabled on {}", request.chain))?;
+                let contract_order_id = generate_contract_order_id();
+                let signature = sign_contract_payment(
+                    signer,
+                    contract,
+                    self.ethereum_service.chain_id(),
+                    contract_merchant_id(merchant_id),
+                    U256::from_dec_str(&contract_order_id)?,
+                    token.to_smallest_unit(request.amount)?,
+                )?;
+                (format!("{:?}", contract), Some(contract_order_id), Some(signature.to_string()))
+            },
+        };
+
         sqlx::query!(
             r#"
             INSERT INTO payments (
-                id, merchant_id, order_id, amount, currency, 
-                payment_address, expires_at, created_at, updated_at
+                id, merchant_id, order_id, amount, chain, currency, payment_mode,
+                payment_address, contract_order_id, contract_signature, success_url, cancel_url,
+                required_confirmations, expires_at, created_at, updated_at
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $15)
             "#,
             payment_id,
             merchant_id,
             request.order_id,
             request.amount,
-            request.currency as Currency,
+            request.chain as Chain,
+            request.currency.clone() as Currency,
+            request.payment_mode as PaymentMode,
             payment_address,
+            contract_order_id,
+            contract_signature,
+            request.success_url,
+            request.cancel_url,
+            required_confirmations,
             expires_at,
             created_at
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await
         .context("Failed to create payment")?;
 
+        tx.commit().await
+            .context("Failed to commit payment")?;
+
         // 生成支付URL和二维码
-        let payment_url = self.generate_payment_url(&request.currency, &payment_address, &request.amount);
+        let contract_order = contract_order_id.as_deref().map(|order_id| ContractOrderArgs {
+            merchant_id,
+            order_id,
+            signature: contract_signature.as_deref(),
+        });
+        let payment_url = self.generate_payment_url(&token, &payment_address, &request.amount, contract_order)?;
         let qr_code = generate_payment_qr_code(&payment_url)
             .context("Failed to generate QR code")?;
 
-        // 启动交易监听
-        let pool_clone = self.pool.clone();
-        let ethereum_service_clone = self.ethereum_service.clone();
-        tokio::spawn(async move {
-            if let Err(e) = ethereum_service_clone.monitor_payment(payment_id, &payment_address, pool_clone).await {
-                log::error!("Failed to monitor payment {}: {}", payment_id, e);
-            }
-        });
+        // 到账检测由各链的区块扫描 (原生币)、Transfer事件索引 (代币) 与合约事件监听 (合约收款) 后台任务统一处理
 
         log::info!("Created payment order: {} for merchant: {}", payment_id, merchant_id);
 
         Ok(CreatePaymentResponse {
             payment_id,
+            payment_mode: request.payment_mode,
             payment_address,
+            contract_order_id,
+            contract_signature,
             amount: request.amount,
+            chain: request.chain,
             currency: request.currency,
             expires_at: Some(expires_at),
+            required_confirmations,
             qr_code,
             payment_url,
+            checkout_url: format!("/checkout/{}", payment_id),
         })
     }
 
@@ -122,9 +201,10 @@ impl PaymentService {
         let payment = sqlx::query_as!(
             Payment,
             r#"
-            SELECT id, merchant_id, order_id, amount, 
-                   currency as "currency: _", payment_address,
-                   status as "status: _", transaction_hash, confirmations,
+            SELECT id, merchant_id, order_id, amount, amount_received,
+                   chain as "chain: _", currency as "currency: _", payment_mode as "payment_mode: _",
+                   payment_address, contract_order_id, contract_signature, success_url, cancel_url,
+                   status as "status: _", transaction_hash, confirmations, required_confirmations,
                    expires_at, created_at, updated_at
             FROM payments 
             WHERE id = $1 AND merchant_id = $2
@@ -136,7 +216,39 @@ impl PaymentService {
         .await
         .context("Failed to fetch payment")?;
 
-        Ok(payment.map(|p| p.to_response()))
+        match payment {
+            Some(payment) => {
+                let mut deposits = self.get_deposits(&[payment.id]).await?;
+                Ok(Some(payment.to_response(deposits.remove(&payment.id).unwrap_or_default())))
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// 根据ID获取收银台展示的支付订单 (无商户权限验证，订单ID即访问凭证)
+    ///
+    /// # Arguments
+    /// * `pool` - 数据库连接池
+    /// * `payment_id` - 支付订单ID
+    pub async fn get_checkout_payment(pool: &PgPool, payment_id: Uuid) -> Result<Option<Payment>> {
+        let payment = sqlx::query_as!(
+            Payment,
+            r#"
+            SELECT id, merchant_id, order_id, amount, amount_received,
+                   chain as "chain: _", currency as "currency: _", payment_mode as "payment_mode: _",
+                   payment_address, contract_order_id, contract_signature, success_url, cancel_url,
+                   status as "status: _", transaction_hash, confirmations, required_confirmations,
+                   expires_at, created_at, updated_at
+            FROM payments 
+            WHERE id = $1
+            "#,
+            payment_id
+        )
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch payment")?;
+
+        Ok(payment)
     }
 
     /// 获取商户的支付订单列表
@@ -164,6 +276,11 @@ impl PaymentService {
             param_index += 1;
         }
 
+        if let Some(chain) = &query.chain {
+            where_conditions.push(format!("chain = ${}", param_index));
+            param_index += 1;
+        }
+
         if let Some(currency) = &query.currency {
             where_conditions.push(format!("currency = ${}", param_index));
             param_index += 1;
@@ -196,9 +313,10 @@ impl PaymentService {
             Payment,
             &format!(
                 r#"
-                SELECT id, merchant_id, order_id, amount, 
-                       currency as "currency: _", payment_address,
-                       status as "status: _", transaction_hash, confirmations,
+                SELECT id, merchant_id, order_id, amount, amount_received,
+                       chain as "chain: _", currency as "currency: _", payment_mode as "payment_mode: _",
+                       payment_address, contract_order_id, contract_signature, success_url, cancel_url,
+                       status as "status: _", transaction_hash, confirmations, required_confirmations,
                        expires_at, created_at, updated_at
                 FROM payments 
                 WHERE {}
@@ -215,9 +333,15 @@ impl PaymentService {
         .await
         .context("Failed to fetch payments")?;
 
+        let payment_ids: Vec<Uuid> = payments.iter().map(|p| p.id).collect();
+        let mut deposits = self.get_deposits(&payment_ids).await?;
+
         let payment_responses: Vec<PaymentResponse> = payments
             .into_iter()
-            .map(|p| p.to_response())
+            .map(|p| {
+                let payment_deposits = deposits.remove(&p.id).unwrap_or_default();
+                p.to_response(payment_deposits)
+            })
             .collect();
 
         let pagination = PaginationInfo::new(
@@ -232,6 +356,44 @@ impl PaymentService {
         })
     }
 
+    /// 批量获取支付订单的到账交易
+    ///
+    /// # Arguments
+    /// * `payment_ids` - 支付订单ID列表
+    ///
+    /// # Returns
+    /// * 按支付订单分组的到账交易 (按区块顺序排列)
+    async fn get_deposits(&self, payment_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<PaymentDeposit>>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT payment_id, transaction_hash, from_address, amount, block_number,
+                   confirmations as "confirmations!", status as "status!: TransactionStatus", created_at as "created_at!"
+            FROM blockchain_transactions
+            WHERE payment_id = ANY($1)
+            ORDER BY block_number ASC NULLS LAST, log_index ASC
+            "#,
+            payment_ids
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch payment deposits")?;
+
+        let mut deposits: HashMap<Uuid, Vec<PaymentDeposit>> = HashMap::new();
+        for row in rows {
+            deposits.entry(row.payment_id).or_default().push(PaymentDeposit {
+                transaction_hash: row.transaction_hash,
+                from_address: row.from_address,
+                amount: row.amount,
+                block_number: row.block_number,
+                confirmations: row.confirmations,
+                status: row.status,
+                created_at: row.created_at,
+            });
+        }
+
+        Ok(deposits)
+    }
+
     /// 更新支付订单状态
     /// 
     /// # Arguments
@@ -283,7 +445,122 @@ impl PaymentService {
         Ok(())
     }
 
+    /// 处理过期订单的迟到付款
+    ///
+    /// 接受时按正常订单结算 (达到确认数后完成)；
+    /// 退款时订单标记为refunded，归集任务将收款地址的资金转入退款地址
+    ///
+    /// # Arguments
+    /// * `payment_id` - 支付订单ID
+    /// * `merchant_id` - 商户ID (用于权限验证)
+    /// * `request` - 处理方式与退款地址
+    ///
+    /// # Returns
+    /// * 处理后的支付订单信息
+    pub async fn resolve_late_payment(
+        &self,
+        payment_id: Uuid,
+        merchant_id: Uuid,
+        request: ResolveLatePaymentRequest,
+    ) -> Result<PaymentResponse> {
+        let payment = sqlx::query!(
+            r#"
+            SELECT status as "status: PaymentStatus", payment_mode as "payment_mode: PaymentMode"
+            FROM payments
+            WHERE id = $1 AND merchant_id = $2
+            "#,
+            payment_id,
+            merchant_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to fetch payment")?
+        .ok_or_else(|| anyhow::anyhow!("Payment not found"))?;
+
+        if payment.status != PaymentStatus::PaidAfterExpiry {
+            anyhow::bail!("Payment has no late deposit awaiting resolution");
+        }
+
+        // 合约收款的资金在合约中，没有可由归集任务退回的收款地址
+        if payment.payment_mode == PaymentMode::Contract && matches!(request.action, LatePaymentAction::Refund) {
+            anyhow::bail!("Late contract payments cannot be refunded automatically");
+        }
+
+        match request.action {
+            LatePaymentAction::Accept => {
+                sqlx::query!(
+                    r#"
+                    UPDATE payments
+                    SET status = 'confirmed', late_payment_action = 'accepted',
+                        late_payment_resolved_at = NOW(), updated_at = NOW()
+                    WHERE id = $1 AND status = 'paid_after_expiry'
+                    "#,
+                    payment_id
+                )
+                .execute(&self.pool)
+                .await
+                .context("Failed to accept late payment")?;
+
+                let status = self.ethereum_service.settle_payment(payment_id, &self.pool).await?;
+                log::info!("Merchant {} accepted late payment {} ({:?})", merchant_id, payment_id, status);
+            },
+            LatePaymentAction::Refund => {
+                // 未指定退款地址时退回最早一笔到账的付款地址
+                let refund_address = match request.refund_address {
+                    Some(address) => address,
+                    None => sqlx::query_scalar!(
+                        r#"
+                        SELECT from_address
+                        FROM blockchain_transactions
+                        WHERE payment_id = $1 AND status = 'confirmed'
+                        ORDER BY block_number ASC, log_index ASC
+                        LIMIT 1
+                        "#,
+                        payment_id
+                    )
+                    .fetch_optional(&self.pool)
+                    .await
+                    .context("Failed to fetch deposit sender")?
+                    .ok_or_else(|| anyhow::anyhow!("No deposit found for payment"))?,
+                };
+
+                if !validate_ethereum_address(&refund_address) {
+                    anyhow::bail!("Invalid refund address");
+                }
+
+                sqlx::query!(
+                    r#"
+                    UPDATE payments
+                    SET status = 'refunded', late_payment_action = 'refunded', refund_address = $1,
+                        late_payment_resolved_at = NOW(), updated_at = NOW()
+                    WHERE id = $2 AND status = 'paid_after_expiry'
+                    "#,
+                    refund_address,
+                    payment_id
+                )
+                .execute(&self.pool)
+                .await
+                .context("Failed to refund late payment")?;
+
+                log::info!("Merchant {} refunded late payment {} to {}", merchant_id, payment_id, refund_address);
+            },
+        }
+
+        // 通知商户处理结果 (接受后的结算状态或payment.refunded)
+        let webhook_service = WebhookService::new(self.pool.clone(), 5);
+        tokio::spawn(async move {
+            if let Err(e) = webhook_service.notify_payment_status(payment_id).await {
+                log::error!("Failed to notify resolved late payment {}: {}", payment_id, e);
+            }
+        });
+
+        self.get_payment(payment_id, merchant_id).await?
+            .ok_or_else(|| anyhow::anyhow!("Payment not found"))
+    }
+
     /// 标记过期的支付订单
+    ///
+    /// 少付订单同样过期，之后的补款按迟到付款处理
     /// 
     /// # Returns
     /// * 标记的订单数量
@@ -292,7 +569,7 @@ impl PaymentService {
             r#"
             UPDATE payments 
             SET status = 'expired', updated_at = NOW()
-            WHERE status = 'pending' AND expires_at < NOW()
+            WHERE status IN ('pending', 'underpaid') AND expires_at < NOW()
             "#
         )
         .execute(&self.pool)
@@ -315,12 +592,13 @@ impl PaymentService {
         let payments = sqlx::query_as!(
             Payment,
             r#"
-            SELECT id, merchant_id, order_id, amount, 
-                   currency as "currency: _", payment_address,
-                   status as "status: _", transaction_hash, confirmations,
+            SELECT id, merchant_id, order_id, amount, amount_received,
+                   chain as "chain: _", currency as "currency: _", payment_mode as "payment_mode: _",
+                   payment_address, contract_order_id, contract_signature, success_url, cancel_url,
+                   status as "status: _", transaction_hash, confirmations, required_confirmations,
                    expires_at, created_at, updated_at
             FROM payments 
-            WHERE status IN ('pending', 'confirmed') 
+            WHERE status IN ('pending', 'detected', 'confirmed') 
             AND (expires_at IS NULL OR expires_at > NOW())
             ORDER BY created_at ASC
             "#
@@ -332,31 +610,48 @@ impl PaymentService {
         Ok(payments)
     }
 
-    /// 生成支付URL
-    fn generate_payment_url(&self, currency: &Currency, address: &str, amount: &Decimal) -> String {
-        match currency {
-            Currency::ETH => {
-                let wei_amount = amount * Decimal::from(10_u64.pow(18));
-                format!("ethereum:{}?value={}", address, wei_amount.trunc())
-            },
-            Currency::USDT => {
-                let usdt_amount = amount * Decimal::from(10_u64.pow(6));
-                format!("ethereum:{}@1/transfer?address={}&uint256={}",
-                    currency.contract_address().unwrap(),
-                    address,
-                    usdt_amount.trunc()
-                )
-            }
-        }
+    /// 生成EIP-681支付URL
+    fn generate_payment_url(
+        &self,
+        token: &Token,
+        address: &str,
+        amount: &Decimal,
+        contract_order: Option<ContractOrderArgs<'_>>,
+    ) -> Result<String> {
+        let uri = PaymentUri::for_payment(
+            token,
+            self.ethereum_service.chain_id(),
+            address,
+            *amount,
+            contract_order,
+        )?;
+
+        Ok(uri.to_string())
     }
 
     /// 验证创建支付请求
-    fn validate_create_request(&self, request: &CreatePaymentRequest) -> Result<()> {
+    ///
+    /// # Returns
+    /// * 订单的收款代币
+    fn validate_create_request(&self, request: &CreatePaymentRequest) -> Result<Token> {
         // 验证订单ID
         validate_order_id(&request.order_id)?;
 
+        // 验证链与币种组合 (代币须已登记且启用)
+        let token = self.token_registry.get_enabled(&request.chain, &request.currency)?;
+
         // 验证支付金额
-        validate_payment_amount(&request.amount, &format!("{:?}", request.currency))?;
+        validate_payment_amount(&request.amount, &token)?;
+
+        // 合约的receivePayment只接收原生币
+        if request.payment_mode == PaymentMode::Contract {
+            if !token.is_native() {
+                anyhow::bail!("Contract payments only support the native currency");
+            }
+            if self.ethereum_service.payment_contract().is_none() || self.contract_signer.is_none() {
+                anyhow::bail!("Contract payments are not enabled on {}", request.chain);
+            }
+        }
 
         // 验证过期时间
         if let Some(expires_in) = request.expires_in {
@@ -368,6 +663,13 @@ impl PaymentService {
             }
         }
 
+        // 验证确认数
+        if let Some(confirmations) = request.required_confirmations {
+            if !(1..=MAX_REQUIRED_CONFIRMATIONS).contains(&confirmations) {
+                anyhow::bail!("Required confirmations must be between 1 and {}", MAX_REQUIRED_CONFIRMATIONS);
+            }
+        }
+
         // 验证回调URL
         if let Some(callback_url) = &request.callback_url {
             if !callback_url.is_empty() && !crate::utils::validate_url(callback_url) {
@@ -375,7 +677,16 @@ impl PaymentService {
             }
         }
 
-        Ok(())
+        // 验证收银台跳转地址 (只允许http/https)
+        for (field, url) in [("success", &request.success_url), ("cancel", &request.cancel_url)] {
+            if let Some(url) = url {
+                if url.len() > 500 || !crate::utils::validate_url(url) {
+                    anyhow::bail!("Invalid {} URL format", field);
+                }
+            }
+        }
+
+        Ok(token)
     }
 
     /// 检查订单ID是否已存在
@@ -413,7 +724,23 @@ mod tests {
             5, // Goerli testnet
         ).await.expect("Failed to create Ethereum service");
 
-        PaymentService::new(pool, ethereum_service)
+        let provider = ethers::providers::Provider::<ethers::providers::Http>::try_from(
+            "https://eth-goerli.alchemyapi.io/v2/demo"
+        ).expect("Failed to create provider");
+        let wallet_manager = WalletManager::new(
+            "test test test test test test test test test test test junk",
+            None,
+            crate::utils::KeyRing::parse(&format!("1:{}", "00".repeat(32)), None).unwrap(),
+            100,
+            "0x0000000000000000000000000000000000000001",
+            Arc::new(provider),
+            pool.clone(),
+            0.1,
+        ).expect("Failed to create wallet manager");
+
+        let token_registry = TokenRegistry::load(pool.clone()).await.expect("Failed to load tokens");
+
+        PaymentService::new(pool, ethereum_service, Arc::new(wallet_manager), token_registry)
     }
 
     #[tokio::test]
@@ -424,9 +751,14 @@ mod tests {
         let request = CreatePaymentRequest {
             order_id: "TEST_ORDER_001".to_string(),
             amount: Decimal::new(100, 2), // 1.00
+            chain: Chain::Ethereum,
             currency: Currency::USDT,
+            payment_mode: PaymentMode::Address,
             callback_url: Some("https://example.com/webhook".to_string()),
             expires_in: Some(3600), // 1小时
+            required_confirmations: None,
+            success_url: None,
+            cancel_url: None,
         };
 
         let response = service.create_payment(merchant_id, request).await.unwrap();
@@ -447,9 +779,14 @@ mod tests {
         let valid_request = CreatePaymentRequest {
             order_id: "VALID_ORDER_123".to_string(),
             amount: Decimal::new(100, 2),
+            chain: Chain::Ethereum,
             currency: Currency::ETH,
+            payment_mode: PaymentMode::Address,
             callback_url: None,
             expires_in: Some(3600),
+            required_confirmations: None,
+            success_url: None,
+            cancel_url: None,
         };
         assert!(service.validate_create_request(&valid_request).is_ok());
 
@@ -457,9 +794,14 @@ mod tests {
         let invalid_amount_request = CreatePaymentRequest {
             order_id: "ORDER_123".to_string(),
             amount: Decimal::ZERO,
+            chain: Chain::Ethereum,
             currency: Currency::ETH,
+            payment_mode: PaymentMode::Address,
             callback_url: None,
             expires_in: Some(3600),
+            required_confirmations: None,
+            success_url: None,
+            cancel_url: None,
         };
         assert!(service.validate_create_request(&invalid_amount_request).is_err());
 
@@ -467,10 +809,60 @@ mod tests {
         let invalid_expiry_request = CreatePaymentRequest {
             order_id: "ORDER_123".to_string(),
             amount: Decimal::new(100, 2),
+            chain: Chain::Ethereum,
             currency: Currency::ETH,
+            payment_mode: PaymentMode::Address,
             callback_url: None,
             expires_in: Some(-1),
+            required_confirmations: None,
+            success_url: None,
+            cancel_url: None,
         };
         assert!(service.validate_create_request(&invalid_expiry_request).is_err());
+
+        // 无效确认数
+        let invalid_confirmations_request = CreatePaymentRequest {
+            order_id: "ORDER_123".to_string(),
+            amount: Decimal::new(100, 2),
+            chain: Chain::Ethereum,
+            currency: Currency::ETH,
+            payment_mode: PaymentMode::Address,
+            callback_url: None,
+            expires_in: Some(3600),
+            required_confirmations: Some(0),
+            success_url: None,
+            cancel_url: None,
+        };
+        assert!(service.validate_create_request(&invalid_confirmations_request).is_err());
+
+        // 无效跳转地址
+        let invalid_redirect_request = CreatePaymentRequest {
+            order_id: "ORDER_123".to_string(),
+            amount: Decimal::new(100, 2),
+            chain: Chain::Ethereum,
+            currency: Currency::ETH,
+            payment_mode: PaymentMode::Address,
+            callback_url: None,
+            expires_in: Some(3600),
+            required_confirmations: None,
+            success_url: Some("javascript:alert(1)".to_string()),
+            cancel_url: None,
+        };
+        assert!(service.validate_create_request(&invalid_redirect_request).is_err());
+
+        // 该链不支持的币种
+        let unsupported_chain_request = CreatePaymentRequest {
+            order_id: "ORDER_123".to_string(),
+            amount: Decimal::new(100, 2),
+            chain: Chain::Bsc,
+            currency: Currency::ETH,
+            payment_mode: PaymentMode::Address,
+            callback_url: None,
+            expires_in: Some(3600),
+            required_confirmations: None,
+            success_url: None,
+            cancel_url: None,
+        };
+        assert!(service.validate_create_request(&unsupported_chain_request).is_err());
     }
 }
@@ -0,0 +1,307 @@
+// ERC20 Transfer事件索引服务
+// 按收款地址 (topic2) 批量查询代币合约的Transfer日志，统一检测所有代币支付
+
+use ethers::{
+    prelude::*,
+    providers::{Provider, Http},
+    types::{Address, Block, Filter, Log, H256},
+    utils::format_units,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+use anyhow::{Result, Context};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+use crate::models::{Chain, Currency, PaymentMode, Token};
+use crate::services::{EthereumService, ChainCursor, TokenRegistry, ethereum_service::DetectedTransfer};
+use crate::utils::{ERC20_TRANSFER_EVENT, decode_erc20_transfer_log};
+
+/// 扫描游标名称
+const CURSOR_NAME: &str = "erc20_transfers";
+
+/// 每次 `eth_getLogs` 查询的最大区块跨度
+const MAX_BLOCKS_PER_QUERY: u64 = 500;
+
+/// 每次 `eth_getLogs` 查询的收款地址数量 (topic2的OR条件)
+const ADDRESS_BATCH_SIZE: usize = 1000;
+
+/// 索引间隔 (秒)
+const INDEX_INTERVAL_SECS: u64 = 12;
+
+/// ERC20 Transfer事件索引服务
+///
+/// 替代每笔支付单独的监听任务，单个进程按批次查询所有待支付地址的转入事件。每条链运行一个实例
+pub struct TokenIndexer {
+    ethereum_service: EthereumService,
+    /// 代币登记 (索引当前链上登记的全部ERC-20代币)
+    token_registry: TokenRegistry,
+    provider: Arc<Provider<Http>>,
+    pool: PgPool,
+    cursor: ChainCursor,
+    /// 订单过期后继续监听的宽限期 (分钟)
+    late_payment_grace_minutes: i32,
+}
+
+impl TokenIndexer {
+    /// 创建新的Transfer事件索引服务
+    ///
+    /// # Arguments
+    /// * `ethereum_service` - 所索引链的以太坊服务
+    /// * `pool` - 数据库连接池
+    /// * `token_registry` - 代币登记
+    /// * `late_payment_grace_minutes` - 订单过期后继续监听收款地址的宽限期 (分钟)
+    pub fn new(
+        ethereum_service: EthereumService,
+        pool: PgPool,
+        token_registry: TokenRegistry,
+        late_payment_grace_minutes: i32,
+    ) -> Self {
+        let provider = ethereum_service.provider();
+        let chain = ethereum_service.chain();
+
+        Self {
+            cursor: ChainCursor::new(chain, CURSOR_NAME, provider.clone(), pool.clone()),
+            token_registry,
+            provider,
+            ethereum_service,
+            pool,
+            late_payment_grace_minutes,
+        }
+    }
+
+    /// 启动事件索引任务
+    pub async fn start_indexing(&self) -> Result<()> {
+        log::info!("Starting ERC20 Transfer event indexer on {}", self.ethereum_service.chain());
+
+        loop {
+            // 加载新登记的代币，刷新失败时沿用上一轮的代币列表
+            if let Err(e) = self.token_registry.refresh().await {
+                log::warn!("Failed to refresh token registry: {}", e);
+            }
+
+            match self.provider.get_block_number().await {
+                Ok(latest_block) => {
+                    let latest_block = latest_block.as_u64();
+
+                    // 从持久化游标继续，首次运行时从最近区块开始
+                    match self.cursor.next_block(latest_block.saturating_sub(MAX_BLOCKS_PER_QUERY)).await {
+                        Ok(next_block) if next_block <= latest_block => {
+                            let to_block = latest_block.min(next_block + MAX_BLOCKS_PER_QUERY - 1);
+
+                            if let Err(e) = self.index_blocks(next_block, to_block).await {
+                                log::error!("Failed to index blocks {}-{}: {}", next_block, to_block, e);
+                            }
+                        },
+                        Ok(_) => {},
+                        Err(e) => log::error!("Failed to get chain cursor: {}", e),
+                    }
+                },
+                Err(e) => log::warn!("Failed to get latest block: {}", e),
+            }
+
+            sleep(Duration::from_secs(INDEX_INTERVAL_SECS)).await;
+        }
+    }
+
+    /// 索引区块范围内转入待支付地址的代币转账并更新支付状态
+    ///
+    /// # Arguments
+    /// * `from_block` - 起始区块 (包含)
+    /// * `to_block` - 结束区块 (包含)
+    ///
+    /// # Returns
+    /// * 检测到的转入交易数量
+    pub async fn index_blocks(&self, from_block: u64, to_block: u64) -> Result<usize> {
+        let mut detected = 0;
+        let tokens = self.token_registry.erc20_tokens(&self.ethereum_service.chain());
+
+        // 父哈希与游标不一致说明发生重组，回滚后下一轮从分叉点继续
+        let first_block = self.get_block_header(from_block).await?;
+        if let Some(fork_block) = self.cursor.check_parent(from_block, first_block.parent_hash).await? {
+            let currencies: Vec<Currency> = tokens.iter().map(|token| token.symbol.clone()).collect();
+            self.cursor.rollback(fork_block, &self.ethereum_service, &currencies, PaymentMode::Address).await?;
+            return Ok(detected);
+        }
+
+        let last_block = if to_block == from_block {
+            first_block
+        } else {
+            self.get_block_header(to_block).await?
+        };
+        let last_block_hash = last_block.hash
+            .ok_or_else(|| anyhow::anyhow!("Block {} has no hash", to_block))?;
+
+        for token in &tokens {
+            let contract: Address = match token.contract_address.as_deref().map(str::parse) {
+                Some(Ok(contract)) => contract,
+                _ => {
+                    log::warn!("Invalid contract address for {} on {}", token.symbol, token.chain);
+                    continue;
+                }
+            };
+            let addresses = self.get_pending_addresses(&token.symbol).await?;
+            let recipients: Vec<Address> = addresses.keys().copied().collect();
+
+            for batch in recipients.chunks(ADDRESS_BATCH_SIZE) {
+                let filter = transfer_filter(contract, batch, from_block, to_block);
+                let logs = self.provider.get_logs(&filter).await
+                    .context("Failed to get Transfer logs")?;
+
+                for (payment_id, transfer) in match_transfer_logs(&logs, &addresses, token) {
+                    log::info!("Detected {:?} deposit of {} to {:?} in tx {:?} for payment {}",
+                        token.symbol, format_units(transfer.value, token.decimals())?,
+                        transfer.to, transfer.tx_hash, payment_id);
+
+                    // 处理失败时不推进游标，下一轮重新索引该区块范围
+                    self.ethereum_service.process_transfer(payment_id, &transfer, &self.pool).await
+                        .with_context(|| format!("Failed to process deposit {:?}", transfer.tx_hash))?;
+
+                    detected += 1;
+                }
+            }
+        }
+
+        // 区块范围内的重组会改变末尾区块哈希，下一轮校验时即可检测
+        self.cursor.record_block(to_block, last_block_hash, last_block.parent_hash).await?;
+
+        Ok(detected)
+    }
+
+    /// 获取区块头
+    async fn get_block_header(&self, number: u64) -> Result<Block<H256>> {
+        self.provider.get_block(number).await
+            .context("Failed to get block")?
+            .ok_or_else(|| anyhow::anyhow!("Block {} not found", number))
+    }
+
+    /// 获取等待该代币付款的支付地址 (包括少付待补款、确认中可能追加付款以及宽限期内的过期订单)
+    async fn get_pending_addresses(&self, currency: &Currency) -> Result<HashMap<Address, Uuid>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT pa.address, pa.payment_id
+            FROM payment_addresses pa
+            JOIN payments p ON p.id = pa.payment_id
+            WHERE p.chain = $1 AND p.currency = $2
+              AND (p.status IN ('pending', 'detected', 'underpaid', 'confirmed')
+                   OR (p.status IN ('expired', 'paid_after_expiry')
+                       AND p.expires_at > NOW() - make_interval(mins => $3)))
+            "#,
+            self.ethereum_service.chain() as Chain,
+            currency.clone() as Currency,
+            self.late_payment_grace_minutes
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch pending payment addresses")?;
+
+        let mut addresses = HashMap::with_capacity(rows.len());
+        for row in rows {
+            match row.address.parse::<Address>() {
+                Ok(address) => { addresses.insert(address, row.payment_id); },
+                Err(e) => log::warn!("Invalid payment address {}: {}", row.address, e),
+            }
+        }
+
+        Ok(addresses)
+    }
+}
+
+/// 构造代币合约转入指定地址的Transfer事件过滤器
+fn transfer_filter(contract: Address, recipients: &[Address], from_block: u64, to_block: u64) -> Filter {
+    Filter::new()
+        .address(contract)
+        .topic0(ERC20_TRANSFER_EVENT)
+        .topic2(recipients.iter().map(|address| H256::from(*address)).collect::<Vec<_>>())
+        .from_block(from_block)
+        .to_block(to_block)
+}
+
+/// 解析Transfer日志并匹配待支付地址
+fn match_transfer_logs(
+    logs: &[Log],
+    addresses: &HashMap<Address, Uuid>,
+    token: &Token,
+) -> Vec<(Uuid, DetectedTransfer)> {
+    logs.iter()
+        // 被重组移除的日志不计入
+        .filter(|log| log.removed != Some(true))
+        .filter_map(|log| {
+            let transfer = decode_erc20_transfer_log(log)?;
+            if transfer.amount.is_zero() {
+                return None;
+            }
+            let payment_id = addresses.get(&transfer.to)?;
+
+            Some((*payment_id, DetectedTransfer {
+                tx_hash: log.transaction_hash?,
+                from: transfer.from,
+                to: transfer.to,
+                value: transfer.amount,
+                currency: token.symbol.clone(),
+                decimals: token.decimals(),
+                block_number: log.block_number,
+                log_index: log.log_index,
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi;
+    use crate::models::test_token;
+
+    fn transfer_log(to: Address, amount: u64) -> Log {
+        Log {
+            topics: vec![ERC20_TRANSFER_EVENT, H256::from(Address::repeat_byte(0x22)), H256::from(to)],
+            data: abi::encode(&[abi::Token::Uint(U256::from(amount))]).into(),
+            transaction_hash: Some(H256::repeat_byte(0xab)),
+            block_number: Some(100.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_transfer_filter_topics() {
+        let recipients = vec![Address::repeat_byte(0x11), Address::repeat_byte(0x33)];
+        let filter = transfer_filter(Address::repeat_byte(0xee), &recipients, 10, 20);
+
+        let expected: Vec<Option<H256>> = recipients.iter().map(|a| Some(H256::from(*a))).collect();
+        assert_eq!(filter.topics[0], Some(ERC20_TRANSFER_EVENT.into()));
+        assert_eq!(filter.topics[1], None);
+        assert_eq!(filter.topics[2], Some(ValueOrArray::Array(expected)));
+    }
+
+    #[test]
+    fn test_match_transfer_logs() {
+        let payment_address = Address::repeat_byte(0x11);
+        let payment_id = Uuid::new_v4();
+        let addresses = HashMap::from([(payment_address, payment_id)]);
+
+        let mut removed = transfer_log(payment_address, 3_000_000);
+        removed.removed = Some(true);
+
+        let mut deposit = transfer_log(payment_address, 2_500_000);
+        deposit.log_index = Some(U256::from(3));
+
+        let logs = vec![
+            deposit,
+            transfer_log(payment_address, 0),
+            transfer_log(Address::repeat_byte(0x33), 1_000_000),
+            removed,
+        ];
+
+        let usdt = test_token(Chain::Ethereum, Currency::USDT, Some("0xdAC17F958D2ee523a2206206994597C13D831ec7"), 6);
+
+        let transfers = match_transfer_logs(&logs, &addresses, &usdt);
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].0, payment_id);
+        assert_eq!(transfers[0].1.value, U256::from(2_500_000u64));
+        assert_eq!(transfers[0].1.from, Address::repeat_byte(0x22));
+        assert_eq!(transfers[0].1.currency, Currency::USDT);
+        assert_eq!(transfers[0].1.decimals, 6);
+        assert_eq!(transfers[0].1.log_index, Some(U256::from(3)));
+    }
+}
@@ -0,0 +1,228 @@
+// 代币登记服务
+// 维护tokens表及其内存缓存，供订单校验、支付链接生成、链上扫描与归集查询代币信息
+
+use sqlx::PgPool;
+use uuid::Uuid;
+use anyhow::{Result, Context};
+use std::sync::{Arc, RwLock};
+use rust_decimal::Decimal;
+use crate::models::{Chain, Currency, Token, CreateTokenRequest, UpdateTokenRequest, NATIVE_DECIMALS};
+use crate::utils::validate_ethereum_address;
+
+/// 代币精度上限
+const MAX_TOKEN_DECIMALS: i32 = 36;
+
+/// 代币登记服务
+///
+/// 查询走内存缓存，登记或修改代币后立即刷新；后台任务在每轮开始时调用`refresh`
+/// 以获取其他进程登记的新代币
+#[derive(Clone)]
+pub struct TokenRegistry {
+    pool: PgPool,
+    tokens: Arc<RwLock<Vec<Token>>>,
+}
+
+impl TokenRegistry {
+    /// 创建空的代币登记服务 (需调用`refresh`加载)
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool, tokens: Arc::new(RwLock::new(Vec::new())) }
+    }
+
+    /// 创建代币登记服务并加载全部代币
+    pub async fn load(pool: PgPool) -> Result<Self> {
+        let registry = Self::new(pool);
+        registry.refresh().await?;
+        Ok(registry)
+    }
+
+    /// 从数据库重新加载代币
+    pub async fn refresh(&self) -> Result<()> {
+        let tokens = sqlx::query_as!(
+            Token,
+            r#"
+            SELECT id, chain as "chain: _", symbol as "symbol: _", contract_address,
+                   decimals, min_amount, max_amount, enabled, created_at, updated_at
+            FROM tokens
+            ORDER BY chain, symbol
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to load tokens")?;
+
+        *self.tokens.write().expect("token registry lock poisoned") = tokens;
+        Ok(())
+    }
+
+    /// 查找代币 (包括已停用的代币，用于已有订单的扫描与归集)
+    pub fn find(&self, chain: &Chain, symbol: &Currency) -> Option<Token> {
+        self.tokens.read().expect("token registry lock poisoned")
+            .iter()
+            .find(|token| token.chain == *chain && token.symbol == *symbol)
+            .cloned()
+    }
+
+    /// 查找代币，未登记时返回错误
+    pub fn get(&self, chain: &Chain, symbol: &Currency) -> Result<Token> {
+        self.find(chain, symbol)
+            .ok_or_else(|| anyhow::anyhow!("Currency {} is not registered on {}", symbol, chain))
+    }
+
+    /// 获取可用于新建订单的代币
+    pub fn get_enabled(&self, chain: &Chain, symbol: &Currency) -> Result<Token> {
+        match self.find(chain, symbol) {
+            Some(token) if token.enabled => Ok(token),
+            _ => anyhow::bail!("Currency {} is not supported on {}", symbol, chain),
+        }
+    }
+
+    /// 获取指定链上的全部ERC-20代币 (包括已停用的代币)
+    pub fn erc20_tokens(&self, chain: &Chain) -> Vec<Token> {
+        self.tokens.read().expect("token registry lock poisoned")
+            .iter()
+            .filter(|token| token.chain == *chain && !token.is_native())
+            .cloned()
+            .collect()
+    }
+
+    /// 列出代币
+    ///
+    /// # Arguments
+    /// * `chain` - 链过滤 (为空时返回全部)
+    /// * `enabled_only` - 是否只返回启用的代币
+    pub fn list(&self, chain: Option<Chain>, enabled_only: bool) -> Vec<Token> {
+        self.tokens.read().expect("token registry lock poisoned")
+            .iter()
+            .filter(|token| chain.map_or(true, |chain| token.chain == chain))
+            .filter(|token| !enabled_only || token.enabled)
+            .cloned()
+            .collect()
+    }
+
+    /// 登记新代币
+    ///
+    /// # Arguments
+    /// * `request` - 登记代币请求
+    ///
+    /// # Returns
+    /// * 登记的代币
+    pub async fn create_token(&self, request: CreateTokenRequest) -> Result<Token> {
+        Self::validate_create_request(&request)?;
+
+        if self.find(&request.chain, &request.symbol).is_some() {
+            anyhow::bail!("Currency {} is already registered on {}", request.symbol, request.chain);
+        }
+
+        let token = sqlx::query_as!(
+            Token,
+            r#"
+            INSERT INTO tokens (chain, symbol, contract_address, decimals, min_amount, max_amount, enabled)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, chain as "chain: _", symbol as "symbol: _", contract_address,
+                      decimals, min_amount, max_amount, enabled, created_at, updated_at
+            "#,
+            request.chain as Chain,
+            request.symbol as Currency,
+            request.contract_address,
+            request.decimals,
+            request.min_amount,
+            request.max_amount,
+            request.enabled.unwrap_or(true)
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to register token")?;
+
+        log::info!("Registered token {} on {} ({:?}, {} decimals)",
+            token.symbol, token.chain, token.contract_address, token.decimals);
+
+        self.refresh().await?;
+        Ok(token)
+    }
+
+    /// 更新代币金额限制或启用状态
+    ///
+    /// # Arguments
+    /// * `token_id` - 代币ID
+    /// * `request` - 更新代币请求
+    ///
+    /// # Returns
+    /// * 更新后的代币 (不存在时为None)
+    pub async fn update_token(&self, token_id: Uuid, request: UpdateTokenRequest) -> Result<Option<Token>> {
+        let current = match self.tokens.read().expect("token registry lock poisoned")
+            .iter()
+            .find(|token| token.id == token_id)
+            .cloned()
+        {
+            Some(token) => token,
+            None => return Ok(None),
+        };
+
+        let min_amount = request.min_amount.unwrap_or(current.min_amount);
+        let max_amount = request.max_amount.unwrap_or(current.max_amount);
+        Self::validate_amount_limits(min_amount, max_amount)?;
+
+        let token = sqlx::query_as!(
+            Token,
+            r#"
+            UPDATE tokens
+            SET min_amount = $2, max_amount = $3, enabled = $4
+            WHERE id = $1
+            RETURNING id, chain as "chain: _", symbol as "symbol: _", contract_address,
+                      decimals, min_amount, max_amount, enabled, created_at, updated_at
+            "#,
+            token_id,
+            min_amount,
+            max_amount,
+            request.enabled.unwrap_or(current.enabled)
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to update token")?;
+
+        self.refresh().await?;
+        Ok(token)
+    }
+
+    /// 验证登记代币请求
+    fn validate_create_request(request: &CreateTokenRequest) -> Result<()> {
+        Self::validate_amount_limits(request.min_amount, request.max_amount)?;
+
+        if request.decimals < 0 || request.decimals > MAX_TOKEN_DECIMALS {
+            anyhow::bail!("Token decimals must be between 0 and {}", MAX_TOKEN_DECIMALS);
+        }
+
+        match &request.contract_address {
+            // 原生代币只能是该链的原生币
+            None => {
+                if !request.symbol.is_native_on(&request.chain) {
+                    anyhow::bail!("Native currency on {} is {}", request.chain, request.chain.native_currency());
+                }
+                if request.decimals != NATIVE_DECIMALS as i32 {
+                    anyhow::bail!("Native currency must have {} decimals", NATIVE_DECIMALS);
+                }
+            },
+            Some(contract_address) => {
+                if !validate_ethereum_address(contract_address) {
+                    anyhow::bail!("Invalid contract address");
+                }
+                if request.symbol.is_native_on(&request.chain) {
+                    anyhow::bail!("{} is the native currency on {}", request.symbol, request.chain);
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// 验证金额限制
+    fn validate_amount_limits(min_amount: Decimal, max_amount: Decimal) -> Result<()> {
+        if min_amount <= Decimal::ZERO {
+            anyhow::bail!("min_amount must be positive");
+        }
+        if max_amount < min_amount {
+            anyhow::bail!("max_amount must not be less than min_amount");
+        }
+        Ok(())
+    }
+}
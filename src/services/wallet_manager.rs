@@ -4,109 +4,399 @@
 use ethers::{
     prelude::*,
     providers::{Provider, Http},
-    types::{Address, U256, TransactionRequest, Bytes},
-    utils::parse_ether,
+    types::{Address, U256, TransactionRequest, Eip1559TransactionRequest, transaction::eip2718::TypedTransaction},
+    utils::{parse_ether, parse_units, format_units},
     signers::{LocalWallet, Signer},
 };
-use sqlx::PgPool;
+use coins_bip32::prelude::{SigningKey, XPriv, XPub};
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
+use chrono::Utc;
 use anyhow::{Result, Context};
+use rust_decimal::Decimal;
+use std::str::FromStr;
 use std::sync::Arc;
-use std::collections::HashMap;
-use tokio::sync::RwLock;
-use crate::models::{PaymentAddress, WalletInfo};
+use std::num::NonZeroUsize;
+use lru::LruCache;
+use tokio::sync::Mutex;
+use crate::utils::{
+    parse_master_key, parse_account_xpub, derive_account_xpriv, derive_signing_key,
+    derive_address_from_xpub, account_xpub, derivation_path, signing_key_address, KeyRing,
+    encode_erc20_balance_of, encode_erc20_transfer,
+};
+use crate::config::Config;
+use crate::models::{Chain, Currency, NATIVE_DECIMALS};
+use crate::services::TokenRegistry;
+use crate::services::gas_oracle::{GasOracle, Eip1559Fees, DEFAULT_MAX_FEE_GWEI, bump_fees};
+use crate::services::nonce_manager::NonceManager;
+
+/// 早期未加密私钥的密钥版本 (十六进制明文)
+const LEGACY_KEY_VERSION: i32 = 0;
+
+/// 未配置时归集交易的替换等待时间 (分钟)
+const DEFAULT_TX_REPLACE_AFTER_MINUTES: i64 = 15;
+
+/// 未配置时归集交易的确认区块数
+const DEFAULT_SWEEP_CONFIRMATIONS: u64 = 12;
+
+/// 每轮重新扫描的已归集地址数量
+const RESCAN_BATCH_SIZE: i64 = 200;
 
 /// HD钱包管理器
+///
+/// 支持两种模式:
+/// * 签名模式: 持有HD主密钥，可派生地址并签名归集交易
+/// * 观察模式: 仅持有xpub，只能派生地址，归集由独立的签名器进程完成
 pub struct WalletManager {
-    /// 主钱包（用于签名交易）
-    master_wallet: LocalWallet,
+    /// BIP44外部链扩展公钥 (m/44'/60'/0'/0)
+    account_xpub: XPub,
+    /// BIP44外部链扩展私钥 (观察模式下为None)
+    account_key: Option<XPriv>,
+    /// 私钥加密密钥环 (观察模式下为None)
+    key_ring: Option<Arc<KeyRing>>,
     /// 以太坊提供者
     provider: Arc<Provider<Http>>,
-    /// 地址索引计数器
-    address_index: Arc<RwLock<u32>>,
-    /// 地址到私钥的映射缓存
-    address_cache: Arc<RwLock<HashMap<Address, LocalWallet>>>,
+    /// 归集所在链 (充值地址在各EVM链上相同，归集按链分别进行)
+    chain: Chain,
+    /// 地址到私钥的LRU缓存 (未命中时从数据库加载)
+    address_cache: Arc<Mutex<LruCache<Address, LocalWallet>>>,
     /// 数据库连接池
     pool: PgPool,
+    /// 代币登记 (合约地址与精度，每轮归集前刷新)
+    token_registry: TokenRegistry,
     /// 归集阈值（ETH）
     collection_threshold: U256,
+    /// ERC20代币归集阈值 (代币单位)
+    token_collection_threshold: f64,
+    /// gas钱包 (为代币归集补充手续费，仅签名器配置)
+    gas_tank: Option<LocalWallet>,
+    /// EIP-1559费用估算器
+    gas_oracle: GasOracle,
+    /// 发送地址nonce管理
+    nonce_manager: NonceManager,
+    /// 归集交易超过该时间未上链时提高费用替换
+    tx_replace_after: chrono::Duration,
+    /// 归集交易达到该确认数后地址才标记为已归集
+    sweep_confirmations: u64,
     /// 主归集地址
     master_address: Address,
 }
 
 impl WalletManager {
-    /// 创建新的钱包管理器
+    /// 创建签名模式的钱包管理器
+    ///
+    /// # Arguments
+    /// * `master_key` - HD主密钥 (BIP39助记词或xprv扩展私钥)
+    /// * `passphrase` - BIP39助记词密码 (可选)
+    /// * `key_ring` - 私钥加密密钥环
+    /// * `key_cache_size` - 签名私钥缓存容量
+    /// * `collection_address` - 归集目标地址
+    /// * `provider` - 以太坊提供者
+    /// * `pool` - 数据库连接池
+    /// * `collection_threshold_eth` - 归集阈值 (ETH)
     pub fn new(
-        master_private_key: &str,
+        master_key: &str,
+        passphrase: Option<&str>,
+        key_ring: KeyRing,
+        key_cache_size: usize,
+        collection_address: &str,
+        provider: Arc<Provider<Http>>,
+        pool: PgPool,
+        collection_threshold_eth: f64,
+    ) -> Result<Self> {
+        let master = parse_master_key(master_key, passphrase)
+            .context("Invalid HD master key")?;
+        let account_key = derive_account_xpriv(&master)?;
+
+        Self::with_keys(
+            account_xpub(&account_key),
+            Some(account_key),
+            Some(Arc::new(key_ring)),
+            key_cache_size,
+            collection_address,
+            provider,
+            pool,
+            collection_threshold_eth,
+        )
+    }
+
+    /// 创建观察模式的钱包管理器
+    ///
+    /// 进程内不保存任何私钥，适用于对外提供服务的API进程
+    ///
+    /// # Arguments
+    /// * `xpub` - 账户级或外部链级xpub扩展公钥
+    /// * `collection_address` - 归集目标地址
+    /// * `provider` - 以太坊提供者
+    /// * `pool` - 数据库连接池
+    /// * `collection_threshold_eth` - 归集阈值 (ETH)
+    pub fn new_watch_only(
+        xpub: &str,
+        collection_address: &str,
+        provider: Arc<Provider<Http>>,
+        pool: PgPool,
+        collection_threshold_eth: f64,
+    ) -> Result<Self> {
+        let account_xpub = parse_account_xpub(xpub)
+            .context("Invalid HD account xpub")?;
+
+        Self::with_keys(
+            account_xpub,
+            None,
+            None,
+            1, // 观察模式不缓存私钥
+            collection_address,
+            provider,
+            pool,
+            collection_threshold_eth,
+        )
+    }
+
+    fn with_keys(
+        account_xpub: XPub,
+        account_key: Option<XPriv>,
+        key_ring: Option<Arc<KeyRing>>,
+        key_cache_size: usize,
+        collection_address: &str,
         provider: Arc<Provider<Http>>,
         pool: PgPool,
         collection_threshold_eth: f64,
     ) -> Result<Self> {
-        let master_wallet: LocalWallet = master_private_key.parse()
-            .context("Invalid master private key")?;
-        
-        let master_address = master_wallet.address();
+        let master_address: Address = collection_address.parse()
+            .context("Invalid collection address")?;
         let collection_threshold = parse_ether(collection_threshold_eth)?;
+        let key_cache_size = NonZeroUsize::new(key_cache_size)
+            .context("Key cache size must be greater than 0")?;
+        let gas_oracle = GasOracle::new(provider.clone(), DEFAULT_MAX_FEE_GWEI)?;
 
         Ok(Self {
-            master_wallet,
-            provider,
-            address_index: Arc::new(RwLock::new(0)),
-            address_cache: Arc::new(RwLock::new(HashMap::new())),
+            account_xpub,
+            account_key,
+            key_ring,
+            provider: provider.clone(),
+            chain: Chain::default(),
+            address_cache: Arc::new(Mutex::new(LruCache::new(key_cache_size))),
+            token_registry: TokenRegistry::new(pool.clone()),
             pool,
             collection_threshold,
+            token_collection_threshold: 0.0,
+            gas_tank: None,
+            gas_oracle,
+            nonce_manager: NonceManager::new(provider.clone()),
+            tx_replace_after: chrono::Duration::minutes(DEFAULT_TX_REPLACE_AFTER_MINUTES),
+            sweep_confirmations: DEFAULT_SWEEP_CONFIRMATIONS,
             master_address,
         })
     }
 
+    /// 是否为观察模式 (不持有私钥)
+    pub fn is_watch_only(&self) -> bool {
+        self.account_key.is_none()
+    }
+
+    /// 根据配置创建API进程使用的钱包管理器
+    ///
+    /// 配置了 `HD_ACCOUNT_XPUB` 时使用观察模式，否则使用 `HD_MASTER_KEY` 签名模式
+    ///
+    /// # Arguments
+    /// * `config` - 应用配置
+    /// * `pool` - 数据库连接池
+    pub fn from_config(config: &Config, pool: PgPool) -> Result<Self> {
+        let wallet = &config.wallet;
+        let collection_address = wallet.collection_address.as_deref()
+            .context("COLLECTION_ADDRESS is not configured")?;
+        let provider = Self::http_provider(&config.default_chain().rpc_url)?;
+
+        match (&wallet.hd_account_xpub, &wallet.hd_master_key) {
+            (Some(xpub), _) => Self::new_watch_only(
+                xpub,
+                collection_address,
+                provider,
+                pool,
+                wallet.collection_threshold,
+            ),
+            (None, Some(master_key)) => Self::new(
+                master_key,
+                wallet.hd_passphrase.as_deref(),
+                config.key_ring()?,
+                wallet.key_cache_size,
+                collection_address,
+                provider,
+                pool,
+                wallet.collection_threshold,
+            ),
+            (None, None) => anyhow::bail!("HD wallet is not configured (set HD_ACCOUNT_XPUB or HD_MASTER_KEY)"),
+        }
+    }
+
+    /// 根据配置创建签名器进程使用的钱包管理器
+    ///
+    /// 持有HD主密钥，并使用 `ETHEREUM_PRIVATE_KEY` 作为代币归集的gas钱包，
+    /// 归集交易的最大费用受 `ETHEREUM_MAX_GAS_PRICE` 限制
+    ///
+    /// # Arguments
+    /// * `config` - 应用配置
+    /// * `pool` - 数据库连接池
+    /// * `chain` - 归集所在链 (需已启用)
+    pub fn signer_from_config(config: &Config, pool: PgPool, chain: &Chain) -> Result<Self> {
+        let wallet = &config.wallet;
+        let master_key = wallet.hd_master_key.as_deref()
+            .context("HD_MASTER_KEY is required in signer mode")?;
+        let collection_address = wallet.collection_address.as_deref()
+            .context("COLLECTION_ADDRESS is required in signer mode")?;
+        let chain_config = config.chain(chain)?;
+
+        let provider = Self::http_provider(&chain_config.rpc_url)?;
+        let mut manager = Self::new(
+            master_key,
+            wallet.hd_passphrase.as_deref(),
+            config.key_ring()?,
+            wallet.key_cache_size,
+            collection_address,
+            provider.clone(),
+            pool,
+            wallet.collection_threshold,
+        )?;
+
+        let gas_tank: LocalWallet = config.blockchain.ethereum.private_key.parse()
+            .context("Invalid ETHEREUM_PRIVATE_KEY for gas tank wallet")?;
+        log::info!("Gas tank wallet: {:?}", gas_tank.address());
+
+        manager.chain = *chain;
+        manager.gas_tank = Some(gas_tank);
+        manager.gas_oracle = GasOracle::new(provider, config.blockchain.ethereum.max_gas_price)?;
+        manager.tx_replace_after = chrono::Duration::minutes(wallet.collection_tx_timeout as i64);
+        manager.sweep_confirmations = chain_config.default_confirmations.max(1) as u64;
+        manager.token_collection_threshold = wallet.collection_token_threshold;
+
+        Ok(manager)
+    }
+
+    fn http_provider(rpc_url: &str) -> Result<Arc<Provider<Http>>> {
+        let provider = Provider::<Http>::try_from(rpc_url)
+            .context("Failed to create HTTP provider")?;
+
+        Ok(Arc::new(provider))
+    }
+
     /// 生成新的支付地址
     /// 
     /// 使用HD钱包派生路径: m/44'/60'/0'/0/{index}
-    pub async fn generate_payment_address(&self, payment_id: Uuid) -> Result<String> {
-        let mut index_guard = self.address_index.write().await;
-        let current_index = *index_guard;
-        *index_guard += 1;
-        drop(index_guard);
-
-        // 在实际应用中，这里应该使用HD钱包派生
-        // 为了演示，我们使用确定性方法生成地址
-        let derived_key = self.derive_private_key(current_index)?;
-        let wallet = LocalWallet::from(derived_key);
-        let address = wallet.address();
-
-        // 缓存地址和私钥
-        {
-            let mut cache = self.address_cache.write().await;
-            cache.insert(address, wallet);
-        }
+    /// 观察模式下仅保存地址索引，私钥由签名器按索引重新派生
+    ///
+    /// # Arguments
+    /// * `tx` - 创建支付订单的数据库事务，地址与订单一同提交或回滚
+    /// * `payment_id` - 支付订单ID
+    ///
+    /// # Returns
+    /// * 支付地址
+    pub async fn generate_payment_address(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        payment_id: Uuid,
+    ) -> Result<String> {
+        let current_index = Self::allocate_address_index(tx).await?;
+        let address = derive_address_from_xpub(&self.account_xpub, current_index)?;
+
+        let address_str = format!("{:?}", address);
+
+        let (encrypted_key, key_version) = match &self.account_key {
+            Some(_) => {
+                let derived_key = self.derive_private_key(current_index)?;
+                let (key_version, encrypted) = self.encrypt_private_key(&address_str, &derived_key)?;
+
+                // 缓存地址和私钥
+                let mut cache = self.address_cache.lock().await;
+                cache.put(address, LocalWallet::from(derived_key));
+
+                (Some(encrypted), key_version)
+            },
+            None => (None, LEGACY_KEY_VERSION),
+        };
 
         // 保存地址信息到数据库
         sqlx::query!(
             r#"
             INSERT INTO payment_addresses (
                 id, payment_id, address_index, address, 
-                private_key_encrypted, created_at, updated_at
+                private_key_encrypted, key_version, created_at, updated_at
             )
-            VALUES ($1, $2, $3, $4, $5, NOW(), NOW())
+            VALUES ($1, $2, $3, $4, $5, $6, NOW(), NOW())
             "#,
             Uuid::new_v4(),
             payment_id,
             current_index as i32,
-            format!("{:?}", address),
-            self.encrypt_private_key(&derived_key)?, // 加密存储私钥
+            address_str,
+            encrypted_key, // 加密存储私钥 (观察模式下为NULL)
+            key_version,
         )
-        .execute(&self.pool)
+        .execute(&mut **tx)
         .await
         .context("Failed to save payment address")?;
 
-        log::info!("Generated payment address {} for payment {}", address, payment_id);
-        Ok(format!("{:?}", address))
+        log::info!("Generated payment address {:?} ({}) for payment {}",
+            address, derivation_path(current_index), payment_id);
+        Ok(address_str)
+    }
+
+    /// 分配下一个地址索引
+    ///
+    /// 计数器行在事务提交前保持锁定，多实例并发分配时串行化，
+    /// 事务回滚时索引同时回滚，保证索引连续且不重复
+    async fn allocate_address_index(tx: &mut Transaction<'_, Postgres>) -> Result<u32> {
+        let index = sqlx::query_scalar!(
+            r#"
+            UPDATE hd_address_counter
+            SET next_index = next_index + 1, updated_at = NOW()
+            RETURNING next_index - 1 as "index!"
+            "#
+        )
+        .fetch_one(&mut **tx)
+        .await
+        .context("Failed to allocate address index")?;
+
+        let index = u32::try_from(index)
+            .context("Invalid address index")?;
+        if index > crate::utils::MAX_ADDRESS_INDEX {
+            anyhow::bail!("HD address index space exhausted");
+        }
+
+        Ok(index)
     }
 
     /// 检查并执行资金归集
     /// 
     /// 扫描所有有余额的地址，如果余额超过阈值则归集到主地址
+    ///
+    /// 地址归集状态: active -> sweeping -> swept / sweep_failed，
+    /// 只有归集交易达到确认数后才标记为swept，失败的地址在下一轮重试
     pub async fn collect_funds(&self) -> Result<Vec<String>> {
+        if self.is_watch_only() {
+            anyhow::bail!("Fund collection requires the signer process (wallet is watch-only)");
+        }
+
+        // 加载最新登记的代币 (合约地址与精度)
+        self.token_registry.refresh().await?;
+
+        // 根据回执更新已广播归集交易的状态
+        if let Err(e) = self.track_pending_collections().await {
+            log::error!("Failed to track pending collection transactions: {}", e);
+        }
+
+        // 已归集地址收到迟到或重复付款时重新归集
+        if let Err(e) = self.rescan_swept_addresses().await {
+            log::error!("Failed to rescan swept addresses: {}", e);
+        }
+
+        // 网络费用超过上限时延迟本轮归集
+        let fees = match self.gas_oracle.suggest_fees().await? {
+            Some(fees) => fees,
+            None => {
+                log::warn!("Network fees exceed configured cap, deferring collection");
+                return Ok(Vec::new());
+            }
+        };
+
         let mut collected_txs = Vec::new();
 
         // 获取所有有余额的地址
@@ -116,16 +406,43 @@ impl WalletManager {
             let address: Address = address_info.address.parse()
                 .context("Invalid address format")?;
 
+            // 已退款的迟到付款转入退款地址，其余归集到主地址
+            let refund_address: Option<Address> = address_info.refund_address.as_deref()
+                .map(|refund_address| refund_address.parse().context("Invalid refund address"))
+                .transpose()?;
+
+            // 退款不受归集阈值限制，低于阈值的迟到付款也要全额退回
+            let is_refund = refund_address.is_some();
+            let is_native = address_info.currency.is_native_on(&self.chain);
+
+            // 先归集代币 (可能需要补充gas)，再归集剩余的ETH
+            if !is_native {
+                let destination = refund_address.unwrap_or(self.master_address);
+                match self.collect_token_from_address(address, address_info.address_index, destination, &address_info.currency, is_refund, &fees).await {
+                    Ok(Some(tx_hash)) => collected_txs.push(tx_hash),
+                    Ok(None) => {},
+                    Err(e) => {
+                        log::error!("Failed to collect {:?} from {}: {}", address_info.currency, address, e);
+                    }
+                }
+            }
+
             // 检查余额
             let balance = self.provider.get_balance(address, None).await
                 .context("Failed to get balance")?;
 
-            if balance > self.collection_threshold {
-                match self.collect_from_address(address, balance).await {
+            // 退款地址只接收退款币种，代币退款地址上剩余的ETH (如gas补充的余量) 归集到主地址
+            let refund_native = is_refund && is_native && !balance.is_zero();
+            let destination = match refund_address {
+                Some(refund_address) if is_native => refund_address,
+                _ => self.master_address,
+            };
+            if balance > self.collection_threshold || refund_native {
+                match self.collect_from_address(address, address_info.address_index, destination, balance, &fees).await {
                     Ok(tx_hash) => {
                         collected_txs.push(tx_hash);
-                        log::info!("Collected {} ETH from {} to master address", 
-                            ethers::utils::format_ether(balance), address);
+                        log::info!("Collected {} {:?} from {} to {:?}", 
+                            ethers::utils::format_ether(balance), self.chain.native_currency(), address, destination);
                     },
                     Err(e) => {
                         log::error!("Failed to collect from {}: {}", address, e);
@@ -137,19 +454,20 @@ impl WalletManager {
         Ok(collected_txs)
     }
 
-    /// 从指定地址归集资金到主地址
-    async fn collect_from_address(&self, from_address: Address, balance: U256) -> Result<String> {
-        // 从缓存获取私钥
-        let wallet = {
-            let cache = self.address_cache.read().await;
-            cache.get(&from_address).cloned()
-                .ok_or_else(|| anyhow::anyhow!("Private key not found for address"))?
-        };
+    /// 从指定地址归集资金到目标地址 (主地址或退款地址)
+    async fn collect_from_address(
+        &self,
+        from_address: Address,
+        address_index: i32,
+        to_address: Address,
+        balance: U256,
+        fees: &Eip1559Fees,
+    ) -> Result<String> {
+        let wallet = self.signing_wallet(from_address, address_index).await?;
 
-        // 估算gas费用
-        let gas_price = self.provider.get_gas_price().await?;
+        // 按最大费用预留gas，未用完的部分留在充值地址
         let gas_limit = U256::from(21000); // 标准ETH转账gas限制
-        let gas_cost = gas_price * gas_limit;
+        let gas_cost = fees.max_cost(gas_limit);
 
         // 确保余额足够支付gas费用
         if balance <= gas_cost {
@@ -159,33 +477,646 @@ impl WalletManager {
         let amount_to_send = balance - gas_cost;
 
         // 构建交易
-        let tx = TransactionRequest::new()
+        let tx = Eip1559TransactionRequest::new()
             .from(from_address)
-            .to(self.master_address)
+            .to(to_address)
             .value(amount_to_send)
-            .gas(gas_limit)
-            .gas_price(gas_price);
+            .gas(gas_limit);
 
         // 签名并发送交易
-        let signed_tx = wallet.sign_transaction(&tx).await?;
-        let tx_hash = self.provider.send_raw_transaction(signed_tx).await?;
+        let sent = self.send_transaction(&wallet, tx, fees).await?;
 
         // 记录归集交易
-        self.record_collection_transaction(from_address, amount_to_send, tx_hash).await?;
+        self.record_collection_transaction(
+            from_address,
+            to_address,
+            &self.chain.native_currency(),
+            CollectionTxType::Sweep,
+            amount_to_send,
+            &sent,
+        ).await?;
+
+        Ok(format!("{:?}", sent.tx_hash))
+    }
+
+    /// 从指定地址归集ERC20代币到目标地址 (主地址或退款地址)
+    ///
+    /// 充值地址通常没有ETH支付手续费，不足时先由gas钱包补充恰好够用的ETH，
+    /// 补充交易上链后在下一轮归集代币
+    ///
+    /// # Arguments
+    /// * `from_address` - 充值地址
+    /// * `address_index` - 充值地址的HD派生索引
+    /// * `to_address` - 目标地址 (主地址或退款地址)
+    /// * `currency` - 归集的代币
+    /// * `ignore_threshold` - 忽略归集阈值 (退款时退回全部余额)
+    /// * `fees` - 本轮归集使用的EIP-1559费用
+    ///
+    /// # Returns
+    /// * 归集交易哈希 (余额未达到阈值或等待gas补充时为None)
+    async fn collect_token_from_address(
+        &self,
+        from_address: Address,
+        address_index: i32,
+        to_address: Address,
+        currency: &Currency,
+        ignore_threshold: bool,
+        fees: &Eip1559Fees,
+    ) -> Result<Option<String>> {
+        let contract = self.token_contract(currency)?;
+        let token_balance = self.get_erc20_balance(contract, from_address).await?;
+
+        if token_balance.is_zero() || (!ignore_threshold && token_balance < self.token_threshold(currency)?) {
+            return Ok(None);
+        }
+
+        let wallet = self.signing_wallet(from_address, address_index).await?;
+
+        let tx = Eip1559TransactionRequest::new()
+            .from(from_address)
+            .to(contract)
+            .data(encode_erc20_transfer(to_address, token_balance));
+
+        // 估算gas并预留20%余量
+        let estimated_gas = self.provider.estimate_gas(&tx.clone().into(), None).await
+            .context("Failed to estimate token transfer gas")?;
+        let gas_limit = estimated_gas * 12 / 10;
+        let gas_cost = fees.max_cost(gas_limit);
+
+        // 补充的gas上链前不发送代币转账，避免等待阻塞其他地址的归集
+        if self.has_pending_top_up(from_address).await? {
+            log::debug!("Gas top-up to {:?} is still pending, deferring token sweep", from_address);
+            return Ok(None);
+        }
+
+        let eth_balance = self.provider.get_balance(from_address, None).await
+            .context("Failed to get balance")?;
+        if eth_balance < gas_cost {
+            let tx_hash = self.top_up_gas(from_address, gas_cost - eth_balance, fees).await?;
+            log::info!("Sent gas top-up {:?} to {:?}, token sweep resumes after it is mined", tx_hash, from_address);
+            return Ok(None);
+        }
+
+        let sent = self.send_transaction(&wallet, tx.gas(gas_limit), fees).await?;
+
+        self.record_collection_transaction(
+            from_address,
+            to_address,
+            currency,
+            CollectionTxType::Sweep,
+            token_balance,
+            &sent,
+        ).await?;
+
+        log::info!("Collected {} {:?} from {} to {:?}",
+            format_units(token_balance, self.token_decimals(currency)?)?, currency, from_address, to_address);
+
+        Ok(Some(format!("{:?}", sent.tx_hash)))
+    }
+
+    /// 从gas钱包向充值地址补充ETH
+    ///
+    /// 只广播并记录交易，不等待上链；上链结果由 `track_pending_collections` 跟踪，
+    /// 超时未上链时同样按费用替换
+    async fn top_up_gas(&self, to: Address, amount: U256, fees: &Eip1559Fees) -> Result<H256> {
+        let gas_tank = self.gas_tank.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Gas tank wallet is not configured"))?;
+
+        let tx = Eip1559TransactionRequest::new()
+            .from(gas_tank.address())
+            .to(to)
+            .value(amount)
+            .gas(21000);
+
+        let sent = self.send_transaction(gas_tank, tx, fees).await?;
+        let tx_hash = sent.tx_hash;
+
+        self.record_collection_transaction(
+            gas_tank.address(),
+            to,
+            &self.chain.native_currency(),
+            CollectionTxType::GasTopUp,
+            amount,
+            &sent,
+        ).await?;
+
+        log::info!("Topping up {} {:?} gas to {:?}", ethers::utils::format_ether(amount), self.chain.native_currency(), to);
+        Ok(tx_hash)
+    }
+
+    /// 检查是否有发往该地址且尚未上链的gas补充交易
+    async fn has_pending_top_up(&self, address: Address) -> Result<bool> {
+        let pending = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM collection_transactions
+                WHERE to_address = $1 AND tx_type = $2 AND status = 'pending' AND chain = $3
+            ) as "exists!"
+            "#,
+            format!("{:?}", address),
+            CollectionTxType::GasTopUp.as_str(),
+            self.chain.as_str()
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to check pending gas top-up")?;
+
+        Ok(pending)
+    }
+
+    /// 签名并广播EIP-1559交易
+    ///
+    /// nonce由nonce管理器分配，发送失败时释放该地址的本地nonce
+    async fn send_transaction(
+        &self,
+        wallet: &LocalWallet,
+        tx: Eip1559TransactionRequest,
+        fees: &Eip1559Fees,
+    ) -> Result<SentTransaction> {
+        let nonce = self.nonce_manager.next_nonce(wallet.address()).await?;
+
+        match self.sign_and_send(wallet, tx, fees, nonce).await {
+            Ok(sent) => Ok(sent),
+            Err(e) => {
+                self.nonce_manager.reset(wallet.address()).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// 使用指定nonce签名并广播交易
+    async fn sign_and_send(
+        &self,
+        wallet: &LocalWallet,
+        tx: Eip1559TransactionRequest,
+        fees: &Eip1559Fees,
+        nonce: U256,
+    ) -> Result<SentTransaction> {
+        let chain_id = self.provider.get_chainid().await
+            .context("Failed to get chain ID")?
+            .as_u64();
+
+        let tx = tx
+            .chain_id(chain_id)
+            .nonce(nonce)
+            .max_fee_per_gas(fees.max_fee_per_gas)
+            .max_priority_fee_per_gas(fees.max_priority_fee_per_gas);
+        let typed: TypedTransaction = tx.clone().into();
+
+        let signature = wallet.clone().with_chain_id(chain_id).sign_transaction(&typed).await
+            .context("Failed to sign transaction")?;
+
+        let pending = self.provider.send_raw_transaction(typed.rlp_signed(&signature)).await
+            .context("Failed to send transaction")?;
+
+        Ok(SentTransaction {
+            tx_hash: pending.tx_hash(),
+            nonce,
+            fees: *fees,
+            request: tx,
+        })
+    }
+
+    /// 跟踪待确认的归集交易
+    ///
+    /// * 已上链: 根据回执更新状态和实际手续费
+    /// * nonce已被同地址的其他交易使用: 标记为dropped
+    /// * 超时未上链: 使用相同nonce提高费用重新发送 (replace-by-fee)
+    async fn track_pending_collections(&self) -> Result<()> {
+        let pending = sqlx::query_as!(
+            PendingCollectionTx,
+            r#"
+            SELECT tx_hash, from_address, to_address, amount, currency as "currency: Currency",
+                   tx_type, nonce, gas_limit, max_fee_per_gas, max_priority_fee_per_gas,
+                   tx_to, tx_value, tx_data, created_at as "created_at!"
+            FROM collection_transactions
+            WHERE status = 'pending' AND chain = $1
+            ORDER BY created_at ASC
+            "#,
+            self.chain.as_str()
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch pending collection transactions")?;
+
+        for tx in pending {
+            let hash: H256 = tx.tx_hash.parse()
+                .context("Invalid transaction hash")?;
+
+            if let Some(receipt) = self.provider.get_transaction_receipt(hash).await? {
+                // 成功的归集交易需达到确认数，避免区块重组后地址被误标记为已归集
+                if receipt.status == Some(1u64.into()) && tx.tx_type == CollectionTxType::Sweep.as_str() {
+                    let current_block = self.provider.get_block_number().await?;
+                    let confirmations = receipt.block_number
+                        .map_or(0, |block| current_block.saturating_sub(block).as_u64() + 1);
+
+                    if confirmations < self.sweep_confirmations {
+                        continue;
+                    }
+                }
+
+                self.record_transaction_receipt(&receipt).await?;
+                continue;
+            }
+
+            // 早期记录没有nonce信息，只能等待上链
+            let Some(nonce) = tx.nonce else { continue };
+
+            let from_address: Address = tx.from_address.parse()
+                .context("Invalid address format")?;
+            let confirmed_nonce = self.provider.get_transaction_count(from_address, None).await
+                .context("Failed to get nonce")?;
+
+            if confirmed_nonce > U256::from(nonce) {
+                self.resolve_used_nonce(&tx, from_address, nonce).await?;
+                continue;
+            }
+
+            if Utc::now() - tx.created_at > self.tx_replace_after {
+                match self.replace_transaction(&tx, from_address, nonce).await {
+                    Ok(replacement) => {
+                        log::warn!("Replaced stuck collection transaction {} with {:?}", tx.tx_hash, replacement);
+                    },
+                    Err(e) => {
+                        log::error!("Failed to replace stuck collection transaction {}: {}", tx.tx_hash, e);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 处理nonce已被使用但本交易无回执的情况
+    ///
+    /// 已上链的可能是之前被替换的同nonce交易，查到回执时按其结果记录，
+    /// 否则该笔归集视为失败
+    async fn resolve_used_nonce(&self, tx: &PendingCollectionTx, from_address: Address, nonce: i64) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE collection_transactions
+            SET status = 'dropped', updated_at = NOW()
+            WHERE tx_hash = $1 AND status = 'pending'
+            "#,
+            tx.tx_hash
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to mark collection transaction dropped")?;
+
+        let replaced = sqlx::query_scalar!(
+            r#"
+            SELECT tx_hash
+            FROM collection_transactions
+            WHERE from_address = $1 AND nonce = $2 AND status = 'replaced'
+            "#,
+            format!("{:?}", from_address),
+            nonce
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch replaced collection transactions")?;
+
+        let mut resolved = false;
+        for tx_hash in replaced {
+            let hash: H256 = tx_hash.parse()
+                .context("Invalid transaction hash")?;
+
+            if let Some(receipt) = self.provider.get_transaction_receipt(hash).await? {
+                self.record_transaction_receipt(&receipt).await?;
+                resolved = true;
+            }
+        }
+
+        if !resolved && tx.tx_type == CollectionTxType::Sweep.as_str() {
+            self.mark_sweep_result(&tx.from_address, false).await?;
+        }
+
+        log::warn!("Collection transaction {} dropped, nonce {} of {:?} already used",
+            tx.tx_hash, nonce, from_address);
+        Ok(())
+    }
+
+    /// 提高费用替换超时未上链的交易
+    ///
+    /// # Returns
+    /// * 替换交易哈希
+    async fn replace_transaction(&self, tx: &PendingCollectionTx, from_address: Address, nonce: i64) -> Result<H256> {
+        let previous = Eip1559Fees {
+            base_fee_per_gas: U256::zero(),
+            max_fee_per_gas: parse_stored_u256(&tx.max_fee_per_gas)?,
+            max_priority_fee_per_gas: parse_stored_u256(&tx.max_priority_fee_per_gas)?,
+        };
+        let suggested = self.gas_oracle.suggest_fees().await?;
+        let fees = bump_fees(&previous, suggested.as_ref());
+
+        if fees.max_fee_per_gas > self.gas_oracle.max_fee_cap() {
+            anyhow::bail!("Replacement fee exceeds configured cap");
+        }
+
+        let gas_limit = U256::from(tx.gas_limit.context("Missing gas limit")?);
+        let tx_to: Address = tx.tx_to.as_deref().context("Missing transaction recipient")?.parse()
+            .context("Invalid address format")?;
+        let data = hex::decode(tx.tx_data.as_deref().unwrap_or_default().trim_start_matches("0x"))
+            .context("Invalid transaction data")?;
+        let mut value = parse_stored_u256(&tx.tx_value)?;
+
+        // ETH归集从转账金额中扣除增加的手续费，保证余额足够
+        let tx_type: CollectionTxType = tx.tx_type.parse()?;
+        if matches!(tx_type, CollectionTxType::Sweep) && tx.currency.is_native_on(&self.chain) {
+            let extra_cost = fees.max_cost(gas_limit) - previous.max_cost(gas_limit);
+            value = value.checked_sub(extra_cost)
+                .ok_or_else(|| anyhow::anyhow!("Balance cannot cover replacement fee"))?;
+        }
+
+        let wallet = self.wallet_for(from_address).await?;
+        let request = Eip1559TransactionRequest::new()
+            .from(from_address)
+            .to(tx_to)
+            .value(value)
+            .gas(gas_limit)
+            .data(data);
+
+        let sent = self.sign_and_send(&wallet, request, &fees, U256::from(nonce)).await?;
+
+        let amount = if tx.currency.is_native_on(&self.chain) {
+            value
+        } else {
+            parse_units(tx.amount.to_string(), self.token_decimals(&tx.currency)?)?.into()
+        };
+        let to_address: Address = tx.to_address.parse()
+            .context("Invalid address format")?;
+
+        self.record_collection_transaction(from_address, to_address, &tx.currency, tx_type, amount, &sent).await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE collection_transactions
+            SET status = 'replaced', replaced_by = $1, updated_at = NOW()
+            WHERE tx_hash = $2
+            "#,
+            format!("{:?}", sent.tx_hash),
+            tx.tx_hash
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to mark collection transaction replaced")?;
+
+        Ok(sent.tx_hash)
+    }
+
+    /// 获取发送地址对应的签名钱包 (gas钱包或充值地址)
+    async fn wallet_for(&self, address: Address) -> Result<LocalWallet> {
+        if let Some(gas_tank) = self.gas_tank.as_ref().filter(|wallet| wallet.address() == address) {
+            return Ok(gas_tank.clone());
+        }
+
+        let address_index = sqlx::query_scalar!(
+            "SELECT address_index FROM payment_addresses WHERE address = $1",
+            format!("{:?}", address)
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to fetch payment address")?
+        .ok_or_else(|| anyhow::anyhow!("Unknown sender address {:?}", address))?;
+
+        self.signing_wallet(address, address_index).await
+    }
 
-        Ok(format!("{:?}", tx_hash))
+    /// 根据交易回执记录状态、实际消耗的gas和有效gas价格，并推进地址归集状态
+    async fn record_transaction_receipt(&self, receipt: &TransactionReceipt) -> Result<()> {
+        let success = receipt.status == Some(1u64.into());
+        let status = if success { "confirmed" } else { "failed" };
+        let gas_used = receipt.gas_used.map(|gas| gas.as_u64() as i64);
+        let gas_price = receipt.effective_gas_price.map(|price| price.to_string());
+
+        let updated = sqlx::query!(
+            r#"
+            UPDATE collection_transactions
+            SET status = $1, gas_used = $2, gas_price = $3, updated_at = NOW()
+            WHERE tx_hash = $4
+            RETURNING from_address, tx_type
+            "#,
+            status,
+            gas_used,
+            gas_price,
+            format!("{:?}", receipt.transaction_hash),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to record collection transaction receipt")?;
+
+        if let Some(row) = updated {
+            if row.tx_type == CollectionTxType::Sweep.as_str() {
+                self.mark_sweep_result(&row.from_address, success).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 根据归集交易结果更新地址归集状态
+    ///
+    /// * 成功: 该地址没有其他待确认的归集交易时，sweeping -> swept
+    /// * 失败: 标记为sweep_failed，下一轮重新归集
+    async fn mark_sweep_result(&self, address: &str, success: bool) -> Result<()> {
+        if success {
+            sqlx::query!(
+                r#"
+                UPDATE payment_addresses
+                SET collection_status = 'swept', is_collected = true, swept_at = NOW(), updated_at = NOW()
+                WHERE address = $1 AND collection_status = 'sweeping'
+                  AND NOT EXISTS (
+                      SELECT 1 FROM collection_transactions
+                      WHERE from_address = $1 AND tx_type = 'sweep' AND status = 'pending'
+                  )
+                "#,
+                address
+            )
+            .execute(&self.pool)
+            .await
+            .context("Failed to mark address swept")?;
+        } else {
+            sqlx::query!(
+                r#"
+                UPDATE payment_addresses
+                SET collection_status = 'sweep_failed', is_collected = false, updated_at = NOW()
+                WHERE address = $1
+                "#,
+                address
+            )
+            .execute(&self.pool)
+            .await
+            .context("Failed to mark address sweep failed")?;
+
+            log::warn!("Sweep of {} failed, will retry in next collection cycle", address);
+        }
+
+        Ok(())
+    }
+
+    /// 重新扫描已归集地址
+    ///
+    /// 归集完成后又收到的迟到或重复付款需要再次归集，
+    /// 余额超过阈值的地址重新置为active。每轮按上次扫描时间处理一批
+    async fn rescan_swept_addresses(&self) -> Result<()> {
+        let addresses = sqlx::query_as!(
+            PaymentAddressInfo,
+            r#"
+            SELECT pa.address, pa.address_index, p.currency as "currency: Currency",
+                   p.refund_address, pa.created_at
+            FROM payment_addresses pa
+            JOIN payments p ON p.id = pa.payment_id
+            WHERE pa.collection_status = 'swept'
+              AND p.chain = $1
+            ORDER BY pa.last_scanned_at ASC NULLS FIRST
+            LIMIT $2
+            "#,
+            self.chain as Chain,
+            RESCAN_BATCH_SIZE
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch swept addresses")?;
+
+        for address_info in addresses {
+            let address: Address = address_info.address.parse()
+                .context("Invalid address format")?;
+
+            let mut funded = self.provider.get_balance(address, None).await
+                .context("Failed to get balance")? > self.collection_threshold;
+
+            if !funded && !address_info.currency.is_native_on(&self.chain) {
+                let contract = self.token_contract(&address_info.currency)?;
+                let token_balance = self.get_erc20_balance(contract, address).await?;
+                funded = !token_balance.is_zero() && token_balance >= self.token_threshold(&address_info.currency)?;
+            }
+
+            sqlx::query!(
+                r#"
+                UPDATE payment_addresses
+                SET last_scanned_at = NOW(),
+                    collection_status = CASE WHEN $2 THEN 'active' ELSE collection_status END,
+                    is_collected = CASE WHEN $2 THEN false ELSE is_collected END,
+                    updated_at = NOW()
+                WHERE address = $1 AND collection_status = 'swept'
+                "#,
+                address_info.address,
+                funded
+            )
+            .execute(&self.pool)
+            .await
+            .context("Failed to update rescanned address")?;
+
+            if funded {
+                log::info!("Swept address {} received new funds, queued for collection", address_info.address);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 代币归集阈值 (最小单位)
+    fn token_threshold(&self, currency: &Currency) -> Result<U256> {
+        Ok(parse_units(self.token_collection_threshold, self.token_decimals(currency)?)?.into())
+    }
+
+    /// 获取代币在归集链上的合约地址
+    fn token_contract(&self, currency: &Currency) -> Result<Address> {
+        self.token_registry.get(&self.chain, currency)?
+            .contract_address
+            .ok_or_else(|| anyhow::anyhow!("No contract address for currency {:?} on {}", currency, self.chain))?
+            .parse()
+            .context("Invalid contract address")
+    }
+
+    /// 获取币种在归集链上的精度
+    fn token_decimals(&self, currency: &Currency) -> Result<u32> {
+        if currency.is_native_on(&self.chain) {
+            return Ok(NATIVE_DECIMALS);
+        }
+        Ok(self.token_registry.get(&self.chain, currency)?.decimals())
+    }
+
+    /// 查询ERC20代币余额
+    async fn get_erc20_balance(&self, contract: Address, owner: Address) -> Result<U256> {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(contract)
+            .data(encode_erc20_balance_of(owner))
+            .into();
+
+        let result = self.provider.call(&tx, None).await
+            .context("Failed to call balanceOf")?;
+
+        Ok(U256::from_big_endian(&result))
+    }
+
+    /// 获取地址对应的签名钱包
+    ///
+    /// 优先读取LRU缓存，未命中时从 `payment_addresses` 加载并解密私钥；
+    /// 观察模式生成的地址没有存储私钥，按地址索引重新派生
+    async fn signing_wallet(&self, address: Address, address_index: i32) -> Result<LocalWallet> {
+        if let Some(wallet) = self.address_cache.lock().await.get(&address).cloned() {
+            return Ok(wallet);
+        }
+
+        let wallet = LocalWallet::from(self.load_private_key(address, address_index).await?);
+        self.address_cache.lock().await.put(address, wallet.clone());
+
+        Ok(wallet)
+    }
+
+    /// 从数据库加载地址私钥
+    async fn load_private_key(&self, address: Address, address_index: i32) -> Result<SigningKey> {
+        let address_str = format!("{:?}", address);
+
+        let stored = sqlx::query!(
+            r#"
+            SELECT private_key_encrypted, key_version
+            FROM payment_addresses
+            WHERE address = $1
+            "#,
+            address_str
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to load payment address key")?;
+
+        if let Some(row) = stored {
+            if let Some(encrypted) = row.private_key_encrypted {
+                return self.decrypt_private_key(&address_str, &encrypted, row.key_version);
+            }
+        }
+
+        let index = u32::try_from(address_index)
+            .context("Invalid address index")?;
+        let private_key = self.derive_private_key(index)?;
+
+        if signing_key_address(&private_key) != address {
+            anyhow::bail!("Derived key at {} does not match address {:?}",
+                derivation_path(index), address);
+        }
+
+        Ok(private_key)
     }
 
     /// 获取有资金的地址列表
+    ///
+    /// 等待商户处理的迟到付款暂不归集
     async fn get_funded_addresses(&self) -> Result<Vec<PaymentAddressInfo>> {
         let addresses = sqlx::query_as!(
             PaymentAddressInfo,
             r#"
-            SELECT address, address_index, created_at
-            FROM payment_addresses 
-            WHERE is_collected = false
-            ORDER BY created_at ASC
-            "#
+            SELECT pa.address, pa.address_index, p.currency as "currency: Currency",
+                   p.refund_address, pa.created_at
+            FROM payment_addresses pa
+            JOIN payments p ON p.id = pa.payment_id
+            WHERE pa.collection_status IN ('active', 'sweep_failed')
+              AND p.status != 'paid_after_expiry'
+              AND p.chain = $1
+            ORDER BY pa.created_at ASC
+            "#,
+            self.chain as Chain
         )
         .fetch_all(&self.pool)
         .await
@@ -195,34 +1126,61 @@ impl WalletManager {
     }
 
     /// 记录归集交易
+    ///
+    /// # Arguments
+    /// * `amount` - 转账数量 (最小单位，按币种精度换算后存储)
+    /// * `sent` - 已广播的交易 (保存nonce和交易参数用于替换)
     async fn record_collection_transaction(
         &self,
         from_address: Address,
+        to_address: Address,
+        currency: &Currency,
+        tx_type: CollectionTxType,
         amount: U256,
-        tx_hash: H256,
+        sent: &SentTransaction,
     ) -> Result<()> {
+        let amount = Decimal::from_str(&format_units(amount, self.token_decimals(currency)?)?)
+            .context("Invalid collection amount")?;
+        let request = &sent.request;
+
         sqlx::query!(
             r#"
             INSERT INTO collection_transactions (
-                id, from_address, to_address, amount, tx_hash, created_at
+                id, chain, from_address, to_address, amount, currency, tx_type, tx_hash,
+                nonce, gas_limit, max_fee_per_gas, max_priority_fee_per_gas,
+                tx_to, tx_value, tx_data, created_at
             )
-            VALUES ($1, $2, $3, $4, $5, NOW())
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, NOW())
             "#,
             Uuid::new_v4(),
+            self.chain.as_str(),
             format!("{:?}", from_address),
-            format!("{:?}", self.master_address),
-            amount.to_string(),
-            format!("{:?}", tx_hash),
+            format!("{:?}", to_address),
+            amount,
+            currency.clone() as Currency,
+            tx_type.as_str(),
+            format!("{:?}", sent.tx_hash),
+            sent.nonce.as_u64() as i64,
+            request.gas.map(|gas| gas.as_u64() as i64),
+            sent.fees.max_fee_per_gas.to_string(),
+            sent.fees.max_priority_fee_per_gas.to_string(),
+            request.to.as_ref().and_then(|to| to.as_address()).map(|to| format!("{:?}", to)),
+            request.value.unwrap_or_default().to_string(),
+            request.data.as_ref().map(|data| hex::encode(data)),
         )
         .execute(&self.pool)
         .await
         .context("Failed to record collection transaction")?;
 
-        // 标记地址为已归集
+        if !matches!(tx_type, CollectionTxType::Sweep) {
+            return Ok(());
+        }
+
+        // 归集交易已广播，等待确认后才标记为已归集
         sqlx::query!(
             r#"
             UPDATE payment_addresses 
-            SET is_collected = true, updated_at = NOW()
+            SET collection_status = 'sweeping', updated_at = NOW()
             WHERE address = $1
             "#,
             format!("{:?}", from_address)
@@ -234,33 +1192,120 @@ impl WalletManager {
         Ok(())
     }
 
-    /// 派生私钥（简化版本，实际应使用BIP32）
-    fn derive_private_key(&self, index: u32) -> Result<k256::SecretKey> {
-        // 这里应该使用真正的BIP32 HD钱包派生
-        // 为了演示，使用简化的确定性生成
-        use k256::elliptic_curve::rand_core::{RngCore, SeedableRng};
-        use rand_chacha::ChaCha20Rng;
-        
-        let master_key = self.master_wallet.signer().to_bytes();
-        let mut seed = [0u8; 32];
-        seed[..master_key.len()].copy_from_slice(&master_key);
-        
-        // 使用index作为额外熵
-        seed[28..32].copy_from_slice(&index.to_be_bytes());
-        
-        let mut rng = ChaCha20Rng::from_seed(seed);
-        let mut key_bytes = [0u8; 32];
-        rng.fill_bytes(&mut key_bytes);
-        
-        k256::SecretKey::from_bytes(&key_bytes.into())
-            .map_err(|e| anyhow::anyhow!("Failed to create secret key: {}", e))
+    /// 派生私钥
+    ///
+    /// 按BIP44路径 m/44'/60'/0'/0/{index} 派生，可在任意标准钱包中通过助记词恢复
+    fn derive_private_key(&self, index: u32) -> Result<SigningKey> {
+        let account_key = self.account_key.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Private key derivation unavailable in watch-only mode"))?;
+
+        derive_signing_key(account_key, index)
     }
 
     /// 加密私钥存储
-    fn encrypt_private_key(&self, private_key: &k256::SecretKey) -> Result<String> {
-        // 实际应用中应使用AES加密
-        // 这里为了演示使用简单的hex编码
-        Ok(hex::encode(private_key.to_bytes()))
+    ///
+    /// 使用当前版本的KEK进行AES-256-GCM加密，地址作为附加认证数据，
+    /// 防止密文被复制到其他地址记录
+    ///
+    /// # Returns
+    /// * (密钥版本, 密文)
+    fn encrypt_private_key(&self, address: &str, private_key: &SigningKey) -> Result<(i32, String)> {
+        let key_ring = self.key_ring()?;
+        key_ring.encrypt(&private_key.to_bytes(), address.as_bytes())
+    }
+
+    /// 解密私钥
+    ///
+    /// 支持任意仍在密钥环中的版本，以及早期未加密的十六进制私钥 (版本0)
+    fn decrypt_private_key(&self, address: &str, encrypted: &str, key_version: i32) -> Result<SigningKey> {
+        let key_bytes = if key_version == LEGACY_KEY_VERSION {
+            hex::decode(encrypted).context("Invalid legacy private key encoding")?
+        } else {
+            self.key_ring()?.decrypt(key_version, encrypted, address.as_bytes())?
+        };
+
+        let private_key = SigningKey::from_slice(&key_bytes)
+            .map_err(|e| anyhow::anyhow!("Invalid private key bytes: {}", e))?;
+
+        if !format!("{:?}", signing_key_address(&private_key)).eq_ignore_ascii_case(address) {
+            anyhow::bail!("Decrypted private key does not match address {}", address);
+        }
+
+        Ok(private_key)
+    }
+
+    fn key_ring(&self) -> Result<&KeyRing> {
+        self.key_ring.as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Private key encryption unavailable in watch-only mode"))
+    }
+
+    /// 使用当前KEK版本重新加密所有私钥
+    ///
+    /// 按批次处理，每批在独立事务中使用 `FOR UPDATE SKIP LOCKED` 锁定记录，
+    /// 轮换期间新旧版本的密钥同时存在于密钥环中，服务无需停机。
+    /// 所有记录轮换完成后即可从 `WALLET_KEKS` 中移除旧版本密钥
+    ///
+    /// # Arguments
+    /// * `batch_size` - 每批处理的记录数
+    ///
+    /// # Returns
+    /// * 重新加密的记录数
+    pub async fn rotate_encryption_keys(&self, batch_size: i64) -> Result<u64> {
+        let current_version = self.key_ring()?.current_version();
+        let mut rotated = 0u64;
+
+        loop {
+            let mut tx = self.pool.begin().await
+                .context("Failed to begin key rotation transaction")?;
+
+            let rows = sqlx::query!(
+                r#"
+                SELECT id, address, private_key_encrypted as "private_key_encrypted!", key_version
+                FROM payment_addresses
+                WHERE private_key_encrypted IS NOT NULL AND key_version <> $1
+                ORDER BY created_at ASC
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+                "#,
+                current_version,
+                batch_size
+            )
+            .fetch_all(&mut *tx)
+            .await
+            .context("Failed to fetch payment addresses for key rotation")?;
+
+            if rows.is_empty() {
+                break;
+            }
+
+            for row in &rows {
+                let private_key = self.decrypt_private_key(&row.address, &row.private_key_encrypted, row.key_version)
+                    .with_context(|| format!("Failed to decrypt private key of {}", row.address))?;
+                let (key_version, encrypted) = self.encrypt_private_key(&row.address, &private_key)?;
+
+                sqlx::query!(
+                    r#"
+                    UPDATE payment_addresses
+                    SET private_key_encrypted = $1, key_version = $2, updated_at = NOW()
+                    WHERE id = $3
+                    "#,
+                    encrypted,
+                    key_version,
+                    row.id
+                )
+                .execute(&mut *tx)
+                .await
+                .context("Failed to update re-encrypted private key")?;
+            }
+
+            tx.commit().await
+                .context("Failed to commit key rotation batch")?;
+
+            rotated += rows.len() as u64;
+            log::info!("Re-encrypted {} private keys to key version {}", rotated, current_version);
+        }
+
+        Ok(rotated)
     }
 
     /// 获取钱包统计信息
@@ -269,8 +1314,8 @@ impl WalletManager {
             r#"
             SELECT 
                 COUNT(*) as total_addresses,
-                COUNT(CASE WHEN is_collected = true THEN 1 END) as collected_addresses,
-                COUNT(CASE WHEN is_collected = false THEN 1 END) as active_addresses
+                COUNT(CASE WHEN collection_status = 'swept' THEN 1 END) as collected_addresses,
+                COUNT(CASE WHEN collection_status <> 'swept' THEN 1 END) as active_addresses
             FROM payment_addresses
             "#
         )
@@ -296,7 +1341,74 @@ impl WalletManager {
 struct PaymentAddressInfo {
     address: String,
     address_index: i32,
-    created_at: chrono::DateTime<chrono::Utc>,
+    currency: Currency,
+    /// 迟到付款的退款地址 (为空时归集到主地址)
+    refund_address: Option<String>,
+    created_at: chrono::DateTime<Utc>,
+}
+
+/// 归集交易类型
+#[derive(Debug, Clone, Copy)]
+enum CollectionTxType {
+    /// 充值地址归集到主地址
+    Sweep,
+    /// gas钱包向充值地址补充手续费
+    GasTopUp,
+}
+
+impl CollectionTxType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CollectionTxType::Sweep => "sweep",
+            CollectionTxType::GasTopUp => "gas_topup",
+        }
+    }
+}
+
+impl FromStr for CollectionTxType {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "sweep" => Ok(CollectionTxType::Sweep),
+            "gas_topup" => Ok(CollectionTxType::GasTopUp),
+            other => anyhow::bail!("Unknown collection transaction type: {}", other),
+        }
+    }
+}
+
+/// 已广播的交易
+#[derive(Debug)]
+struct SentTransaction {
+    tx_hash: H256,
+    nonce: U256,
+    fees: Eip1559Fees,
+    request: Eip1559TransactionRequest,
+}
+
+/// 待确认的归集交易
+#[derive(Debug)]
+struct PendingCollectionTx {
+    tx_hash: String,
+    from_address: String,
+    to_address: String,
+    amount: Decimal,
+    currency: Currency,
+    tx_type: String,
+    nonce: Option<i64>,
+    gas_limit: Option<i64>,
+    max_fee_per_gas: Option<String>,
+    max_priority_fee_per_gas: Option<String>,
+    tx_to: Option<String>,
+    tx_value: Option<String>,
+    tx_data: Option<String>,
+    created_at: chrono::DateTime<Utc>,
+}
+
+/// 解析以十进制字符串存储的数值
+fn parse_stored_u256(value: &Option<String>) -> Result<U256> {
+    let value = value.as_deref().context("Missing stored value")?;
+    U256::from_dec_str(value).context("Invalid stored value")
 }
 
 /// 钱包统计信息
@@ -9,7 +9,7 @@ use serde_json::json;
 use tokio::time::{sleep, Duration};
 use crate::models::{
     WebhookLog, WebhookEventType, WebhookStatus, PaymentWebhookPayload,
-    MerchantWebhookPayload, WebhookRequest, WebhookResponse
+    MerchantWebhookPayload, WebhookRequest, WebhookResponse, PaymentStatus, Chain, Currency
 };
 use crate::utils::{generate_webhook_signature, verify_webhook_signature};
 
@@ -68,7 +68,7 @@ impl WebhookService {
         payload: PaymentWebhookPayload,
     ) -> Result<()> {
         let webhook_id = Uuid::new_v4();
-        let event_type = WebhookEventType::PaymentStatusChanged;
+        let event_type = WebhookEventType::from(payload.status.clone());
 
         // 记录Webhook日志
         self.create_webhook_log(
@@ -95,6 +95,55 @@ impl WebhookService {
         ).await
     }
 
+    /// 按支付订单当前状态发送通知
+    ///
+    /// 商户未配置Webhook URL时跳过
+    ///
+    /// # Arguments
+    /// * `payment_id` - 支付订单ID
+    pub async fn notify_payment_status(&self, payment_id: Uuid) -> Result<()> {
+        let payment = sqlx::query!(
+            r#"
+            SELECT p.merchant_id, p.order_id, p.amount, p.amount_received,
+                   p.chain as "chain: Chain", p.currency as "currency: Currency",
+                   p.status as "status: PaymentStatus", p.transaction_hash, p.confirmations,
+                   m.webhook_url, m.api_secret
+            FROM payments p
+            JOIN merchants m ON m.id = p.merchant_id
+            WHERE p.id = $1
+            "#,
+            payment_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to fetch payment for notification")?
+        .ok_or_else(|| anyhow::anyhow!("Payment not found"))?;
+
+        let Some(webhook_url) = payment.webhook_url else {
+            return Ok(());
+        };
+
+        let payload = PaymentWebhookPayload {
+            payment_id,
+            order_id: payment.order_id,
+            status: payment.status,
+            amount: payment.amount,
+            amount_received: payment.amount_received,
+            chain: payment.chain,
+            currency: payment.currency,
+            transaction_hash: payment.transaction_hash,
+            confirmations: payment.confirmations,
+        };
+
+        self.send_payment_notification(
+            payment_id,
+            payment.merchant_id,
+            &webhook_url,
+            &payment.api_secret,
+            payload,
+        ).await
+    }
+
     /// 发送商户状态变更通知
     /// 
     /// # Arguments
@@ -634,6 +683,8 @@ mod tests {
             order_id: "TEST_ORDER".to_string(),
             status: PaymentStatus::Completed,
             amount: rust_decimal::Decimal::new(100, 2),
+            amount_received: rust_decimal::Decimal::new(100, 2),
+            chain: Chain::Ethereum,
             currency: Currency::ETH,
             transaction_hash: Some("0x123...".to_string()),
             confirmations: Some(12),
@@ -34,6 +34,27 @@ pub fn extract_api_key(req: &HttpRequest) -> ActixResult<String> {
     Err(ErrorUnauthorized("Missing or invalid API key"))
 }
 
+/// 验证管理接口密钥
+/// 
+/// # Arguments
+/// * `req` - HTTP请求对象 (密钥格式与商户API密钥相同)
+/// * `admin_api_key` - 配置的管理接口密钥 (未配置时拒绝所有请求)
+/// 
+/// # Returns
+/// * 密钥是否有效
+pub fn verify_admin_api_key(req: &HttpRequest, admin_api_key: Option<&str>) -> Result<()> {
+    let admin_api_key = admin_api_key
+        .ok_or_else(|| anyhow::anyhow!("Admin API is disabled"))?;
+    let api_key = extract_api_key(req)
+        .map_err(|_| anyhow::anyhow!("Missing or invalid API key"))?;
+
+    if !crate::utils::crypto::constant_time_eq(&api_key, admin_api_key) {
+        anyhow::bail!("Invalid admin API key");
+    }
+
+    Ok(())
+}
+
 /// 验证API密钥并返回商户信息
 /// 
 /// # Arguments
@@ -46,8 +67,9 @@ pub async fn verify_api_key(pool: &PgPool, api_key: &str) -> Result<Merchant> {
     let merchant = sqlx::query_as!(
         Merchant,
         r#"
-        SELECT id, name, email, api_key, api_secret, webhook_url,
-               status as "status: _", created_at, updated_at
+        SELECT id, name, email, api_key, api_secret, webhook_url, payout_address,
+               status as "status: _", payment_tolerance_type as "payment_tolerance_type: _",
+               payment_tolerance, created_at, updated_at
         FROM merchants 
         WHERE api_key = $1 AND status = 'active'
         "#,
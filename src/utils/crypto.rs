@@ -6,9 +6,14 @@ use sha2::Sha256;
 use rand::{distributions::Alphanumeric, Rng};
 use hex;
 use anyhow::{Result, Context};
+use aes_gcm::{Aes256Gcm, Key, Nonce, aead::{Aead, AeadCore, OsRng, Payload}};
+use std::collections::BTreeMap;
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// AES-GCM随机数长度 (字节)
+const AES_GCM_NONCE_LEN: usize = 12;
+
 /// 生成随机API密钥
 /// 
 /// # Arguments
@@ -38,6 +43,18 @@ pub fn generate_api_key_pair(key_length: usize, secret_length: usize) -> (String
     (api_key, api_secret)
 }
 
+/// 生成随机的256位合约链上订单号
+///
+/// 订单号不可预测，防止他人抢先以同一订单号调用合约占用订单
+///
+/// # Returns
+/// * 十进制表示的链上订单号
+pub fn generate_contract_order_id() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill(&mut bytes);
+    ethers::types::U256::from_big_endian(&bytes).to_string()
+}
+
 /// 生成HMAC-SHA256签名
 /// 
 /// # Arguments
@@ -79,7 +96,7 @@ pub fn verify_hmac_signature(message: &str, signature: &str, secret: &str) -> Re
 /// 
 /// # Returns
 /// * 字符串是否相等
-fn constant_time_eq(a: &str, b: &str) -> bool {
+pub(crate) fn constant_time_eq(a: &str, b: &str) -> bool {
     if a.len() != b.len() {
         return false;
     }
@@ -138,6 +155,120 @@ pub fn generate_secure_random_string(length: usize) -> String {
         .collect()
 }
 
+/// 密钥加密密钥 (KEK) 环
+///
+/// 按版本保存多个AES-256密钥，新数据始终使用当前版本加密，
+/// 旧版本密钥保留用于解密，直到所有数据轮换完成
+pub struct KeyRing {
+    keys: BTreeMap<i32, [u8; 32]>,
+    current_version: i32,
+}
+
+impl KeyRing {
+    /// 解析密钥环配置
+    ///
+    /// # Arguments
+    /// * `spec` - 逗号分隔的 `版本:十六进制密钥` 列表，如 `1:ab..,2:cd..`
+    /// * `current_version` - 当前加密版本 (可选，默认使用最大版本)
+    ///
+    /// # Returns
+    /// * 密钥环
+    pub fn parse(spec: &str, current_version: Option<i32>) -> Result<Self> {
+        let mut keys = BTreeMap::new();
+
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (version, key_hex) = entry.split_once(':')
+                .ok_or_else(|| anyhow::anyhow!("Key entry must be in 'version:hex' format"))?;
+
+            let version: i32 = version.trim().parse()
+                .context("Invalid key version")?;
+            if version <= 0 {
+                anyhow::bail!("Key version must be positive (0 is reserved for legacy plaintext)");
+            }
+
+            let key_bytes = hex::decode(key_hex.trim())
+                .context("Key must be hex encoded")?;
+            let key: [u8; 32] = key_bytes.try_into()
+                .map_err(|_| anyhow::anyhow!("Key version {} must be 32 bytes", version))?;
+
+            if keys.insert(version, key).is_some() {
+                anyhow::bail!("Duplicate key version {}", version);
+            }
+        }
+
+        let current_version = match current_version {
+            Some(version) => version,
+            None => *keys.keys().next_back()
+                .ok_or_else(|| anyhow::anyhow!("Key ring cannot be empty"))?,
+        };
+
+        if !keys.contains_key(&current_version) {
+            anyhow::bail!("Current key version {} not found in key ring", current_version);
+        }
+
+        Ok(Self { keys, current_version })
+    }
+
+    /// 当前加密版本
+    pub fn current_version(&self) -> i32 {
+        self.current_version
+    }
+
+    /// 使用当前版本密钥加密
+    ///
+    /// # Arguments
+    /// * `plaintext` - 明文
+    /// * `aad` - 附加认证数据 (绑定密文所属记录，防止密文被挪用)
+    ///
+    /// # Returns
+    /// * (密钥版本, 十六进制编码的 nonce || 密文)
+    pub fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<(i32, String)> {
+        let cipher = self.cipher(self.current_version)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+        let ciphertext = cipher.encrypt(&nonce, Payload { msg: plaintext, aad })
+            .map_err(|_| anyhow::anyhow!("AES-GCM encryption failed"))?;
+
+        let mut output = nonce.to_vec();
+        output.extend_from_slice(&ciphertext);
+
+        Ok((self.current_version, hex::encode(output)))
+    }
+
+    /// 使用指定版本密钥解密
+    ///
+    /// # Arguments
+    /// * `version` - 加密时使用的密钥版本
+    /// * `encoded` - 十六进制编码的 nonce || 密文
+    /// * `aad` - 附加认证数据
+    ///
+    /// # Returns
+    /// * 明文
+    pub fn decrypt(&self, version: i32, encoded: &str, aad: &[u8]) -> Result<Vec<u8>> {
+        let cipher = self.cipher(version)?;
+        let data = hex::decode(encoded)
+            .context("Ciphertext must be hex encoded")?;
+
+        if data.len() <= AES_GCM_NONCE_LEN {
+            anyhow::bail!("Ciphertext too short");
+        }
+
+        let (nonce, ciphertext) = data.split_at(AES_GCM_NONCE_LEN);
+        cipher.decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+            .map_err(|_| anyhow::anyhow!("AES-GCM decryption failed (wrong key or tampered data)"))
+    }
+
+    fn cipher(&self, version: i32) -> Result<Aes256Gcm> {
+        // 局部引入，避免与HMAC的 `Mac::new_from_slice` 冲突
+        use aes_gcm::KeyInit;
+
+        let key = self.keys.get(&version)
+            .ok_or_else(|| anyhow::anyhow!("Key version {} not found in key ring", version))?;
+
+        Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -157,6 +288,14 @@ mod tests {
         assert_ne!(api_key, api_secret);
     }
 
+    #[test]
+    fn test_generate_contract_order_id() {
+        let order_id = generate_contract_order_id();
+        assert!(order_id.len() <= 78 && order_id.chars().all(|c| c.is_ascii_digit()));
+        assert!(ethers::types::U256::from_dec_str(&order_id).is_ok());
+        assert_ne!(order_id, generate_contract_order_id());
+    }
+
     #[test]
     fn test_hmac_signature() {
         let message = "test message";
@@ -179,6 +318,50 @@ mod tests {
         assert!(!constant_time_eq("hello", "hello world"));
     }
 
+    const KEY_1: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+    const KEY_2: &str = "1f1e1d1c1b1a191817161514131211100f0e0d0c0b0a09080706050403020100";
+
+    #[test]
+    fn test_key_ring_round_trip() {
+        let ring = KeyRing::parse(&format!("1:{}", KEY_1), None).unwrap();
+        let (version, encrypted) = ring.encrypt(b"secret key", b"0xabc").unwrap();
+
+        assert_eq!(version, 1);
+        assert_ne!(encrypted, hex::encode(b"secret key"));
+        assert_eq!(ring.decrypt(version, &encrypted, b"0xabc").unwrap(), b"secret key");
+
+        // 错误的附加数据或被篡改的密文无法解密
+        assert!(ring.decrypt(version, &encrypted, b"0xdef").is_err());
+        let mut tampered = encrypted.clone();
+        tampered.replace_range(30..32, if &encrypted[30..32] == "00" { "ff" } else { "00" });
+        assert!(ring.decrypt(version, &tampered, b"0xabc").is_err());
+    }
+
+    #[test]
+    fn test_key_ring_rotation() {
+        let old_ring = KeyRing::parse(&format!("1:{}", KEY_1), None).unwrap();
+        let (old_version, encrypted) = old_ring.encrypt(b"secret key", b"aad").unwrap();
+
+        // 新密钥环包含新旧两个版本，默认使用最大版本
+        let ring = KeyRing::parse(&format!("1:{},2:{}", KEY_1, KEY_2), None).unwrap();
+        assert_eq!(ring.current_version(), 2);
+
+        let plaintext = ring.decrypt(old_version, &encrypted, b"aad").unwrap();
+        let (new_version, re_encrypted) = ring.encrypt(&plaintext, b"aad").unwrap();
+        assert_eq!(new_version, 2);
+        assert_eq!(ring.decrypt(new_version, &re_encrypted, b"aad").unwrap(), b"secret key");
+        assert!(ring.decrypt(1, &re_encrypted, b"aad").is_err());
+    }
+
+    #[test]
+    fn test_key_ring_parse_errors() {
+        assert!(KeyRing::parse("", None).is_err());
+        assert!(KeyRing::parse("1:abcd", None).is_err()); // 长度不足
+        assert!(KeyRing::parse(&format!("0:{}", KEY_1), None).is_err()); // 版本0保留
+        assert!(KeyRing::parse(&format!("1:{},1:{}", KEY_1, KEY_2), None).is_err());
+        assert!(KeyRing::parse(&format!("1:{}", KEY_1), Some(2)).is_err());
+    }
+
     #[test]
     fn test_webhook_signature() {
         let payload = r#"{"event":"payment.completed","payment_id":"123"}"#;
@@ -0,0 +1,440 @@
+// EIP-681支付链接
+// 构造与解析 `ethereum:` 支付URI，订单响应、二维码与收银台页面统一使用
+
+use ethers::abi::{encode, short_signature, param_type::Reader, ParamType, Token as AbiToken};
+use ethers::types::{Address, Bytes, U256};
+use ethers::utils::to_checksum;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+use anyhow::{Result, Context};
+use std::fmt;
+use std::str::FromStr;
+use crate::models::Token;
+use super::contract_merchant_id;
+
+/// 代币 `transfer` 调用的建议Gas上限
+pub const TOKEN_TRANSFER_GAS_LIMIT: u64 = 100_000;
+
+/// 合约 `receivePayment` 调用的建议Gas上限
+pub const RECEIVE_PAYMENT_GAS_LIMIT: u64 = 150_000;
+
+/// 合约收款模式的订单参数
+#[derive(Debug, Clone, Copy)]
+pub struct ContractOrderArgs<'a> {
+    /// 商户ID
+    pub merchant_id: Uuid,
+    /// 链上订单号 (十进制)
+    pub order_id: &'a str,
+    /// 服务端对订单参数的签名 (0x开头的十六进制)
+    pub signature: Option<&'a str>,
+}
+
+/// EIP-681支付URI
+///
+/// 格式: `ethereum:<地址>[@链ID][/函数名][?参数]`，
+/// 金额参数均为最小单位的整数 (wei或代币最小单位)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentUri {
+    /// 收款地址或被调用的合约地址
+    pub target: Address,
+    /// 链ID (缺省时钱包使用当前网络)
+    pub chain_id: Option<u64>,
+    /// 调用的合约函数
+    pub function: Option<String>,
+    /// 函数参数 (ABI类型, 值)，按调用顺序排列
+    pub args: Vec<(String, String)>,
+    /// 附带的原生币数量 (wei)
+    pub value: Option<U256>,
+    /// 建议Gas上限
+    pub gas_limit: Option<u64>,
+    /// 建议Gas价格 (wei)
+    pub gas_price: Option<U256>,
+}
+
+impl PaymentUri {
+    /// 原生币转账
+    pub fn native_transfer(recipient: Address, chain_id: u64, value: U256) -> Self {
+        Self {
+            target: recipient,
+            chain_id: Some(chain_id),
+            function: None,
+            args: Vec::new(),
+            value: Some(value),
+            gas_limit: None,
+            gas_price: None,
+        }
+    }
+
+    /// ERC-20代币转账: `transfer(address,uint256)`
+    pub fn token_transfer(token: Address, chain_id: u64, recipient: Address, amount: U256) -> Self {
+        Self {
+            target: token,
+            chain_id: Some(chain_id),
+            function: Some("transfer".to_string()),
+            args: vec![
+                ("address".to_string(), to_checksum(&recipient, None)),
+                ("uint256".to_string(), amount.to_string()),
+            ],
+            value: None,
+            gas_limit: None,
+            gas_price: None,
+        }
+    }
+
+    /// 合约收款: `receivePayment(merchantId, orderId, amount, signature)`，附带订单金额的原生币
+    pub fn contract_payment(contract: Address, chain_id: u64, merchant_id: U256, order_id: U256, amount: U256, signature: &Bytes) -> Self {
+        Self {
+            target: contract,
+            chain_id: Some(chain_id),
+            function: Some("receivePayment".to_string()),
+            args: vec![
+                ("uint256".to_string(), merchant_id.to_string()),
+                ("uint256".to_string(), order_id.to_string()),
+                ("uint256".to_string(), amount.to_string()),
+                ("bytes".to_string(), signature.to_string()),
+            ],
+            value: Some(amount),
+            gas_limit: None,
+            gas_price: None,
+        }
+    }
+
+    /// 构造订单的支付URI
+    ///
+    /// # Arguments
+    /// * `token` - 订单的收款代币
+    /// * `chain_id` - 收款链的链ID
+    /// * `payment_address` - 收款地址 (合约收款模式下为合约地址)
+    /// * `amount` - 支付金额
+    /// * `contract_order` - 合约收款模式下的订单参数
+    pub fn for_payment(
+        token: &Token,
+        chain_id: u64,
+        payment_address: &str,
+        amount: Decimal,
+        contract_order: Option<ContractOrderArgs<'_>>,
+    ) -> Result<Self> {
+        let target: Address = payment_address.parse()
+            .context("Invalid payment address")?;
+        let smallest_unit = token.to_smallest_unit(amount)
+            .context("Invalid payment amount")?;
+
+        if let Some(order) = contract_order {
+            let order_id = U256::from_dec_str(order.order_id).context("Invalid contract order ID")?;
+            let signature: Bytes = order.signature
+                .ok_or_else(|| anyhow::anyhow!("Contract order {} has no signature", order.order_id))?
+                .parse()
+                .context("Invalid contract order signature")?;
+            return Ok(Self::contract_payment(target, chain_id, contract_merchant_id(order.merchant_id), order_id, smallest_unit, &signature)
+                .with_gas_limit(RECEIVE_PAYMENT_GAS_LIMIT));
+        }
+
+        match &token.contract_address {
+            None => Ok(Self::native_transfer(target, chain_id, smallest_unit)),
+            Some(contract_address) => {
+                let contract: Address = contract_address.parse()
+                    .context("Invalid token contract address")?;
+                Ok(Self::token_transfer(contract, chain_id, target, smallest_unit)
+                    .with_gas_limit(TOKEN_TRANSFER_GAS_LIMIT))
+            }
+        }
+    }
+
+    /// 钱包交易的调用数据 (不调用合约函数时为空)
+    pub fn calldata(&self) -> Result<Bytes> {
+        let Some(function) = &self.function else {
+            return Ok(Bytes::default());
+        };
+
+        let mut params = Vec::with_capacity(self.args.len());
+        let mut tokens = Vec::with_capacity(self.args.len());
+        for (kind, value) in &self.args {
+            let param = Reader::read(kind)
+                .with_context(|| format!("Unsupported parameter type: {}", kind))?;
+            let token = match param {
+                ParamType::Address => AbiToken::Address(value.parse()
+                    .with_context(|| format!("Invalid address parameter: {}", value))?),
+                ParamType::Uint(_) => AbiToken::Uint(parse_number(value)?),
+                ParamType::Bool => AbiToken::Bool(value.parse()
+                    .with_context(|| format!("Invalid bool parameter: {}", value))?),
+                ParamType::Bytes => AbiToken::Bytes(value.parse::<Bytes>()
+                    .with_context(|| format!("Invalid bytes parameter: {}", value))?
+                    .to_vec()),
+                _ => anyhow::bail!("Unsupported parameter type: {}", kind),
+            };
+            params.push(param);
+            tokens.push(token);
+        }
+
+        let mut data = short_signature(function, &params).to_vec();
+        data.extend(encode(&tokens));
+        Ok(data.into())
+    }
+
+    /// 设置建议Gas上限
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+
+    /// 设置建议Gas价格
+    pub fn with_gas_price(mut self, gas_price: U256) -> Self {
+        self.gas_price = Some(gas_price);
+        self
+    }
+}
+
+impl fmt::Display for PaymentUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ethereum:{}", to_checksum(&self.target, None))?;
+
+        if let Some(chain_id) = self.chain_id {
+            write!(f, "@{}", chain_id)?;
+        }
+        if let Some(function) = &self.function {
+            write!(f, "/{}", function)?;
+        }
+
+        let mut params: Vec<(&str, String)> = self.args.iter()
+            .map(|(kind, value)| (kind.as_str(), value.clone()))
+            .collect();
+        if let Some(value) = self.value {
+            params.push(("value", value.to_string()));
+        }
+        if let Some(gas_limit) = self.gas_limit {
+            params.push(("gasLimit", gas_limit.to_string()));
+        }
+        if let Some(gas_price) = self.gas_price {
+            params.push(("gasPrice", gas_price.to_string()));
+        }
+
+        for (i, (key, value)) in params.iter().enumerate() {
+            write!(f, "{}{}={}", if i == 0 { "?" } else { "&" }, key, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for PaymentUri {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let rest = s.strip_prefix("ethereum:")
+            .ok_or_else(|| anyhow::anyhow!("Payment URI must start with ethereum:"))?;
+        let rest = rest.strip_prefix("pay-").unwrap_or(rest);
+
+        let (path, query) = match rest.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (rest, None),
+        };
+        let (target, function) = match path.split_once('/') {
+            Some((target, function)) => (target, Some(function)),
+            None => (path, None),
+        };
+        let (target, chain_id) = match target.split_once('@') {
+            Some((target, chain_id)) => (target, Some(chain_id)),
+            None => (target, None),
+        };
+
+        // 不支持ENS名称，目标须为0x开头的40位十六进制地址
+        if target.len() != 42 || !target.starts_with("0x") || !target[2..].chars().all(|c| c.is_ascii_hexdigit()) {
+            anyhow::bail!("Invalid target address: {}", target);
+        }
+
+        let mut uri = PaymentUri {
+            target: target.parse().context("Invalid target address")?,
+            chain_id: chain_id
+                .map(|id| id.parse::<u64>().with_context(|| format!("Invalid chain ID: {}", id)))
+                .transpose()?,
+            function: match function {
+                Some(name) if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => {
+                    anyhow::bail!("Invalid function name: {}", name);
+                },
+                name => name.map(str::to_string),
+            },
+            args: Vec::new(),
+            value: None,
+            gas_limit: None,
+            gas_price: None,
+        };
+
+        for param in query.into_iter().flat_map(|query| query.split('&')).filter(|p| !p.is_empty()) {
+            let (key, value) = param.split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid parameter: {}", param))?;
+
+            match key {
+                "value" => uri.value = Some(parse_number(value)?),
+                "gas" | "gasLimit" => {
+                    let gas_limit = parse_number(value)?;
+                    if gas_limit > U256::from(u64::MAX) {
+                        anyhow::bail!("Gas limit out of range: {}", value);
+                    }
+                    uri.gas_limit = Some(gas_limit.as_u64());
+                },
+                "gasPrice" => uri.gas_price = Some(parse_number(value)?),
+                kind if !kind.is_empty() && kind.chars().all(|c| c.is_ascii_alphanumeric() || c == '[' || c == ']') => {
+                    uri.args.push((kind.to_string(), value.to_string()));
+                },
+                _ => anyhow::bail!("Invalid parameter: {}", param),
+            }
+        }
+
+        Ok(uri)
+    }
+}
+
+/// 解析EIP-681数值，支持科学计数法 (如 `2.014e18`)
+fn parse_number(value: &str) -> Result<U256> {
+    let invalid = || anyhow::anyhow!("Invalid number: {}", value);
+
+    let (mantissa, exponent) = match value.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<u32>().map_err(|_| invalid())?),
+        None => (value, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    if integer.is_empty()
+        || !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit())
+        || fraction.len() as u32 > exponent
+    {
+        return Err(invalid());
+    }
+
+    let digits = U256::from_dec_str(&format!("{}{}", integer, fraction)).map_err(|_| invalid())?;
+    U256::from(10).checked_pow(U256::from(exponent - fraction.len() as u32))
+        .and_then(|scale| digits.checked_mul(scale))
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_token, Chain, Currency};
+
+    const RECIPIENT: &str = "0x742d35Cc6634c0532925A3B8d4c9DB96Dfbbb8B2";
+    const USDT: &str = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
+
+    #[test]
+    fn test_native_payment_uri() {
+        let uri = PaymentUri::for_payment(&test_token(Chain::Ethereum, Currency::ETH, None, 18), 5, &RECIPIENT.to_lowercase(), Decimal::new(15, 1), None).unwrap();
+
+        assert_eq!(uri.to_string(), format!("ethereum:{}@5?value=1500000000000000000", RECIPIENT));
+    }
+
+    #[test]
+    fn test_token_payment_uri() {
+        let uri = PaymentUri::for_payment(&test_token(Chain::Ethereum, Currency::USDT, Some(USDT), 6), 56, RECIPIENT, Decimal::new(1050, 2), None).unwrap();
+
+        assert_eq!(
+            uri.to_string(),
+            format!("ethereum:{}@56/transfer?address={}&uint256=10500000&gasLimit={}", USDT, RECIPIENT, TOKEN_TRANSFER_GAS_LIMIT)
+        );
+    }
+
+    #[test]
+    fn test_contract_payment_uri() {
+        let eth = test_token(Chain::Ethereum, Currency::ETH, None, 18);
+        let signature = format!("0x{}1b", "ab".repeat(64));
+        let mut order = ContractOrderArgs {
+            merchant_id: Uuid::parse_str("00000000-0000-0000-0000-0000000000ff").unwrap(),
+            order_id: "42",
+            signature: Some(&signature),
+        };
+        let uri = PaymentUri::for_payment(&eth, 137, RECIPIENT, Decimal::ONE, Some(order)).unwrap();
+
+        assert_eq!(
+            uri.to_string(),
+            format!("ethereum:{}@137/receivePayment?uint256=255&uint256=42&uint256=1000000000000000000&bytes={}&value=1000000000000000000&gasLimit={}",
+                RECIPIENT, signature, RECEIVE_PAYMENT_GAS_LIMIT)
+        );
+
+        // 未签名的合约订单无法生成支付链接
+        order.signature = None;
+        assert!(PaymentUri::for_payment(&eth, 137, RECIPIENT, Decimal::ONE, Some(order)).is_err());
+    }
+
+    #[test]
+    fn test_payment_uri_round_trip() {
+        let recipient: Address = RECIPIENT.parse().unwrap();
+        let uris = vec![
+            PaymentUri::native_transfer(recipient, 1, U256::exp10(18)),
+            PaymentUri::native_transfer(recipient, 1, U256::zero()).with_gas_price(U256::from(30_000_000_000u64)),
+            PaymentUri::token_transfer(USDT.parse().unwrap(), 8453, recipient, U256::from(1_000_000)).with_gas_limit(65_000),
+            PaymentUri::contract_payment(recipient, 42161, U256::from(u128::MAX), U256::MAX, U256::from(1), &Bytes::from(vec![0xab; 65]))
+                .with_gas_limit(RECEIVE_PAYMENT_GAS_LIMIT)
+                .with_gas_price(U256::from(100)),
+        ];
+
+        for uri in uris {
+            assert_eq!(uri.to_string().parse::<PaymentUri>().unwrap(), uri);
+        }
+    }
+
+    #[test]
+    fn test_payment_uri_calldata() {
+        use ethers::abi::AbiEncode;
+        use crate::utils::{encode_erc20_transfer, wopay::ReceivePaymentCall};
+
+        let recipient: Address = RECIPIENT.parse().unwrap();
+
+        let native = PaymentUri::native_transfer(recipient, 1, U256::exp10(18));
+        assert!(native.calldata().unwrap().is_empty());
+
+        let transfer = PaymentUri::token_transfer(USDT.parse().unwrap(), 1, recipient, U256::from(10_500_000));
+        assert_eq!(transfer.calldata().unwrap(), encode_erc20_transfer(recipient, U256::from(10_500_000)));
+
+        let signature = Bytes::from(vec![0xab; 65]);
+        let payment = PaymentUri::contract_payment(recipient, 1, U256::from(255), U256::from(42), U256::exp10(18), &signature);
+        let expected = ReceivePaymentCall {
+            merchant_id: U256::from(255),
+            order_id: U256::from(42),
+            amount: U256::exp10(18),
+            signature,
+        }.encode();
+        assert_eq!(payment.calldata().unwrap().to_vec(), expected);
+
+        let invalid: PaymentUri = format!("ethereum:{}/transfer?address=0x123", RECIPIENT).parse().unwrap();
+        assert!(invalid.calldata().is_err());
+    }
+
+    #[test]
+    fn test_parse_payment_uri() {
+        let uri: PaymentUri = format!("ethereum:pay-{}@1?value=2.014e18&gas=21000", RECIPIENT).parse().unwrap();
+        assert_eq!(uri.target, RECIPIENT.parse().unwrap());
+        assert_eq!(uri.chain_id, Some(1));
+        assert_eq!(uri.function, None);
+        assert_eq!(uri.value, Some(U256::from(2_014_000_000_000_000_000u64)));
+        assert_eq!(uri.gas_limit, Some(21000));
+
+        let uri: PaymentUri = format!("ethereum:{}", RECIPIENT).parse().unwrap();
+        assert_eq!(uri.chain_id, None);
+        assert_eq!(uri.value, None);
+
+        let invalid_uris = vec![
+            format!("bitcoin:{}", RECIPIENT),
+            "ethereum:0x123".to_string(),
+            "ethereum:wopay.eth?value=1".to_string(),
+            format!("ethereum:{}@mainnet", RECIPIENT),
+            format!("ethereum:{}?value=1.5", RECIPIENT),
+            format!("ethereum:{}?value=-1", RECIPIENT),
+            format!("ethereum:{}/transfer()?uint256=1", RECIPIENT),
+            format!("ethereum:{}?value", RECIPIENT),
+        ];
+
+        for uri in invalid_uris {
+            assert!(uri.parse::<PaymentUri>().is_err(), "URI should be invalid: {}", uri);
+        }
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("100").unwrap(), U256::from(100));
+        assert_eq!(parse_number("1e3").unwrap(), U256::from(1000));
+        assert_eq!(parse_number("1.5E1").unwrap(), U256::from(15));
+        assert!(parse_number("1.25e1").is_err());
+        assert!(parse_number("").is_err());
+        assert!(parse_number("e18").is_err());
+        assert!(parse_number("1e100").is_err());
+    }
+}
@@ -0,0 +1,170 @@
+// ERC20代币工具函数
+// 构造与解析 transfer / balanceOf 调用数据、解析Transfer事件，不依赖合约ABI文件
+
+use ethers::abi::{self, ParamType, Token};
+use ethers::types::{Address, Bytes, Log, H256, U256};
+
+/// `transfer(address,uint256)` 函数选择器
+pub const ERC20_TRANSFER_SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+
+/// `balanceOf(address)` 函数选择器
+pub const ERC20_BALANCE_OF_SELECTOR: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+
+/// `Transfer(address,address,uint256)` 事件签名 (topic0)
+pub const ERC20_TRANSFER_EVENT: H256 = H256([
+    0xdd, 0xf2, 0x52, 0xad, 0x1b, 0xe2, 0xc8, 0x9b, 0x69, 0xc2, 0xb0, 0x68, 0xfc, 0x37, 0x8d, 0xaa,
+    0x95, 0x2b, 0xa7, 0xf1, 0x63, 0xc4, 0xa1, 0x16, 0x28, 0xf5, 0x5a, 0x4d, 0xf5, 0x23, 0xb3, 0xef,
+]);
+
+/// 解析后的ERC20 Transfer事件
+#[derive(Debug, Clone, PartialEq)]
+pub struct Erc20Transfer {
+    /// 发送方 (topic1)
+    pub from: Address,
+    /// 接收方 (topic2)
+    pub to: Address,
+    /// 转账数量 (代币最小单位)
+    pub amount: U256,
+}
+
+/// 构造ERC20 `transfer` 调用数据
+///
+/// # Arguments
+/// * `to` - 收款地址
+/// * `amount` - 转账数量 (代币最小单位)
+///
+/// # Returns
+/// * 交易data字段
+pub fn encode_erc20_transfer(to: Address, amount: U256) -> Bytes {
+    encode_call(ERC20_TRANSFER_SELECTOR, &[Token::Address(to), Token::Uint(amount)])
+}
+
+/// 构造ERC20 `balanceOf` 调用数据
+///
+/// # Arguments
+/// * `owner` - 查询地址
+///
+/// # Returns
+/// * eth_call的data字段
+pub fn encode_erc20_balance_of(owner: Address) -> Bytes {
+    encode_call(ERC20_BALANCE_OF_SELECTOR, &[Token::Address(owner)])
+}
+
+/// 解析ERC20 `transfer` 调用数据
+///
+/// # Arguments
+/// * `data` - 交易input字段
+///
+/// # Returns
+/// * 收款地址与转账数量，不是 `transfer` 调用时返回None
+pub fn decode_erc20_transfer_call(data: &[u8]) -> Option<(Address, U256)> {
+    if data.len() < 4 || data[..4] != ERC20_TRANSFER_SELECTOR {
+        return None;
+    }
+
+    let mut tokens = abi::decode(&[ParamType::Address, ParamType::Uint(256)], &data[4..]).ok()?.into_iter();
+    match (tokens.next()?, tokens.next()?) {
+        (Token::Address(to), Token::Uint(amount)) => Some((to, amount)),
+        _ => None,
+    }
+}
+
+/// 解析ERC20 `Transfer` 事件日志
+///
+/// # Arguments
+/// * `log` - 事件日志
+///
+/// # Returns
+/// * 解析结果，不是标准Transfer事件时返回None
+pub fn decode_erc20_transfer_log(log: &Log) -> Option<Erc20Transfer> {
+    // ERC721的Transfer事件tokenId在topic3中，topics长度为4
+    if log.topics.len() != 3 || log.topics[0] != ERC20_TRANSFER_EVENT || log.data.len() < 32 {
+        return None;
+    }
+
+    Some(Erc20Transfer {
+        from: Address::from(log.topics[1]),
+        to: Address::from(log.topics[2]),
+        amount: U256::from_big_endian(&log.data[..32]),
+    })
+}
+
+fn encode_call(selector: [u8; 4], args: &[Token]) -> Bytes {
+    let mut data = selector.to_vec();
+    data.extend(abi::encode(args));
+    data.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::utils::id;
+
+    #[test]
+    fn test_selectors() {
+        assert_eq!(id("transfer(address,uint256)"), ERC20_TRANSFER_SELECTOR);
+        assert_eq!(id("balanceOf(address)"), ERC20_BALANCE_OF_SELECTOR);
+    }
+
+    #[test]
+    fn test_transfer_event_signature() {
+        assert_eq!(id("Transfer(address,address,uint256)"), ERC20_TRANSFER_EVENT.0[..4]);
+        assert_eq!(ethers::utils::keccak256("Transfer(address,address,uint256)"), ERC20_TRANSFER_EVENT.0);
+    }
+
+    #[test]
+    fn test_decode_transfer_log() {
+        let from: Address = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".parse().unwrap();
+        let to: Address = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse().unwrap();
+
+        let mut log = Log {
+            topics: vec![ERC20_TRANSFER_EVENT, H256::from(from), H256::from(to)],
+            data: abi::encode(&[Token::Uint(U256::from(2_500_000u64))]).into(),
+            ..Default::default()
+        };
+
+        let transfer = decode_erc20_transfer_log(&log).unwrap();
+        assert_eq!(transfer, Erc20Transfer { from, to, amount: U256::from(2_500_000u64) });
+
+        // ERC721 Transfer带有indexed tokenId
+        log.topics.push(H256::from_low_u64_be(1));
+        assert!(decode_erc20_transfer_log(&log).is_none());
+    }
+
+    #[test]
+    fn test_encode_transfer() {
+        let to: Address = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse().unwrap();
+        let data = encode_erc20_transfer(to, U256::from(1_000_000u64));
+
+        assert_eq!(data.len(), 4 + 32 * 2);
+        assert_eq!(
+            hex::encode(&data),
+            concat!(
+                "a9059cbb",
+                "00000000000000000000000070997970c51812dc3a010c7d01b50e0d17dc79c8",
+                "00000000000000000000000000000000000000000000000000000000000f4240",
+            )
+        );
+    }
+
+    #[test]
+    fn test_decode_transfer_call() {
+        let to: Address = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse().unwrap();
+        let data = encode_erc20_transfer(to, U256::from(1_000_000u64));
+
+        assert_eq!(decode_erc20_transfer_call(&data), Some((to, U256::from(1_000_000u64))));
+        assert!(decode_erc20_transfer_call(&encode_erc20_balance_of(to)).is_none());
+        assert!(decode_erc20_transfer_call(&data[..36]).is_none());
+    }
+
+    #[test]
+    fn test_encode_balance_of() {
+        let owner: Address = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".parse().unwrap();
+        let data = encode_erc20_balance_of(owner);
+
+        assert_eq!(
+            hex::encode(&data),
+            "70a08231000000000000000000000000f39fd6e51aad88f6f4ce6ab8827279cfffb92266"
+        );
+    }
+}
@@ -0,0 +1,269 @@
+// HD钱包工具函数
+// 基于BIP32/BIP39/BIP44标准派生以太坊地址，保证地址可在MetaMask/Ledger等标准钱包中恢复
+
+use anyhow::{Result, Context};
+use coins_bip32::prelude::{Hint, MainnetEncoder, Parent, SigningKey, VerifyingKey, XKeyEncoder, XKeyInfo, XPriv, XPub};
+use coins_bip39::{English, Mnemonic};
+use ethers::types::Address;
+use ethers::utils::{public_key_to_address, secret_key_to_address};
+
+/// 以太坊BIP44外部链派生路径 (m/44'/60'/0'/0)
+///
+/// 第 `index` 个支付地址的完整路径为 `m/44'/60'/0'/0/{index}`
+pub const ETHEREUM_DERIVATION_PATH: &str = "m/44'/60'/0'/0";
+
+/// 非硬化派生的最大索引 (2^31 - 1)
+pub const MAX_ADDRESS_INDEX: u32 = 0x7FFF_FFFF;
+
+/// 解析HD主密钥
+///
+/// # Arguments
+/// * `master_key` - BIP39助记词或Base58编码的xprv扩展私钥
+/// * `passphrase` - BIP39助记词密码 (可选，仅对助记词有效)
+///
+/// # Returns
+/// * BIP32根扩展私钥
+pub fn parse_master_key(master_key: &str, passphrase: Option<&str>) -> Result<XPriv> {
+    let master_key = master_key.trim();
+
+    if master_key.starts_with("xprv") {
+        return MainnetEncoder::xpriv_from_base58(master_key)
+            .map_err(|e| anyhow::anyhow!("Invalid xprv extended key: {}", e));
+    }
+
+    let mnemonic = Mnemonic::<English>::new_from_phrase(master_key)
+        .map_err(|e| anyhow::anyhow!("Invalid BIP39 mnemonic: {}", e))?;
+
+    let seed = mnemonic.to_seed(passphrase)
+        .map_err(|e| anyhow::anyhow!("Failed to derive seed from mnemonic: {}", e))?;
+
+    // 使用Legacy编码，保证导出的扩展密钥为标准的xprv/xpub格式
+    XPriv::root_from_seed(&seed, Some(Hint::Legacy))
+        .map_err(|e| anyhow::anyhow!("Failed to derive master key from seed: {}", e))
+}
+
+/// 派生以太坊外部链扩展私钥 (m/44'/60'/0'/0)
+///
+/// # Arguments
+/// * `master` - BIP32根扩展私钥
+///
+/// # Returns
+/// * 外部链扩展私钥，可直接用于按索引派生支付地址
+pub fn derive_account_xpriv(master: &XPriv) -> Result<XPriv> {
+    master.derive_path(ETHEREUM_DERIVATION_PATH)
+        .map_err(|e| anyhow::anyhow!("Failed to derive {}: {}", ETHEREUM_DERIVATION_PATH, e))
+}
+
+/// 派生指定索引的支付地址私钥
+///
+/// # Arguments
+/// * `account` - 外部链扩展私钥 (m/44'/60'/0'/0)
+/// * `index` - 地址索引
+///
+/// # Returns
+/// * 路径 `m/44'/60'/0'/0/{index}` 对应的签名私钥
+pub fn derive_signing_key(account: &XPriv, index: u32) -> Result<SigningKey> {
+    if index > MAX_ADDRESS_INDEX {
+        anyhow::bail!("Address index {} exceeds non-hardened range", index);
+    }
+
+    let child = account.derive_child(index)
+        .map_err(|e| anyhow::anyhow!("Failed to derive child key {}: {}", index, e))?;
+
+    let signing_key: &SigningKey = child.as_ref();
+    Ok(signing_key.clone())
+}
+
+/// 解析外部链扩展公钥 (观察钱包模式)
+///
+/// 支持账户级 (m/44'/60'/0') 或外部链级 (m/44'/60'/0'/0) 的xpub，
+/// 账户级xpub会自动派生到外部链
+///
+/// # Arguments
+/// * `xpub` - Base58编码的xpub扩展公钥
+///
+/// # Returns
+/// * 外部链扩展公钥
+pub fn parse_account_xpub(xpub: &str) -> Result<XPub> {
+    let key = MainnetEncoder::xpub_from_base58(xpub.trim())
+        .map_err(|e| anyhow::anyhow!("Invalid xpub extended key: {}", e))?;
+
+    let info: &XKeyInfo = key.as_ref();
+    match info.depth {
+        3 => key.derive_child(0)
+            .map_err(|e| anyhow::anyhow!("Failed to derive external chain from xpub: {}", e)),
+        4 => Ok(key),
+        depth => anyhow::bail!(
+            "Unsupported xpub depth {} (expected m/44'/60'/0' or m/44'/60'/0'/0)", depth
+        ),
+    }
+}
+
+/// 获取外部链扩展私钥对应的扩展公钥
+pub fn account_xpub(account: &XPriv) -> XPub {
+    account.verify_key()
+}
+
+/// 使用扩展公钥派生指定索引的支付地址 (无需私钥)
+///
+/// # Arguments
+/// * `account` - 外部链扩展公钥 (m/44'/60'/0'/0)
+/// * `index` - 地址索引
+///
+/// # Returns
+/// * 路径 `m/44'/60'/0'/0/{index}` 对应的以太坊地址
+pub fn derive_address_from_xpub(account: &XPub, index: u32) -> Result<Address> {
+    if index > MAX_ADDRESS_INDEX {
+        anyhow::bail!("Address index {} exceeds non-hardened range", index);
+    }
+
+    let child = account.derive_child(index)
+        .map_err(|e| anyhow::anyhow!("Failed to derive child public key {}: {}", index, e))?;
+
+    let verifying_key: &VerifyingKey = child.as_ref();
+    Ok(public_key_to_address(verifying_key))
+}
+
+/// 获取私钥对应的以太坊地址
+pub fn signing_key_address(key: &SigningKey) -> Address {
+    secret_key_to_address(key)
+}
+
+/// 获取派生路径字符串
+pub fn derivation_path(index: u32) -> String {
+    format!("{}/{}", ETHEREUM_DERIVATION_PATH, index)
+}
+
+/// 将扩展私钥编码为Base58字符串 (xprv...)
+pub fn xpriv_to_base58(key: &XPriv) -> Result<String> {
+    MainnetEncoder::xpriv_to_base58(key)
+        .map_err(|e| anyhow::anyhow!("Failed to encode xprv: {}", e))
+        .context("Extended key serialization failed")
+}
+
+/// 将扩展公钥编码为Base58字符串 (xpub...)
+pub fn xpub_to_base58(key: &XPub) -> Result<String> {
+    MainnetEncoder::xpub_to_base58(key)
+        .map_err(|e| anyhow::anyhow!("Failed to encode xpub: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hardhat/Anvil默认测试助记词
+    const TEST_MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+    #[test]
+    fn test_bip32_vector_1() {
+        // BIP32 Test vector 1, seed: 000102030405060708090a0b0c0d0e0f
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = XPriv::root_from_seed(&seed, Some(Hint::Legacy)).unwrap();
+
+        assert_eq!(
+            xpriv_to_base58(&master).unwrap(),
+            "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi"
+        );
+
+        let child = master.derive_path("m/0'/1/2'/2/1000000000").unwrap();
+        assert_eq!(
+            xpriv_to_base58(&child).unwrap(),
+            "xprvA41z7zogVVwxVSgdKUHDy1SKmdb533PjDz7J6N6mV6uS3ze1ai8FHa8kmHScGpWmj4WggLyQjgPie1rFSruoUihUZREPSL39UNdE3BBDu76"
+        );
+    }
+
+    #[test]
+    fn test_bip32_vector_2() {
+        // BIP32 Test vector 2
+        let seed = hex::decode(
+            "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a29f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542"
+        ).unwrap();
+        let master = XPriv::root_from_seed(&seed, Some(Hint::Legacy)).unwrap();
+
+        assert_eq!(
+            xpriv_to_base58(&master).unwrap(),
+            "xprv9s21ZrQH143K31xYSDQpPDxsXRTUcvj2iNHm5NUtrGiGG5e2DtALGdso3pGz6ssrdK4PFmM8NSpSBHNqPqm55Qn3LqFtT2emdEXVYsCzC2U"
+        );
+
+        let child = master.derive_path("m/0/2147483647'/1/2147483646'/2").unwrap();
+        assert_eq!(
+            xpriv_to_base58(&child).unwrap(),
+            "xprvA2nrNbFZABcdryreWet9Ea4LvTJcGsqrMzxHx98MMrotbir7yrKCEXw7nadnHM8Dq38EGfSh6dqA9QWTyefMLEcBYJUuekgW4BYPJcr9E7j"
+        );
+    }
+
+    #[test]
+    fn test_bip44_ethereum_addresses() {
+        // 与MetaMask/Anvil派生结果一致
+        let master = parse_master_key(TEST_MNEMONIC, None).unwrap();
+        let account = derive_account_xpriv(&master).unwrap();
+
+        let address_0 = signing_key_address(&derive_signing_key(&account, 0).unwrap());
+        let address_1 = signing_key_address(&derive_signing_key(&account, 1).unwrap());
+
+        assert_eq!(
+            address_0,
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".parse::<Address>().unwrap()
+        );
+        assert_eq!(
+            address_1,
+            "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse::<Address>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_master_key_from_xprv() {
+        let from_mnemonic = parse_master_key(TEST_MNEMONIC, None).unwrap();
+        let encoded = xpriv_to_base58(&from_mnemonic).unwrap();
+        let from_xprv = parse_master_key(&encoded, None).unwrap();
+
+        assert_eq!(from_mnemonic, from_xprv);
+    }
+
+    #[test]
+    fn test_xpub_derivation_matches_private_derivation() {
+        let master = parse_master_key(TEST_MNEMONIC, None).unwrap();
+        let account = derive_account_xpriv(&master).unwrap();
+        let xpub = account_xpub(&account);
+
+        for index in [0, 1, 42] {
+            let from_private = signing_key_address(&derive_signing_key(&account, index).unwrap());
+            let from_public = derive_address_from_xpub(&xpub, index).unwrap();
+            assert_eq!(from_private, from_public);
+        }
+    }
+
+    #[test]
+    fn test_parse_account_xpub_depths() {
+        let master = parse_master_key(TEST_MNEMONIC, None).unwrap();
+        let expected = derive_account_xpriv(&master).unwrap().verify_key();
+
+        // 外部链级xpub (m/44'/60'/0'/0)
+        let chain_xpub = xpub_to_base58(&expected).unwrap();
+        assert_eq!(parse_account_xpub(&chain_xpub).unwrap(), expected);
+
+        // 账户级xpub (m/44'/60'/0')
+        let account_level = master.derive_path("m/44'/60'/0'").unwrap().verify_key();
+        let account_xpub = xpub_to_base58(&account_level).unwrap();
+        assert_eq!(parse_account_xpub(&account_xpub).unwrap(), expected);
+
+        // 根xpub不支持
+        let root_xpub = xpub_to_base58(&master.verify_key()).unwrap();
+        assert!(parse_account_xpub(&root_xpub).is_err());
+    }
+
+    #[test]
+    fn test_invalid_master_key() {
+        assert!(parse_master_key("not a valid mnemonic", None).is_err());
+        assert!(parse_master_key("xprvInvalid", None).is_err());
+    }
+
+    #[test]
+    fn test_index_out_of_range() {
+        let master = parse_master_key(TEST_MNEMONIC, None).unwrap();
+        let account = derive_account_xpriv(&master).unwrap();
+
+        assert!(derive_signing_key(&account, MAX_ADDRESS_INDEX + 1).is_err());
+        assert_eq!(derivation_path(5), "m/44'/60'/0'/0/5");
+    }
+}
@@ -5,9 +5,17 @@ pub mod crypto;
 pub mod auth;
 pub mod qr;
 pub mod validation;
+pub mod hd_wallet;
+pub mod erc20;
+pub mod wopay;
+pub mod eip681;
 
 // 重新导出常用函数
 pub use crypto::*;
 pub use auth::*;
 pub use qr::*;
 pub use validation::*;
+pub use hd_wallet::*;
+pub use erc20::*;
+pub use wopay::{Wopay, PaymentReceivedFilter, contract_merchant_id, sign_contract_payment};
+pub use eip681::{PaymentUri, ContractOrderArgs};
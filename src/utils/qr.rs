@@ -5,6 +5,7 @@ use qrcode::QrCode;
 use image::{ImageBuffer, Luma};
 use base64;
 use anyhow::{Result, Context};
+use super::PaymentUri;
 
 /// 生成支付二维码
 /// 
@@ -124,29 +125,9 @@ pub fn validate_payment_qr_content(content: &str) -> bool {
     false
 }
 
-/// 验证Ethereum支付URL格式
+/// 验证Ethereum支付URL格式 (EIP-681)
 fn validate_ethereum_payment_url(url: &str) -> bool {
-    // 基础格式: ethereum:0x...?value=...
-    if !url.starts_with("ethereum:0x") {
-        return false;
-    }
-    
-    // 提取地址部分
-    let parts: Vec<&str> = url.split('?').collect();
-    if parts.len() != 2 {
-        return false;
-    }
-    
-    let address_part = parts[0];
-    let address = &address_part[9..]; // 去掉 "ethereum:" 前缀
-    
-    // 验证以太坊地址格式 (42字符，以0x开头)
-    if address.len() != 42 || !address.starts_with("0x") {
-        return false;
-    }
-    
-    // 验证地址是否为有效的十六进制
-    address[2..].chars().all(|c| c.is_ascii_hexdigit())
+    url.parse::<PaymentUri>().is_ok()
 }
 
 /// 验证比特币支付URL格式 (预留功能)
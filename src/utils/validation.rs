@@ -5,6 +5,7 @@ use regex::Regex;
 use rust_decimal::Decimal;
 use anyhow::{Result, Context};
 use std::collections::HashMap;
+use crate::models::Token;
 
 /// 验证以太坊地址格式
 /// 
@@ -75,48 +76,29 @@ pub fn validate_url(url: &str) -> bool {
 /// 
 /// # Arguments
 /// * `amount` - 支付金额
-/// * `currency` - 币种
+/// * `token` - 收款代币 (精度与金额限制取自代币登记)
 /// 
 /// # Returns
 /// * 金额是否有效
-pub fn validate_payment_amount(amount: &Decimal, currency: &str) -> Result<()> {
+pub fn validate_payment_amount(amount: &Decimal, token: &Token) -> Result<()> {
     // 检查金额是否为正数
     if *amount <= Decimal::ZERO {
         anyhow::bail!("Payment amount must be positive");
     }
 
     // 检查金额精度
-    let scale = amount.scale();
-    let max_scale = match currency {
-        "ETH" => 18,
-        "USDT" => 6,
-        _ => 18, // 默认18位精度
-    };
-
-    if scale > max_scale {
-        anyhow::bail!("Amount precision too high for currency {}", currency);
+    if amount.normalize().scale() > token.decimals() {
+        anyhow::bail!("Amount precision too high for currency {}", token.symbol);
     }
 
     // 检查最小金额限制
-    let min_amount = match currency {
-        "ETH" => Decimal::new(1, 4), // 0.0001 ETH
-        "USDT" => Decimal::new(1, 2), // 0.01 USDT
-        _ => Decimal::new(1, 4),
-    };
-
-    if *amount < min_amount {
-        anyhow::bail!("Amount too small for currency {}", currency);
+    if *amount < token.min_amount {
+        anyhow::bail!("Amount too small for currency {}", token.symbol);
     }
 
     // 检查最大金额限制 (防止意外的大额交易)
-    let max_amount = match currency {
-        "ETH" => Decimal::new(1000, 0), // 1000 ETH
-        "USDT" => Decimal::new(1000000, 0), // 1,000,000 USDT
-        _ => Decimal::new(1000, 0),
-    };
-
-    if *amount > max_amount {
-        anyhow::bail!("Amount too large for currency {}", currency);
+    if *amount > token.max_amount {
+        anyhow::bail!("Amount too large for currency {}", token.symbol);
     }
 
     Ok(())
@@ -306,6 +288,7 @@ impl Default for InputValidator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::{test_token, Chain, Currency};
 
     #[test]
     fn test_validate_ethereum_address() {
@@ -343,14 +326,23 @@ mod tests {
 
     #[test]
     fn test_validate_payment_amount() {
+        let token = |symbol: Currency, decimals: i32, min_amount: Decimal, max_amount: Decimal| Token {
+            min_amount,
+            max_amount,
+            ..test_token(Chain::Ethereum, symbol, None, decimals)
+        };
+        let eth = token(Currency::ETH, 18, Decimal::new(1, 4), Decimal::new(1000, 0));
+        let usdt = token(Currency::USDT, 6, Decimal::new(1, 2), Decimal::new(1000000, 0));
+
         // 有效金额
-        assert!(validate_payment_amount(&Decimal::new(1, 0), "ETH").is_ok()); // 1 ETH
-        assert!(validate_payment_amount(&Decimal::new(100, 2), "USDT").is_ok()); // 1.00 USDT
+        assert!(validate_payment_amount(&Decimal::new(1, 0), &eth).is_ok()); // 1 ETH
+        assert!(validate_payment_amount(&Decimal::new(100, 2), &usdt).is_ok()); // 1.00 USDT
         
         // 无效金额
-        assert!(validate_payment_amount(&Decimal::ZERO, "ETH").is_err()); // 零金额
-        assert!(validate_payment_amount(&Decimal::new(-1, 0), "ETH").is_err()); // 负金额
-        assert!(validate_payment_amount(&Decimal::new(10000, 0), "ETH").is_err()); // 金额过大
+        assert!(validate_payment_amount(&Decimal::ZERO, &eth).is_err()); // 零金额
+        assert!(validate_payment_amount(&Decimal::new(-1, 0), &eth).is_err()); // 负金额
+        assert!(validate_payment_amount(&Decimal::new(10000, 0), &eth).is_err()); // 金额过大
+        assert!(validate_payment_amount(&Decimal::new(1000001, 7), &usdt).is_err()); // 超出代币精度
     }
 
     #[test]
@@ -0,0 +1,211 @@
+// Wopay收款合约绑定
+// 由 `contract/Wopay.sol` 的ABI生成，修改合约接口时需同步更新
+
+use ethers::abi::{encode, Token};
+use ethers::contract::abigen;
+use ethers::signers::LocalWallet;
+use ethers::types::{Address, Bytes, H256, U256};
+use ethers::utils::{hash_message, keccak256};
+use anyhow::{Result, Context};
+use uuid::Uuid;
+
+abigen!(
+    Wopay,
+    r#"[
+        function owner() external view returns (address)
+        function paymentSigner() external view returns (address)
+        function receivePayment(uint256 merchantId, uint256 orderId, uint256 amount, bytes signature) external payable
+        function paymentHash(uint256 merchantId, uint256 orderId, uint256 amount) external view returns (bytes32)
+        function setPaymentSigner(address signer) external
+        function orderPaid(uint256 orderId) external view returns (bool)
+        function merchantBalance(uint256 merchantId) external view returns (uint256)
+        function merchantPayout(uint256 merchantId) external view returns (address)
+        function setMerchantPayout(uint256 merchantId, address payout) external
+        function withdraw(uint256 merchantId, uint256 amount) external
+        function transferOwnership(address newOwner) external
+        function balance() external view returns (uint256)
+        event PaymentReceived(uint256 indexed orderId, uint256 indexed merchantId, uint256 amount, address indexed sender)
+        event MerchantPayoutUpdated(uint256 indexed merchantId, address payout)
+        event Withdrawal(uint256 indexed merchantId, address indexed payout, uint256 amount)
+        event OwnershipTransferred(address indexed previousOwner, address indexed newOwner)
+        event PaymentSignerUpdated(address indexed signer)
+    ]"#
+);
+
+/// 商户在合约中的ID (商户UUID的128位整数形式)
+pub fn contract_merchant_id(merchant_id: Uuid) -> U256 {
+    U256::from(merchant_id.as_u128())
+}
+
+/// 合约收款订单的签名摘要 (与合约的 `paymentHash` 一致)
+///
+/// # Arguments
+/// * `contract` - 收款合约地址
+/// * `chain_id` - 合约所在链的链ID
+/// * `merchant_id` - 合约中的商户ID
+/// * `order_id` - 链上订单号
+/// * `amount` - 订单金额 (wei)
+pub fn contract_payment_hash(contract: Address, chain_id: u64, merchant_id: U256, order_id: U256, amount: U256) -> H256 {
+    H256(keccak256(encode(&[
+        Token::Address(contract),
+        Token::Uint(chain_id.into()),
+        Token::Uint(merchant_id),
+        Token::Uint(order_id),
+        Token::Uint(amount),
+    ])))
+}
+
+/// 签名合约收款订单 (EIP-191)
+///
+/// 付款时合约校验签名者为 `paymentSigner`，付款人无法改动商户ID、订单号或降低订单金额
+///
+/// # Returns
+/// * 65字节的签名 (r, s, v)
+pub fn sign_contract_payment(
+    signer: &LocalWallet,
+    contract: Address,
+    chain_id: u64,
+    merchant_id: U256,
+    order_id: U256,
+    amount: U256,
+) -> Result<Bytes> {
+    let hash = contract_payment_hash(contract, chain_id, merchant_id, order_id, amount);
+    let signature = signer.sign_hash(hash_message(hash))
+        .context("Failed to sign contract payment")?;
+    Ok(signature.to_vec().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::contract::EthEvent;
+    use ethers::signers::Signer;
+    use ethers::types::Signature;
+
+    #[test]
+    fn test_payment_received_signature() {
+        assert_eq!(PaymentReceivedFilter::abi_signature(), "PaymentReceived(uint256,uint256,uint256,address)");
+        assert_eq!(
+            PaymentReceivedFilter::signature().0,
+            keccak256("PaymentReceived(uint256,uint256,uint256,address)")
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires anvil, solc and the test database"]
+    async fn test_devnet_wopay_contract() {
+        use crate::devnet::Devnet;
+        use ethers::providers::Middleware;
+        use ethers::signers::Signer;
+
+        let devnet = Devnet::start().await;
+        let address = devnet.deploy("contract/Wopay.sol", "Wopay", 0).await;
+        let owner = Wopay::new(address, devnet.client(0));
+        let payer = Wopay::new(address, devnet.client(1));
+        let payout = devnet.wallet(2).address();
+        let payout_client = Wopay::new(address, devnet.client(2));
+
+        let merchant = contract_merchant_id(Uuid::new_v4());
+        let other_merchant = contract_merchant_id(Uuid::new_v4());
+        let one_ether = U256::exp10(18);
+        let chain_id = devnet.wallet(0).chain_id();
+        let sign = |signer: &LocalWallet, merchant_id: U256, order_id: u64, amount: U256| {
+            sign_contract_payment(signer, address, chain_id, merchant_id, U256::from(order_id), amount).unwrap()
+        };
+        let signer = devnet.wallet(0);
+
+        assert_eq!(owner.owner().call().await.unwrap(), devnet.wallet(0).address());
+        assert_eq!(owner.payment_signer().call().await.unwrap(), devnet.wallet(0).address());
+        assert_eq!(
+            H256(owner.payment_hash(merchant, U256::from(1), one_ether).call().await.unwrap()),
+            contract_payment_hash(address, chain_id, merchant, U256::from(1), one_ether)
+        );
+
+        // 付款计入商户余额，同一订单不能重复支付，金额必须大于0
+        payer.receive_payment(merchant, U256::from(1), one_ether, sign(&signer, merchant, 1, one_ether))
+            .value(one_ether).send().await.unwrap().await.unwrap();
+        payer.receive_payment(merchant, U256::from(2), one_ether, sign(&signer, merchant, 2, one_ether))
+            .value(one_ether).send().await.unwrap().await.unwrap();
+        assert!(payer.receive_payment(merchant, U256::from(1), one_ether, sign(&signer, merchant, 1, one_ether))
+            .value(one_ether).send().await.is_err());
+        assert!(payer.receive_payment(merchant, U256::from(3), U256::zero(), sign(&signer, merchant, 3, U256::zero()))
+            .send().await.is_err());
+
+        // 付款金额不得低于订单金额，商户ID、订单号与金额必须与签名一致，签名者必须为paymentSigner
+        let order_3 = sign(&signer, merchant, 3, one_ether);
+        assert!(payer.receive_payment(merchant, U256::from(3), one_ether, order_3.clone())
+            .value(one_ether - 1).send().await.is_err());
+        assert!(payer.receive_payment(other_merchant, U256::from(3), one_ether, order_3.clone())
+            .value(one_ether).send().await.is_err());
+        assert!(payer.receive_payment(merchant, U256::from(3), one_ether / 2, order_3.clone())
+            .value(one_ether).send().await.is_err());
+        assert!(payer.receive_payment(merchant, U256::from(3), one_ether, sign(&devnet.wallet(1), merchant, 3, one_ether))
+            .value(one_ether).send().await.is_err());
+        assert!(!owner.order_paid(U256::from(3)).call().await.unwrap());
+
+        assert!(owner.order_paid(U256::from(1)).call().await.unwrap());
+        assert_eq!(owner.merchant_balance(merchant).call().await.unwrap(), one_ether * 2);
+        assert_eq!(owner.balance().call().await.unwrap(), one_ether * 2);
+
+        let events = owner.payment_received_filter().from_block(0u64).query().await.unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].merchant_id, merchant);
+        assert_eq!(events[0].sender, devnet.wallet(1).address());
+
+        // 未登记提款地址时不能提款，只有管理者可以登记
+        assert!(owner.withdraw(merchant, one_ether).send().await.is_err());
+        assert!(payer.set_merchant_payout(merchant, devnet.wallet(1).address()).send().await.is_err());
+        owner.set_merchant_payout(merchant, payout).send().await.unwrap().await.unwrap();
+
+        // 管理者或提款地址可以提款，资金只转入提款地址
+        assert!(payer.withdraw(merchant, one_ether).send().await.is_err());
+        assert!(owner.withdraw(merchant, one_ether * 3).send().await.is_err());
+
+        let before = devnet.provider().get_balance(payout, None).await.unwrap();
+        owner.withdraw(merchant, one_ether).send().await.unwrap().await.unwrap();
+        assert_eq!(devnet.provider().get_balance(payout, None).await.unwrap() - before, one_ether);
+
+        payout_client.withdraw(merchant, one_ether).send().await.unwrap().await.unwrap();
+        assert_eq!(owner.merchant_balance(merchant).call().await.unwrap(), U256::zero());
+        assert_eq!(owner.balance().call().await.unwrap(), U256::zero());
+
+        // 只有管理者可以更换签名者，更换后旧签名失效
+        assert!(payer.set_payment_signer(devnet.wallet(1).address()).send().await.is_err());
+        owner.set_payment_signer(devnet.wallet(4).address()).send().await.unwrap().await.unwrap();
+        assert!(payer.receive_payment(merchant, U256::from(3), one_ether, order_3)
+            .value(one_ether).send().await.is_err());
+        payer.receive_payment(other_merchant, U256::from(3), one_ether, sign(&devnet.wallet(4), other_merchant, 3, one_ether))
+            .value(one_ether).send().await.unwrap().await.unwrap();
+        assert_eq!(owner.merchant_balance(other_merchant).call().await.unwrap(), one_ether);
+
+        // 管理者转移后原管理者失去权限
+        owner.transfer_ownership(devnet.wallet(3).address()).send().await.unwrap().await.unwrap();
+        assert!(owner.set_merchant_payout(merchant, payout).send().await.is_err());
+    }
+
+    #[test]
+    fn test_sign_contract_payment() {
+        let signer: LocalWallet = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318".parse().unwrap();
+        let contract = Address::repeat_byte(0xcc);
+        let hash = contract_payment_hash(contract, 1, U256::from(255), U256::from(42), U256::exp10(18));
+
+        let signature = sign_contract_payment(&signer, contract, 1, U256::from(255), U256::from(42), U256::exp10(18)).unwrap();
+        assert_eq!(signature.len(), 65);
+        assert!(signature[64] == 27 || signature[64] == 28);
+
+        let signature = Signature::try_from(signature.as_ref()).unwrap();
+        assert_eq!(signature.recover(hash_message(hash)).unwrap(), signer.address());
+
+        // 摘要绑定合约地址、链ID、商户ID、订单号与金额
+        assert_ne!(hash, contract_payment_hash(contract, 56, U256::from(255), U256::from(42), U256::exp10(18)));
+        assert_ne!(hash, contract_payment_hash(contract, 1, U256::from(256), U256::from(42), U256::exp10(18)));
+        assert_ne!(hash, contract_payment_hash(contract, 1, U256::from(255), U256::from(42), U256::exp10(17)));
+    }
+
+    #[test]
+    fn test_contract_merchant_id() {
+        let merchant_id = Uuid::parse_str("00000000-0000-0000-0000-0000000000ff").unwrap();
+        assert_eq!(contract_merchant_id(merchant_id), U256::from(255));
+        assert_eq!(contract_merchant_id(Uuid::max()), U256::from(u128::MAX));
+    }
+}